@@ -0,0 +1,87 @@
+// NOTE: since xan is only built as a binary, those benchmarks run the actual
+// executable on a generated file. They are mostly useful to track the cost of
+// evaluating moonblade expressions in the hottest commands. Run them with:
+// cargo bench --bench moonblade
+// Since timings also include process startup and CSV I/O, the tree walker
+// and the bytecode are compared in process by the bench_bytecode ignored test
+// of src/moonblade/bytecode.rs instead.
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use criterion::{criterion_group, criterion_main, Criterion};
+
+const ROWS: usize = 100_000;
+
+fn generate_file() -> PathBuf {
+    let path = env::temp_dir().join("xan-bench-moonblade.csv");
+
+    let mut wtr = csv::Writer::from_path(&path).unwrap();
+    wtr.write_record(["name", "count", "price"]).unwrap();
+
+    for i in 0..ROWS {
+        wtr.write_record([
+            format!("name{}", i % 1000),
+            (i % 100).to_string(),
+            format!("{}.5", i % 37),
+        ])
+        .unwrap();
+    }
+
+    wtr.flush().unwrap();
+
+    path
+}
+
+fn xan(path: &Path, args: &[&str]) {
+    let status = Command::new(env!("CARGO_BIN_EXE_xan"))
+        .args(args)
+        .arg(path)
+        .stdout(Stdio::null())
+        .status()
+        .unwrap();
+
+    assert!(status.success(), "xan {:?} failed", args);
+}
+
+fn bench_moonblade(c: &mut Criterion) {
+    let path = generate_file();
+
+    let cases: &[(&str, &[&str])] = &[
+        ("filter_compare", &["filter", "count > 50"]),
+        ("filter_bytes", &["filter", "name eq 'name45'"]),
+        ("filter_logic", &["filter", "count > 10 && price < 20"]),
+        ("map_arithmetic", &["map", "count + price", "total"]),
+        (
+            "map_call",
+            &["map", "if(count > 50, upper(name), name)", "label"],
+        ),
+        (
+            "agg",
+            &["agg", "sum(count), mean(price), count() where count > 10"],
+        ),
+        (
+            "groupby",
+            &[
+                "groupby",
+                "name",
+                "sum(count * 2), argmax(price, count + 1)",
+            ],
+        ),
+    ];
+
+    let mut group = c.benchmark_group("moonblade");
+    group.sample_size(10);
+
+    for (name, args) in cases {
+        group.bench_function(*name, |b| b.iter(|| xan(&path, args)));
+    }
+
+    group.finish();
+
+    fs::remove_file(path).unwrap();
+}
+
+criterion_group!(benches, bench_moonblade);
+criterion_main!(benches);
@@ -43,6 +43,16 @@ You can rename the output columns using the 'as' syntax:
 
     $ xan agg 'sum(n) as sum, max(replies_count) as \"Max Replies\"' file.csv
 
+You can restrict the rows considered by a single aggregation using a 'where'
+clause, which makes it possible to compute conditional aggregations in a single pass:
+
+    $ xan agg 'count() as total, count() where lang eq \"fr\" as french, mean(n) where n > 0 as m' file.csv
+
+You can apply the same aggregation to a selection of columns using a star slice,
+or the \"cols\" function, which will expand into one aggregation per column:
+
+    $ xan agg 'sum(*[count_a:count_z]), mean(cols(\"num_*\"))' file.csv
+
 For a quick review of the capabilities of the script language, use
 the --cheatsheet flag.
 
@@ -4,7 +4,7 @@ use std::path::PathBuf;
 
 use glob::glob;
 
-static COMMANDS: [&str; 58] = [
+static COMMANDS: [&str; 60] = [
     "agg",
     "behead",
     "bins",
@@ -38,10 +38,12 @@ static COMMANDS: [&str; 58] = [
     "join",
     "map",
     "matrix",
+    "melt",
     "merge",
     "network",
     "parallel",
     "partition",
+    "pivot",
     "plot",
     "progress",
     "range",
@@ -162,8 +164,10 @@ pub fn run() {
         || word_before == "transform"
         || word_before == "explode"
         || word_before == "implode"
+        || word_before == "melt"
         || word_before == "groupby"
         || word_before == "partition"
+        || word_before == "pivot"
         || word_before == "plot"
         || word_before == "top"
     {
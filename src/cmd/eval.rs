@@ -1,16 +1,32 @@
-use std::io::{self, Write};
+use std::io::{self, BufRead, IsTerminal, Write};
 
 use colored::Colorize;
+use console::{Key, Term};
 
-use crate::moonblade::Program;
+use crate::cmd::moonblade::MOONBLADE_FUNCTIONS_HELP;
+use crate::config::{Config, Delimiter};
+use crate::moonblade::{DynamicValue, Program};
 use crate::util;
 use crate::CliResult;
 
 static USAGE: &str = "
 Debug command that can be used to evaluate a moonblade expression.
 
+When given --repl, the command will instead start an interactive session
+where expressions are evaluated against the first rows of the given CSV
+file (or the pretend row given through -H/-R). The session supports history
+(arrow keys) and tab completion of function and column names. Lines starting
+with a colon are commands:
+
+    :explain <expr>  Print the concrete plan of the expression.
+    :help <name>     Print the documentation of the given function.
+    :headers         Print the headers of the sample.
+    :rows            Print the rows of the sample.
+    :quit            Exit the session (or use Ctrl-C/Ctrl-D).
+
 Usage:
     xan eval [options] <expr>
+    xan eval --repl [options] [<input>]
     xan eval --help
 
 eval options:
@@ -18,48 +34,69 @@ eval options:
     -e, --explain          Print concrete expression plan.
     -H, --headers <names>  Pretend headers, separated by commas, to consider.
     -R, --row <values>     Pretend row with comma-separated cells.
+    -i, --repl             Start an interactive session.
+    -l, --limit <n>        Number of rows of the input to use as sample in
+                           the interactive session. [default: 5]
 
 Common options:
-    -h, --help  Display this message
+    -h, --help             Display this message
+    -d, --delimiter <arg>  The field delimiter for reading CSV data.
+                           Must be a single character.
 ";
 
 #[derive(Deserialize)]
 struct Args {
-    arg_expr: String,
+    arg_expr: Option<String>,
+    arg_input: Option<String>,
     flag_serialize: bool,
     flag_explain: bool,
     flag_headers: Option<String>,
     flag_row: Option<String>,
+    flag_repl: bool,
+    flag_limit: usize,
+    flag_delimiter: Option<Delimiter>,
 }
 
-pub fn run(argv: &[&str]) -> CliResult<()> {
-    let args: Args = util::get_args(USAGE, argv)?;
+impl Args {
+    fn dummy_sample(&self) -> (csv::ByteRecord, csv::ByteRecord) {
+        let mut dummy_headers = csv::ByteRecord::new();
 
-    let mut dummy_headers = csv::ByteRecord::new();
+        if let Some(headers) = &self.flag_headers {
+            for h in headers.split(',') {
+                dummy_headers.push_field(h.as_bytes());
+            }
+        }
+
+        let mut dummy_row = csv::ByteRecord::new();
 
-    if let Some(headers) = &args.flag_headers {
-        for h in headers.split(',') {
-            dummy_headers.push_field(h.as_bytes());
+        if let Some(cells) = &self.flag_row {
+            for c in cells.split(',') {
+                dummy_row.push_field(c.as_bytes());
+            }
+        } else {
+            for _ in dummy_headers.iter() {
+                dummy_row.push_field(b"");
+            }
         }
+
+        (dummy_headers, dummy_row)
     }
+}
 
-    let program = Program::parse(&args.arg_expr, &dummy_headers)?;
+pub fn run(argv: &[&str]) -> CliResult<()> {
+    let args: Args = util::get_args(USAGE, argv)?;
 
-    if args.flag_explain {
-        println!("{}", "concrete plan".cyan());
-        println!("{:?}\n", program.expr);
+    if args.flag_repl {
+        return run_repl(args);
     }
 
-    let mut dummy_row = csv::ByteRecord::new();
+    let (dummy_headers, dummy_row) = args.dummy_sample();
 
-    if let Some(cells) = args.flag_row {
-        for c in cells.split(',') {
-            dummy_row.push_field(c.as_bytes());
-        }
-    } else if let Some(headers) = args.flag_headers {
-        for _ in headers.split(',') {
-            dummy_row.push_field(b"");
-        }
+    let program = Program::parse(args.arg_expr.as_ref().unwrap(), &dummy_headers)?;
+
+    if args.flag_explain {
+        println!("{}", "concrete plan".cyan());
+        println!("{:?}\n", program.expr);
     }
 
     let value = program.run_with_record(0, &dummy_row)?;
@@ -76,3 +113,399 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
 
     Ok(())
 }
+
+// Documented functions, as found in the --functions help, along with the
+// relevant portion of said help.
+fn get_function_docs() -> Vec<(&'static str, String)> {
+    let mut docs: Vec<(&'static str, String)> = Vec::new();
+
+    for line in MOONBLADE_FUNCTIONS_HELP.lines() {
+        if let Some(signature) = line.strip_prefix("    - ") {
+            if let Some((name, _)) = signature.split_once('(') {
+                docs.push((name, line.trim().to_string()));
+                continue;
+            }
+        }
+
+        if let Some((_, doc)) = docs.last_mut() {
+            if !doc.ends_with('\n') {
+                if line.trim().is_empty() {
+                    doc.push('\n');
+                } else {
+                    doc.push('\n');
+                    doc.push_str(line);
+                }
+            }
+        }
+    }
+
+    docs
+}
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+fn common_prefix<'a>(candidates: &[&'a str]) -> &'a str {
+    let first = candidates[0];
+    let mut end = first.len();
+
+    for candidate in candidates[1..].iter() {
+        end = end.min(
+            first
+                .char_indices()
+                .zip(candidate.chars())
+                .find(|((_, a), b)| a != b)
+                .map(|((i, _), _)| i)
+                .unwrap_or(first.len().min(candidate.len())),
+        );
+    }
+
+    &first[..end]
+}
+
+struct LineEditor {
+    term: Term,
+    history: Vec<String>,
+    candidates: Vec<String>,
+}
+
+impl LineEditor {
+    fn redraw(&self, prompt: &str, buffer: &[char], cursor: usize) -> io::Result<()> {
+        self.term.clear_line()?;
+        self.term
+            .write_str(&format!("{}{}", prompt, buffer.iter().collect::<String>()))?;
+
+        if cursor < buffer.len() {
+            self.term.move_cursor_left(buffer.len() - cursor)?;
+        }
+
+        Ok(())
+    }
+
+    fn complete(&self, buffer: &mut Vec<char>, cursor: &mut usize) -> io::Result<()> {
+        let start = buffer[..*cursor]
+            .iter()
+            .rposition(|c| !is_identifier_char(*c))
+            .map(|i| i + 1)
+            .unwrap_or(0);
+
+        let word = buffer[start..*cursor].iter().collect::<String>();
+
+        if word.is_empty() {
+            return Ok(());
+        }
+
+        let matches = self
+            .candidates
+            .iter()
+            .filter(|candidate| candidate.starts_with(&word))
+            .map(|candidate| candidate.as_str())
+            .collect::<Vec<_>>();
+
+        if matches.is_empty() {
+            return Ok(());
+        }
+
+        let completion = common_prefix(&matches);
+
+        for c in completion[word.len()..].chars() {
+            buffer.insert(*cursor, c);
+            *cursor += 1;
+        }
+
+        if matches.len() > 1 && completion == word {
+            self.term.write_line("")?;
+            self.term
+                .write_line(&matches.join("  ").dimmed().to_string())?;
+        }
+
+        Ok(())
+    }
+
+    // NOTE: returns None when the user wants to quit the session
+    fn read_line(&mut self, prompt: &str) -> io::Result<Option<String>> {
+        let mut buffer: Vec<char> = Vec::new();
+        let mut cursor: usize = 0;
+        let mut history_index = self.history.len();
+
+        self.term.write_str(prompt)?;
+
+        loop {
+            match self.term.read_key()? {
+                Key::Enter => {
+                    self.term.write_line("")?;
+                    break;
+                }
+                Key::CtrlC | Key::Char('\u{4}') => {
+                    self.term.write_line("")?;
+                    return Ok(None);
+                }
+                Key::Char(c) if !c.is_control() => {
+                    buffer.insert(cursor, c);
+                    cursor += 1;
+                }
+                Key::Backspace if cursor > 0 => {
+                    cursor -= 1;
+                    buffer.remove(cursor);
+                }
+                Key::Del if cursor < buffer.len() => {
+                    buffer.remove(cursor);
+                }
+                Key::ArrowLeft if cursor > 0 => cursor -= 1,
+                Key::ArrowRight if cursor < buffer.len() => cursor += 1,
+                Key::Home => cursor = 0,
+                Key::End => cursor = buffer.len(),
+                Key::ArrowUp if history_index > 0 => {
+                    history_index -= 1;
+                    buffer = self.history[history_index].chars().collect();
+                    cursor = buffer.len();
+                }
+                Key::ArrowDown if history_index < self.history.len() => {
+                    history_index += 1;
+                    buffer = self
+                        .history
+                        .get(history_index)
+                        .map(|line| line.chars().collect())
+                        .unwrap_or_default();
+                    cursor = buffer.len();
+                }
+                Key::Tab => {
+                    self.complete(&mut buffer, &mut cursor)?;
+                }
+                _ => continue,
+            }
+
+            self.redraw(prompt, &buffer, cursor)?;
+        }
+
+        let line = buffer.into_iter().collect::<String>();
+
+        if !line.trim().is_empty() && self.history.last() != Some(&line) {
+            self.history.push(line.clone());
+        }
+
+        Ok(Some(line))
+    }
+}
+
+struct Session {
+    headers: csv::ByteRecord,
+    rows: Vec<csv::ByteRecord>,
+    docs: Vec<(&'static str, String)>,
+}
+
+impl Session {
+    fn print_value(&self, index: usize, value: &DynamicValue) {
+        println!(
+            "{} {:?} {}",
+            format!("{}:", index).dimmed(),
+            value,
+            format!("<{}>", value.type_of()).dimmed()
+        );
+    }
+
+    fn evaluate(&self, code: &str) {
+        let program = match Program::parse(code, &self.headers) {
+            Err(err) => {
+                eprintln!("{}", err.to_string().red());
+                return;
+            }
+            Ok(program) => program,
+        };
+
+        for (index, row) in self.rows.iter().enumerate() {
+            match program.run_with_record(index, row) {
+                Ok(value) => self.print_value(index, &value),
+                Err(err) => eprintln!(
+                    "{} {}",
+                    format!("{}:", index).dimmed(),
+                    err.to_string().red()
+                ),
+            }
+        }
+    }
+
+    fn explain(&self, code: &str) {
+        match Program::parse(code, &self.headers) {
+            Err(err) => eprintln!("{}", err.to_string().red()),
+            Ok(program) => println!("{:?}", program.expr),
+        }
+    }
+
+    fn help(&self, name: &str) {
+        let docs = self
+            .docs
+            .iter()
+            .filter(|(n, _)| *n == name)
+            .map(|(_, doc)| doc.trim_end())
+            .collect::<Vec<_>>();
+
+        if docs.is_empty() {
+            eprintln!("{}", format!("unknown function \"{}\"", name).red());
+            return;
+        }
+
+        for doc in docs {
+            println!("{}", doc);
+        }
+    }
+
+    fn print_record(&self, record: &csv::ByteRecord) {
+        println!(
+            "{}",
+            record
+                .iter()
+                .map(|cell| String::from_utf8_lossy(cell).into_owned())
+                .collect::<Vec<_>>()
+                .join(&", ".dimmed().to_string())
+        );
+    }
+
+    // NOTE: returns false when the session should end
+    fn handle_line(&self, line: &str) -> bool {
+        let line = line.trim();
+
+        if line.is_empty() {
+            return true;
+        }
+
+        let (command, rest) = match line.strip_prefix(':') {
+            None => {
+                self.evaluate(line);
+                return true;
+            }
+            Some(command) => match command.split_once(char::is_whitespace) {
+                Some((command, rest)) => (command, rest.trim()),
+                None => (command, ""),
+            },
+        };
+
+        match command {
+            "q" | "quit" | "exit" => return false,
+            "e" | "explain" => self.explain(rest),
+            "h" | "help" if !rest.is_empty() => self.help(rest),
+            "headers" => self.print_record(&self.headers),
+            "rows" => {
+                for (index, row) in self.rows.iter().enumerate() {
+                    print!("{} ", format!("{}:", index).dimmed());
+                    self.print_record(row);
+                }
+            }
+            _ => eprintln!(
+                "{}",
+                "available commands are :explain <expr>, :help <name>, :headers, :rows and :quit"
+                    .red()
+            ),
+        }
+
+        true
+    }
+}
+
+fn run_repl(args: Args) -> CliResult<()> {
+    // NOTE: docopt cannot distinguish both usages since --repl is also
+    // part of [options], so the input may end up as the expression.
+    let input = args.arg_input.clone().or_else(|| args.arg_expr.clone());
+
+    let (headers, rows) = match &input {
+        None => {
+            let (headers, row) = args.dummy_sample();
+            (headers, vec![row])
+        }
+        Some(_) => {
+            let mut rdr = Config::new(&input)
+                .delimiter(args.flag_delimiter)
+                .reader()?;
+
+            let headers = rdr.byte_headers()?.clone();
+            let rows = rdr
+                .byte_records()
+                .take(args.flag_limit)
+                .collect::<Result<Vec<_>, _>>()?;
+
+            (headers, rows)
+        }
+    };
+
+    let docs = get_function_docs();
+
+    let mut candidates = docs
+        .iter()
+        .map(|(name, _)| name.to_string())
+        .chain(
+            headers
+                .iter()
+                .map(|h| String::from_utf8_lossy(h).into_owned()),
+        )
+        .collect::<Vec<_>>();
+
+    candidates.sort();
+    candidates.dedup();
+
+    let session = Session {
+        headers,
+        rows,
+        docs,
+    };
+
+    let term = Term::stdout();
+
+    // NOTE: when stdin is not a terminal, we just evaluate each line
+    if !term.is_term() || !io::stdin().is_terminal() {
+        for line in io::stdin().lock().lines() {
+            if !session.handle_line(&line?) {
+                break;
+            }
+        }
+
+        return Ok(());
+    }
+
+    println!(
+        "{}",
+        format!(
+            "Loaded {} sample row(s). Use tab for completion and :quit to exit.",
+            session.rows.len()
+        )
+        .dimmed()
+    );
+
+    let mut editor = LineEditor {
+        term,
+        history: Vec::new(),
+        candidates,
+    };
+
+    while let Some(line) = editor.read_line(&"> ".cyan().to_string())? {
+        if !session.handle_line(&line) {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_function_docs() {
+        let docs = get_function_docs();
+
+        let (_, lower) = docs.iter().find(|(name, _)| *name == "lower").unwrap();
+
+        assert_eq!(
+            lower.trim_end(),
+            "- lower(string) -> string\n        Lowercase string."
+        );
+    }
+
+    #[test]
+    fn test_common_prefix() {
+        assert_eq!(common_prefix(&["split", "splice", "sp"]), "sp");
+        assert_eq!(common_prefix(&["lower", "lower_case"]), "lower");
+        assert_eq!(common_prefix(&["trim", "upper"]), "");
+    }
+}
@@ -1,17 +1,9 @@
 use std::fs;
 
 use crate::config::{Config, Delimiter};
-use crate::util;
+use crate::util::{self, TempFileGuard};
 use crate::CliResult;
 
-struct TempFileGuard(String);
-
-impl Drop for TempFileGuard {
-    fn drop(&mut self) {
-        let _ = fs::remove_file(&self.0);
-    }
-}
-
 static USAGE: &str = "
 Formats CSV data with a custom delimiter or CRLF line endings.
 
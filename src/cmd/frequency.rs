@@ -1,7 +1,10 @@
+use std::io;
+use std::ops::{AddAssign, Sub};
+
 use bstr::ByteSlice;
 use csv::{self, ByteRecord};
 
-use crate::collections::{ClusteredInsertHashmap, Counter};
+use crate::collections::{ClusteredInsertHashmap, Counter, WeightedCounter};
 use crate::config::{Config, Delimiter};
 use crate::select::SelectColumns;
 use crate::util;
@@ -17,7 +20,8 @@ The resulting frequency table will look like this:
 
 field - Name of the column
 value - Some distinct value of the column
-count - Number of rows containing this value
+count - Number of rows containing this value, or sum of their weights
+        when using -w, --weight
 
 By default, there is a row for the N most frequent values for each field in the
 data. The number of values can be tweaked with --limit and --threshold flags
@@ -53,6 +57,10 @@ frequency options:
                            limit.
     -t, --threshold <arg>  If set, won't return items having a count less than
                            this given threshold. It is combined with -l/--limit.
+                           Can be fractional when using -w, --weight.
+    -w, --weight <col>     Column containing frequency weights to sum instead of
+                           counting rows. Rows with an empty weight are ignored.
+                           Cannot be used with -a, --approx.
     -N, --no-extra         Don't include empty cells & remaining counts.
     -p, --parallel         Allow sorting to be done in parallel. This is only
                            useful with -l/--limit set to 0, or with -A, --all.
@@ -79,7 +87,8 @@ struct Args {
     flag_all: bool,
     flag_limit: usize,
     flag_approx: bool,
-    flag_threshold: Option<u64>,
+    flag_threshold: Option<f64>,
+    flag_weight: Option<SelectColumns>,
     flag_no_extra: bool,
     flag_output: Option<String>,
     flag_no_headers: bool,
@@ -95,6 +104,116 @@ impl Args {
             self.flag_limit = 0;
         }
     }
+
+    fn limit(&self) -> Option<usize> {
+        if self.flag_limit == 0 {
+            None
+        } else {
+            Some(self.flag_limit)
+        }
+    }
+
+    fn new_counter(&self) -> FieldCounter {
+        if self.flag_weight.is_some() {
+            FieldCounter::Weighted(WeightedCounter::new())
+        } else if self.flag_approx {
+            FieldCounter::Unweighted(Counter::new(Some(self.flag_limit)))
+        } else {
+            FieldCounter::Unweighted(Counter::new(None))
+        }
+    }
+}
+
+enum FieldCounter {
+    Unweighted(Counter<ValueKey>),
+    Weighted(WeightedCounter<ValueKey>),
+}
+
+impl FieldCounter {
+    fn add(&mut self, key: ValueKey, weight: f64) {
+        match self {
+            Self::Unweighted(counter) => counter.add(key),
+            Self::Weighted(counter) => counter.add(key, weight),
+        }
+    }
+
+    fn write<W: io::Write>(
+        self,
+        wtr: &mut csv::Writer<W>,
+        prefix: &[&[u8]],
+        args: &Args,
+    ) -> CliResult<()> {
+        match self {
+            Self::Unweighted(counter) => {
+                let (total, items) = counter.into_total_and_items(args.limit(), args.flag_parallel);
+
+                write_items(
+                    wtr,
+                    prefix,
+                    total,
+                    items,
+                    args.flag_threshold.map(|t| t.ceil() as u64),
+                    args.flag_no_extra,
+                )
+            }
+            Self::Weighted(counter) => {
+                let (total, items) = counter.into_total_and_items(args.limit(), args.flag_parallel);
+
+                write_items(
+                    wtr,
+                    prefix,
+                    total,
+                    items,
+                    args.flag_threshold,
+                    args.flag_no_extra,
+                )
+            }
+        }
+    }
+}
+
+fn write_items<W, C>(
+    wtr: &mut csv::Writer<W>,
+    prefix: &[&[u8]],
+    total: C,
+    items: Vec<(ValueKey, C)>,
+    threshold: Option<C>,
+    no_extra: bool,
+) -> CliResult<()>
+where
+    W: io::Write,
+    C: Copy + Default + PartialOrd + AddAssign + Sub<Output = C> + ToString,
+{
+    let mut record = ByteRecord::new();
+    let mut emitted = C::default();
+
+    for (value, count) in items {
+        if let Some(threshold) = threshold {
+            if count < threshold {
+                break;
+            }
+        }
+
+        emitted += count;
+
+        record.clear();
+        record.extend(prefix);
+        record.push_field(&value);
+        record.push_field(count.to_string().as_bytes());
+        wtr.write_byte_record(&record)?;
+    }
+
+    let remaining = total - emitted;
+
+    if !no_extra && remaining > C::default() {
+        record.clear();
+        record.extend(prefix);
+        record.push_field(b"<rest>");
+        record.push_field(remaining.to_string().as_bytes());
+        wtr.write_byte_record(&record)?;
+    }
+
+    Ok(())
 }
 
 pub fn run(argv: &[&str]) -> CliResult<()> {
@@ -105,22 +224,20 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         Err("-a, --approx cannot work with --limit=0 or -A, --all!")?;
     }
 
+    if args.flag_approx && args.flag_weight.is_some() {
+        Err("-a, --approx cannot work with -w, --weight!")?;
+    }
+
     if args.flag_no_limit_we_reach_for_the_sky {
         opener::open_browser("https://www.youtube.com/watch?v=7kmEEkECFQw")
             .expect("could not easter egg");
         return Ok(());
     }
 
-    let approx_k = if args.flag_approx {
-        Some(args.flag_limit)
-    } else {
-        None
-    };
-
     let rconf = Config::new(&args.arg_input)
         .delimiter(args.flag_delimiter)
         .no_headers(args.flag_no_headers)
-        .select(args.flag_select);
+        .select(args.flag_select.clone());
 
     let mut rdr = rconf.reader()?;
     let mut wtr = Config::new(&args.flag_output).writer()?;
@@ -129,14 +246,25 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
     let mut sel = rconf.selection(&headers)?;
     let groupby_sel_opt = args
         .flag_groupby
+        .as_ref()
         .map(|cols| cols.selection(&headers, !args.flag_no_headers))
         .transpose()?;
+    let weight_index_opt = args
+        .flag_weight
+        .as_ref()
+        .map(|col| col.single_selection(&headers, !args.flag_no_headers))
+        .transpose()?;
 
     // No need to consider the grouping column when counting frequencies
     if let Some(gsel) = &groupby_sel_opt {
         sel.subtract(gsel);
     }
 
+    // Nor the weight column
+    if let Some(weight_index) = weight_index_opt {
+        sel.remove(weight_index);
+    }
+
     // Nothing was selected
     if sel.is_empty() {
         return Ok(());
@@ -164,11 +292,16 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         }
     }
 
+    let get_weight = |record: &ByteRecord| -> CliResult<Option<f64>> {
+        match weight_index_opt {
+            None => Ok(Some(1.0)),
+            Some(weight_index) => Ok(util::parse_weight(&record[weight_index])?),
+        }
+    };
+
     if let Some(groupby_sel) = groupby_sel_opt {
-        let mut groups_to_fields_to_counter: ClusteredInsertHashmap<
-            GroupKey,
-            Vec<Counter<ValueKey>>,
-        > = ClusteredInsertHashmap::new();
+        let mut groups_to_fields_to_counter: ClusteredInsertHashmap<GroupKey, Vec<FieldCounter>> =
+            ClusteredInsertHashmap::new();
 
         let output_headers = {
             let mut r = ByteRecord::new();
@@ -189,6 +322,11 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
 
         // Aggregating
         while rdr.read_byte_record(&mut record)? {
+            let weight = match get_weight(&record)? {
+                Some(weight) => weight,
+                None => continue,
+            };
+
             let group: Vec<_> = groupby_sel
                 .select(&record)
                 .map(|cell| cell.to_vec())
@@ -198,7 +336,7 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
                 let mut list = Vec::with_capacity(sel.len());
 
                 for _ in 0..sel.len() {
-                    list.push(Counter::new(approx_k));
+                    list.push(args.new_counter());
                 }
 
                 list
@@ -212,7 +350,7 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
                             None => continue,
                         };
 
-                        fields_to_counter[i].add(sub_cell.to_vec());
+                        fields_to_counter[i].add(sub_cell.to_vec(), weight);
                     }
                 } else {
                     let cell = match coerce_cell(cell, args.flag_no_extra) {
@@ -220,7 +358,7 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
                         None => continue,
                     };
 
-                    fields_to_counter[i].add(cell.to_vec());
+                    fields_to_counter[i].add(cell.to_vec(), weight);
                 }
             }
         }
@@ -230,57 +368,14 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
             for (group, counters) in groups_to_fields_to_counter.iter_mut() {
                 let counter = counters.pop().unwrap();
 
-                let (total, items) = counter.into_total_and_items(
-                    if args.flag_limit == 0 {
-                        None
-                    } else {
-                        Some(args.flag_limit)
-                    },
-                    args.flag_parallel,
-                );
-
-                let mut emitted: u64 = 0;
-
-                for (value, count) in items {
-                    if let Some(threshold) = args.flag_threshold {
-                        if count < threshold {
-                            break;
-                        }
-                    }
-
-                    emitted += count;
-
-                    record.clear();
-                    record.push_field(&name);
-
-                    for cell in group {
-                        record.push_field(cell);
-                    }
-
-                    record.push_field(&value);
-                    record.push_field(count.to_string().as_bytes());
-                    wtr.write_byte_record(&record)?;
-                }
-
-                let remaining = total - emitted;
-
-                if !args.flag_no_extra && remaining > 0 {
-                    record.clear();
-                    record.push_field(&name);
+                let mut prefix: Vec<&[u8]> = vec![&name];
+                prefix.extend(group.iter().map(|cell| cell.as_slice()));
 
-                    for cell in group {
-                        record.push_field(cell);
-                    }
-
-                    record.push_field(b"<rest>");
-                    record.push_field(remaining.to_string().as_bytes());
-                    wtr.write_byte_record(&record)?;
-                }
+                counter.write(&mut wtr, &prefix, &args)?;
             }
         }
     } else {
-        let mut fields: Vec<Counter<ValueKey>> =
-            (0..sel.len()).map(|_| Counter::new(approx_k)).collect();
+        let mut fields: Vec<FieldCounter> = (0..sel.len()).map(|_| args.new_counter()).collect();
 
         let output_headers = {
             let mut r = ByteRecord::new();
@@ -296,6 +391,11 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
 
         // Aggregating
         while rdr.read_byte_record(&mut record)? {
+            let weight = match get_weight(&record)? {
+                Some(weight) => weight,
+                None => continue,
+            };
+
             for (cell, counter) in sel.select(&record).zip(fields.iter_mut()) {
                 if let Some(sep) = &args.flag_sep {
                     for sub_cell in cell.split_str(sep) {
@@ -304,7 +404,7 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
                             None => continue,
                         };
 
-                        counter.add(sub_cell.to_vec());
+                        counter.add(sub_cell.to_vec(), weight);
                     }
                 } else {
                     let cell = match coerce_cell(cell, args.flag_no_extra) {
@@ -312,49 +412,14 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
                         None => continue,
                     };
 
-                    counter.add(cell.to_vec());
+                    counter.add(cell.to_vec(), weight);
                 }
             }
         }
 
         // Writing output
         for (name, counter) in field_names.into_iter().zip(fields.into_iter()) {
-            let (total, items) = counter.into_total_and_items(
-                if args.flag_limit == 0 {
-                    None
-                } else {
-                    Some(args.flag_limit)
-                },
-                args.flag_parallel,
-            );
-
-            let mut emitted: u64 = 0;
-
-            for (value, count) in items {
-                if let Some(threshold) = args.flag_threshold {
-                    if count < threshold {
-                        break;
-                    }
-                }
-
-                emitted += count;
-
-                record.clear();
-                record.push_field(&name);
-                record.push_field(&value);
-                record.push_field(count.to_string().as_bytes());
-                wtr.write_byte_record(&record)?;
-            }
-
-            let remaining = total - emitted;
-
-            if !args.flag_no_extra && remaining > 0 {
-                record.clear();
-                record.push_field(&name);
-                record.push_field(b"<rest>");
-                record.push_field(remaining.to_string().as_bytes());
-                wtr.write_byte_record(&record)?;
-            }
+            counter.write(&mut wtr, &[&name], &args)?;
         }
     }
 
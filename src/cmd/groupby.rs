@@ -1,12 +1,19 @@
-use std::io::Write;
+use std::collections::hash_map::DefaultHasher;
+use std::fs::File;
+use std::hash::{Hash, Hasher};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use bytesize::MB;
+use uuid::Uuid;
 
 use crate::config::{Config, Delimiter};
 use crate::select::SelectColumns;
-use crate::util;
+use crate::util::{self, TempFileGuard};
 use crate::CliResult;
 
 use crate::moonblade::AggregationProgram;
-use crate::moonblade::GroupAggregationProgram;
+use crate::moonblade::{GroupAggregationProgram, GroupState};
 
 use crate::cmd::moonblade::{
     get_moonblade_aggregations_function_help, get_moonblade_cheatsheet,
@@ -27,6 +34,133 @@ fn write_group(
     Ok(())
 }
 
+// NOTE: number of temporary files used to partition spilled group states.
+const SPILL_PARTITIONS: u64 = 16;
+
+// NOTE: maximum number of times a partition can be partitioned again, which
+// only happens when its groups still don't fit in memory.
+const MAX_SPILL_LEVEL: usize = 8;
+
+type SpillPartitions = Vec<Option<(TempFileGuard, BufWriter<File>)>>;
+
+// NOTE: this is a hash aggregation spilling aggregation states to disk. Rows
+// are aggregated in memory until the estimated size of the groups' states
+// reaches the memory limit, at which point every state is serialized into
+// temporary files, partitioned by hash of their group, and the aggregation
+// goes on from an empty program. Each partition is then processed by merging
+// back the states of its groups in the order they were spilled, so that
+// order-sensitive aggregations such as values() remain correct. A partition
+// whose groups still exceed the memory limit is partitioned again, using
+// a different hash.
+struct ExternalGroupby<'a> {
+    blueprint: &'a GroupAggregationProgram,
+    error_policy: &'a MoonbladeErrorPolicy,
+    tmp_dir: PathBuf,
+    memory_limit: usize,
+    parallel: bool,
+}
+
+impl ExternalGroupby<'_> {
+    fn partition(group: &Vec<Vec<u8>>, level: usize) -> usize {
+        let mut hasher = DefaultHasher::new();
+        level.hash(&mut hasher);
+        group.hash(&mut hasher);
+
+        (hasher.finish() % SPILL_PARTITIONS) as usize
+    }
+
+    fn new_partitions() -> SpillPartitions {
+        (0..SPILL_PARTITIONS).map(|_| None).collect()
+    }
+
+    // NOTE: spilling a single group would not make it any smaller
+    fn should_spill(&self, program: &GroupAggregationProgram, level: usize) -> bool {
+        level < MAX_SPILL_LEVEL
+            && program.groups_count() > 1
+            && program.estimated_memory() >= self.memory_limit
+    }
+
+    fn spill(
+        &self,
+        program: &mut GroupAggregationProgram,
+        partitions: &mut SpillPartitions,
+        level: usize,
+    ) -> CliResult<()> {
+        for (group, state) in program.drain_group_states() {
+            let partition = &mut partitions[Self::partition(&group, level)];
+
+            if partition.is_none() {
+                let path = self
+                    .tmp_dir
+                    .join(format!("xan-groupby-{}.bin", Uuid::new_v4()));
+                let guard = TempFileGuard(path.to_string_lossy().into_owned());
+                let writer = BufWriter::new(File::create(&path)?);
+
+                *partition = Some((guard, writer));
+            }
+
+            let (_, writer) = partition.as_mut().unwrap();
+
+            bincode::serialize_into(writer, &(group, state))?;
+        }
+
+        Ok(())
+    }
+
+    fn run(
+        &self,
+        wtr: &mut csv::Writer<Box<dyn Write + Send>>,
+        mut program: GroupAggregationProgram,
+        mut partitions: SpillPartitions,
+        level: usize,
+    ) -> CliResult<()> {
+        if partitions.iter().all(|partition| partition.is_none()) {
+            for result in program.into_byte_records(self.parallel) {
+                let (group, group_record) = self.error_policy.handle_error(result)?;
+
+                write_group(wtr, &group, &group_record)?;
+            }
+
+            return Ok(());
+        }
+
+        self.spill(&mut program, &mut partitions, level)?;
+        drop(program);
+
+        let mut guards = Vec::new();
+
+        for (guard, writer) in partitions.into_iter().flatten() {
+            writer.into_inner().map_err(|err| err.into_error())?;
+            guards.push(guard);
+        }
+
+        for guard in guards {
+            let mut program = self.blueprint.clone();
+            let mut sub_partitions = Self::new_partitions();
+
+            let mut reader = BufReader::new(File::open(&guard.0)?);
+
+            while !reader.fill_buf()?.is_empty() {
+                let (group, state): (Vec<Vec<u8>>, GroupState) =
+                    bincode::deserialize_from(&mut reader)?;
+
+                program.merge_group_state(group, state);
+
+                if self.should_spill(&program, level + 1) {
+                    self.spill(&mut program, &mut sub_partitions, level + 1)?;
+                }
+            }
+
+            drop(reader);
+            drop(guard);
+
+            self.run(wtr, program, sub_partitions, level + 1)?;
+        }
+
+        Ok(())
+    }
+}
+
 static USAGE: &str = "
 Group a CSV file by values contained in a column selection then aggregate data per
 group using a custom aggregation expression.
@@ -50,10 +184,38 @@ You can rename the output columns using the 'as' syntax:
 
     $ xan groupby user_name 'sum(n) as sum, max(replies_count) as \"Max Replies\"' file.csv
 
+You can restrict the rows considered by a single aggregation using a 'where'
+clause, which makes it possible to compute conditional aggregations in a single pass:
+
+    $ xan groupby user_name 'count() as total, count() where lang eq \"fr\" as french, mean(n) where n > 0 as m' file.csv
+
+You can apply the same aggregation to a selection of columns using a star slice,
+or the \"cols\" function, which will expand into one aggregation per column:
+
+    $ xan groupby user_name 'sum(*[count_a:count_z]), mean(cols(\"num_*\"))' file.csv
+
 You can group on multiple columns (read `xan select -h` for more information about column selection):
 
     $ xan groupby name,surname 'sum(count)' file.csv
 
+You can also compute subtotals per level, along with a grand total, using
+the --rollup flag. Group cells of rolled-up levels will be left empty, or
+filled with the value given to the --marker flag:
+
+    $ xan groupby --rollup country,city 'sum(count)' file.csv
+
+Or compute arbitrary grouping levels, separated by semicolons, using the flag
+named --grouping-sets. An empty level means a grand total:
+
+    $ xan groupby --grouping-sets 'country,city;city;' country,city 'sum(count)' file.csv
+
+If the groups cannot fit into memory, you can use the --external flag. Each
+time the estimated size of the aggregation states reaches the memory limit,
+those states will be spilled into temporary files on disk, to be merged back
+afterwards. This is slower but keeps memory usage in check:
+
+    $ xan groupby --external -m 1024 user_id 'count()' file.csv
+
 For a quick review of the capabilities of the script language, use
 the --cheatsheet flag.
 
@@ -69,20 +231,36 @@ Usage:
     xan groupby --functions
 
 groupby options:
-    --keep <cols>           Keep this selection of columns, in addition to
-                            the ones representing groups, in the output. Only
-                            values from the first seen row per group will be kept.
-    -S, --sorted            Use this flag to indicate that the file is already sorted on the
-                            group columns, in which case the command will be able to considerably
-                            optimize memory usage.
-    -e, --errors <policy>   What to do with evaluation errors. One of:
-                              - \"panic\": exit on first error
-                              - \"ignore\": ignore row altogether
-                              - \"log\": print error to stderr
-                            [default: panic].
-    -p, --parallel          Whether to use parallelization to speed up computations.
-                            Will automatically select a suitable number of threads to use
-                            based on your number of cores.
+    --keep <cols>             Keep this selection of columns, in addition to
+                              the ones representing groups, in the output. Only
+                              values from the first seen row per group will be kept.
+    -S, --sorted              Use this flag to indicate that the file is already sorted on the
+                              group columns, in which case the command will be able to considerably
+                              optimize memory usage.
+    --rollup                  Also compute aggregations for every prefix of the group
+                              columns, from the full selection down to a grand total.
+    --grouping-sets <sets>    Semicolon-separated list of column selections, all subsets
+                              of the group columns, for which to compute aggregations.
+                              Use an empty selection to compute a grand total.
+    --marker <value>          Value used to fill group cells of rolled-up levels when
+                              using --rollup or --grouping-sets. Defaults to an empty
+                              string.
+    --external                Spill aggregation states to disk when the estimated memory
+                              used by groups exceeds the limit given to -m/--memory-limit.
+    --tmp-dir <arg>           Directory where temporary files will be written when
+                              using --external. Will default to the input file's
+                              directory or \"./\" if reading an incoming stream.
+    -m, --memory-limit <arg>  Maximum estimated memory used by groups before spilling
+                              to disk when using --external, in megabytes.
+                              [default: 512]
+    -e, --errors <policy>     What to do with evaluation errors. One of:
+                                - \"panic\": exit on first error
+                                - \"ignore\": ignore row altogether
+                                - \"log\": print error to stderr
+                              [default: panic].
+    -p, --parallel            Whether to use parallelization to speed up computations.
+                              Will automatically select a suitable number of threads to use
+                              based on your number of cores.
 
 Common options:
     -h, --help               Display this message
@@ -106,6 +284,12 @@ struct Args {
     flag_functions: bool,
     flag_keep: Option<SelectColumns>,
     flag_sorted: bool,
+    flag_rollup: bool,
+    flag_grouping_sets: Option<String>,
+    flag_marker: Option<String>,
+    flag_external: bool,
+    flag_tmp_dir: Option<String>,
+    flag_memory_limit: u64,
     flag_errors: String,
     flag_parallel: bool,
 }
@@ -128,6 +312,20 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         return Ok(());
     }
 
+    if args.flag_rollup && args.flag_grouping_sets.is_some() {
+        Err("--rollup cannot be used with --grouping-sets!")?;
+    }
+
+    if args.flag_sorted && (args.flag_rollup || args.flag_grouping_sets.is_some()) {
+        Err("-S, --sorted cannot be used with --rollup or --grouping-sets!")?;
+    }
+
+    if args.flag_external
+        && (args.flag_sorted || args.flag_rollup || args.flag_grouping_sets.is_some())
+    {
+        Err("--external cannot be used with -S, --sorted, --rollup or --grouping-sets!")?;
+    }
+
     let error_policy = MoonbladeErrorPolicy::try_from_restricted(&args.flag_errors)?;
 
     let rconf = Config::new(&args.arg_input)
@@ -141,6 +339,43 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
 
     let sel = rconf.selection(headers)?;
 
+    // NOTE: grouping sets are expressed as positions in the group selection
+    let grouping_sets_opt: Option<Vec<Vec<usize>>> = if args.flag_rollup {
+        Some((0..=sel.len()).rev().map(|i| (0..i).collect()).collect())
+    } else if let Some(spec) = &args.flag_grouping_sets {
+        let mut grouping_sets = Vec::new();
+
+        for part in spec.split(';') {
+            let part = part.trim();
+
+            if part.is_empty() {
+                grouping_sets.push(vec![]);
+                continue;
+            }
+
+            let mut grouping_set = Vec::new();
+
+            for i in SelectColumns::parse(part)?
+                .selection(headers, !args.flag_no_headers)?
+                .iter()
+            {
+                match sel.iter().position(|j| j == i) {
+                    Some(position) => grouping_set.push(position),
+                    None => Err(format!(
+                        "grouping set \"{}\" contains columns that are not part of the group columns!",
+                        part
+                    ))?,
+                }
+            }
+
+            grouping_sets.push(grouping_set);
+        }
+
+        Some(grouping_sets)
+    } else {
+        None
+    };
+
     // Lol, what a hack...
     if let Some(selection) = args.flag_keep.take() {
         let mut keep_sel = selection.selection(headers, !args.flag_no_headers)?;
@@ -214,6 +449,48 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
                 &error_policy.handle_error(program.finalize(args.flag_parallel))?,
             )?;
         }
+    } else if args.flag_external {
+        let blueprint = GroupAggregationProgram::parse(&args.arg_expression, headers)?;
+
+        write_group(
+            &mut wtr,
+            &sel.collect(headers),
+            &blueprint.headers().collect(),
+        )?;
+
+        let tmp_dir = args.flag_tmp_dir.unwrap_or(match &args.arg_input {
+            None => "./".to_string(),
+            Some(p) => Path::new(p).parent().unwrap().to_str().unwrap().to_string(),
+        });
+
+        let external = ExternalGroupby {
+            blueprint: &blueprint,
+            error_policy: &error_policy,
+            tmp_dir: PathBuf::from(tmp_dir),
+            memory_limit: (args.flag_memory_limit * MB) as usize,
+            parallel: args.flag_parallel,
+        };
+
+        let mut program = blueprint.clone();
+        let mut partitions = ExternalGroupby::new_partitions();
+
+        let mut index: usize = 0;
+
+        while rdr.read_byte_record(&mut record)? {
+            let group = sel.collect(&record);
+
+            program
+                .run_with_record_and_track_memory(group, index, &record)
+                .or_else(|error| error_policy.handle_row_error(index, error))?;
+
+            if external.should_spill(&program, 0) {
+                external.spill(&mut program, &mut partitions, 0)?;
+            }
+
+            index += 1;
+        }
+
+        external.run(&mut wtr, program, partitions, 0)?;
     } else {
         let mut program = GroupAggregationProgram::parse(&args.arg_expression, headers)?;
 
@@ -235,10 +512,24 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
             index += 1;
         }
 
-        for result in program.into_byte_records(args.flag_parallel) {
-            let (group, group_record) = error_policy.handle_error(result)?;
+        if let Some(grouping_sets) = grouping_sets_opt {
+            let marker = args.flag_marker.unwrap_or_default();
+
+            for result in program.into_grouping_sets_byte_records(
+                &grouping_sets,
+                marker.as_bytes(),
+                args.flag_parallel,
+            ) {
+                let (group, group_record) = error_policy.handle_error(result)?;
+
+                write_group(&mut wtr, &group, &group_record)?;
+            }
+        } else {
+            for result in program.into_byte_records(args.flag_parallel) {
+                let (group, group_record) = error_policy.handle_error(result)?;
 
-            write_group(&mut wtr, &group, &group_record)?;
+                write_group(&mut wtr, &group, &group_record)?;
+            }
         }
     }
 
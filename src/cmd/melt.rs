@@ -0,0 +1,168 @@
+use regex::bytes::Regex;
+
+use crate::config::{Config, Delimiter};
+use crate::select::SelectColumns;
+use crate::util;
+use crate::CliResult;
+
+static USAGE: &str = "
+Melt a CSV file, i.e. turn a \"wide\" table into a \"long\" one, by emitting
+one row per melted column, containing the name of the column and its value.
+
+This is conceptually the inverse of the \"pivot\" command.
+
+For instance the following CSV:
+
+*file.csv*
+country,year_2023,year_2024
+fr,10,15
+de,7,
+
+Can be melted likewise:
+
+    $ xan melt 'year_*' --variable-name year file.csv > melted.csv
+
+To produce the following file:
+
+*melted.csv*
+country,year,value
+fr,year_2023,10
+fr,year_2024,15
+de,year_2023,7
+de,year_2024,
+
+Columns that are not melted will be repeated for each emitted row.
+
+Part of the melted column names can also be extracted using a regex given
+to the --extract flag. If the regex contains a capture group, the first one
+will be used, else the whole match will be used. Names not matching the
+regex will be kept as is:
+
+    $ xan melt 'year_*' --extract '\\d+' file.csv
+
+Usage:
+    xan melt [options] <columns> [<input>]
+    xan melt --help
+
+melt options:
+    --variable-name <name>  Name of the column containing the names of
+                            the melted columns.
+                            [default: variable]
+    --value-name <name>     Name of the column containing the values of
+                            the melted columns.
+                            [default: value]
+    -E, --drop-empty        Don't emit rows whose melted value is empty.
+    -x, --extract <regex>   Regex used to extract part of the melted column
+                            names.
+
+Common options:
+    -h, --help             Display this message
+    -o, --output <file>    Write output to <file> instead of stdout.
+    -n, --no-headers       When set, the first row will not be interpreted
+                           as headers. Column indices will be used as
+                           variable names.
+    -d, --delimiter <arg>  The field delimiter for reading CSV data.
+                           Must be a single character.
+";
+
+#[derive(Deserialize)]
+struct Args {
+    arg_columns: SelectColumns,
+    arg_input: Option<String>,
+    flag_variable_name: String,
+    flag_value_name: String,
+    flag_drop_empty: bool,
+    flag_extract: Option<String>,
+    flag_output: Option<String>,
+    flag_no_headers: bool,
+    flag_delimiter: Option<Delimiter>,
+}
+
+pub fn run(argv: &[&str]) -> CliResult<()> {
+    let args: Args = util::get_args(USAGE, argv)?;
+
+    let regex_opt = args
+        .flag_extract
+        .as_ref()
+        .map(|pattern| Regex::new(pattern))
+        .transpose()?;
+
+    let rconfig = Config::new(&args.arg_input)
+        .delimiter(args.flag_delimiter)
+        .no_headers(args.flag_no_headers)
+        .select(args.arg_columns);
+
+    let mut rdr = rconfig.reader()?;
+    let mut wtr = Config::new(&args.flag_output).writer()?;
+
+    let headers = rdr.byte_headers()?.clone();
+    let sel = rconfig.selection(&headers)?;
+
+    if sel.is_empty() {
+        Err("expecting a non-empty column selection")?;
+    }
+
+    let kept_columns = (0..headers.len())
+        .filter(|i| !sel.contains(*i))
+        .collect::<Vec<_>>();
+
+    let variables = sel
+        .iter()
+        .map(|i| {
+            let name = if rconfig.no_headers {
+                i.to_string().into_bytes()
+            } else {
+                headers[*i].to_vec()
+            };
+
+            match &regex_opt {
+                None => name,
+                Some(regex) => match regex.captures(&name) {
+                    None => name,
+                    Some(caps) => caps
+                        .get(1)
+                        .or_else(|| caps.get(0))
+                        .unwrap()
+                        .as_bytes()
+                        .to_vec(),
+                },
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let mut output_record = csv::ByteRecord::new();
+
+    if !rconfig.no_headers {
+        for i in kept_columns.iter() {
+            output_record.push_field(&headers[*i]);
+        }
+
+        output_record.push_field(args.flag_variable_name.as_bytes());
+        output_record.push_field(args.flag_value_name.as_bytes());
+
+        wtr.write_byte_record(&output_record)?;
+    }
+
+    let mut record = csv::ByteRecord::new();
+
+    while rdr.read_byte_record(&mut record)? {
+        for (variable, value) in variables.iter().zip(sel.select(&record)) {
+            if args.flag_drop_empty && value.is_empty() {
+                continue;
+            }
+
+            output_record.clear();
+
+            for i in kept_columns.iter() {
+                output_record.push_field(&record[*i]);
+            }
+
+            output_record.push_field(variable);
+            output_record.push_field(value);
+
+            wtr.write_byte_record(&output_record)?;
+        }
+    }
+
+    Ok(wtr.flush()?)
+}
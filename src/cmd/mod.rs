@@ -32,11 +32,13 @@ pub mod input;
 pub mod join;
 pub mod map;
 pub mod matrix;
+pub mod melt;
 pub mod merge;
-mod moonblade;
+pub(crate) mod moonblade;
 pub mod network;
 pub mod parallel;
 pub mod partition;
+pub mod pivot;
 pub mod plot;
 pub mod progress;
 pub mod range;
@@ -21,7 +21,7 @@ lazy_static! {
     // static ref SPACER_REGEX: Regex = Regex::new(r"(?m)^ {8}([^\n]+)").unwrap();
     static ref UNARY_OPERATOR_REGEX: Regex = Regex::new(r"([!-])x").unwrap();
     static ref BINARY_OPERATOR_REGEX: Regex = Regex::new(
-        r"x (==|!=|<[= ]|>[= ]|&& |\|\| |and|or |not in|in|eq|ne|lt|le|gt|ge|//|\*\*|[+\-*/%.]) y"
+        r"x (==|!=|<[= ]|>[= ]|&& |\|\| |\?\?|and|or |not in|in|eq|ne|lt|le|gt|ge|//|\*\*|[+\-*/%.]) y"
     )
     .unwrap();
     static ref PIPELINE_OPERATOR_REGEX: Regex = Regex::new(
@@ -153,6 +153,11 @@ available functions & operators):
         'b\"hello\"'
         \"b'hello'\"
 
+  . Format string literals (can use single or double quotes):
+        'f\"{name} ({year})\"'
+        'f\"{price:.2f} {count:>5} {ratio:.1%}\"' (with format specs)
+        'f\"{{literal braces}}\"'
+
   . Regex literals:
         '/john/'
         '/john/i' (case-insensitive)
@@ -178,8 +183,7 @@ be read/parsed once per row.
     colorize_cheatsheet(help)
 }
 
-pub fn get_moonblade_functions_help() -> String {
-    let help = "
+pub static MOONBLADE_FUNCTIONS_HELP: &str = "
 # Available functions & operators
 
 (use --cheatsheet for a reminder of the expression language's basics)
@@ -242,6 +246,8 @@ use the operators in the previous section.
     x in y
     x not in y
 
+    x ?? y - y if x is null or empty, x otherwise (short-circuits)
+
 ## Indexing & slicing operators
 
     x[y] - get y from x (string or list index, map key)
@@ -252,6 +258,9 @@ use the operators in the previous section.
     Negative indices are accepted and mean the same thing as with
     the Python language.
 
+    Indexing null returns null, so that chains such as x[a][b] will not
+    fail when some intermediate key is missing.
+
 ## Pipeline operator (using \"_\" for left-hand side substitution)
 
     trim(name) | len(_)         - Same as len(trim(name))
@@ -276,6 +285,14 @@ use the operators in the previous section.
     - ceil(x) -> number
         Return the smallest integer greater than or equal to x.
 
+    - decimal(x, scale?) -> decimal
+        Convert x to an exact fixed-point decimal number, so that arithmetic
+        operations, sums and means computed on it do not suffer from floating
+        point artefacts (e.g. decimal(\"0.1\") + decimal(\"0.2\") is exactly 0.3).
+        Mixing decimals with floats yields floats. If scale is given, the
+        number will be rounded or padded to this number of decimal places.
+        Useful for monetary amounts: sum(decimal(amount)).
+
     - div(x, y, *n) -> number
         Divide two or more numbers.
 
@@ -325,10 +342,26 @@ use the operators in the previous section.
     - and(a, b, *x) -> T
         Perform boolean AND operation on two or more values.
 
+    - case(value, pattern, result, *pattern_result_pairs, default?) -> T
+        Return the result associated with the first pattern matching given value,
+        or the default value (null if not given) if none matched. Patterns can be:
+            - a regex, e.g. /^john/i, matching the value as a string
+            - a list, e.g. ['fr', 'be'], matching if any of its items matches
+            - a map of numeric bounds (among gt, ge, lt & le), e.g. {ge: 18, lt: 65}
+            - a lambda, e.g. x => x > 5, matching if it returns a truthy value
+            - null, matching null or empty values
+            - any other value, tested for equality (numerically if a number)
+        Will short-circuit, only evaluating patterns until a match is found
+        and the relevant result.
+
     - if(cond, then, else?) -> T
         Evaluate condition and switch to correct branch.
         Will actually short-circuit. Contrary to \"or\" and \"and\".
 
+    - ifnull(value, fallback) -> T
+        Return fallback if value is null or empty, else value. Same as
+        `value ?? fallback`. Contrary to \"coalesce\", will short-circuit.
+
     - unless(cond, then, else?) -> T
         Shorthand for `if(not(cond), then, else?)`.
 
@@ -360,6 +393,21 @@ use the operators in the previous section.
 
 ## String & sequence helpers
 
+    - captures(string, regex) -> map?
+        Return a map of the capture groups of the first match of the
+        regex in the string, keyed by name for named groups, e.g.
+        \"(?<year>[0-9]{4})\", or by index for unnamed ones. Returns nothing
+        if the regex does not match.
+
+    - casefold(string) -> string
+        Apply full Unicode case folding to string, which is more thorough
+        than lowercasing when comparing strings caselessly, e.g.
+        \"Straße\" -> \"strasse\".
+
+    - collapse_whitespace(string) -> string
+        Trim string and replace every run of whitespace (including line
+        breaks, tabs and non-breaking spaces) by a single space.
+
     - compact(list) -> list
         Drop all falsey values from given list.
 
@@ -374,12 +422,22 @@ use the operators in the previous section.
         Count number of times pattern appear in seq. Pattern
         can be a regular expression.
 
+    - count_matches(seq, pattern) -> int
+        Alias of count, which reads better when pattern is a regular
+        expression, e.g. count_matches(text, /#\\w+/).
+
     - endswith(string, pattern) -> bool
         Test if string ends with pattern.
 
     - escape_regex(string) -> string
         Escape a string so it can be used safely in a regular expression.
 
+    - find_all(string, pattern, group?) -> list
+        Return all non-overlapping matches of the pattern in the string.
+        Pattern can be a regular expression or a literal string. If a
+        group index or name is given, return the matched group of the
+        regular expression instead of the whole match.
+
     - first(seq) -> T
         Get first element of sequence.
 
@@ -391,10 +449,19 @@ use the operators in the previous section.
         the first \"{}\" by the value of the name column, then the
         second one by the value of the surname column.
 
-    - get(target, index_or_key, default?) -> T
+    - format(value, spec) -> string
+        Format value according to given spec, following the syntax
+        [[fill]align][+][0][width][,][.precision][f|%], which is a subset
+        of Python's format specification mini-language. Also used by
+        f-string placeholders, e.g. f\"{price:.2f}\".
+
+        Example: `format(3.14159, \"08.2\")` will return \"00003.14\".
+
+    - get(target, index_or_key_or_path, default?) -> T
         Get nth element of sequence (can use negative indexing), or key of mapping.
-        Returns nothing if index or key is not found or alternatively the provided
-        default value.
+        Can also take a list of indices & keys to traverse nested data.
+        Returns nothing if target is null, if index or key is not found, or
+        alternatively the provided default value.
 
     - join(seq, sep) -> string
         Join sequence by separator.
@@ -419,7 +486,8 @@ use the operators in the previous section.
         Format a number with thousands separator and proper significance.
 
     - replace(string, pattern, replacement) -> string
-        Replace pattern in string. Can use a regex.
+        Replace pattern in string. Can use a regex, in which case the
+        replacement can refer to capture groups using \"$1\" or \"${name}\".
 
     - rtrim(string, pattern?) -> string
         Trim string of trailing whitespace or
@@ -429,7 +497,7 @@ use the operators in the previous section.
         Return slice of sequence.
 
     - split(string, sep, max?) -> list
-        Split a string by separator.
+        Split a string by separator. Separator can be a regex.
 
     - startswith(string, pattern) -> bool
         Test if string starts with pattern.
@@ -441,6 +509,10 @@ use the operators in the previous section.
     - unidecode(string) -> string
         Convert string to ascii as well as possible.
 
+    - unicode_normalize(string, form?) -> string
+        Apply given Unicode normalization form to string. Form must be
+        one of \"NFC\" (the default), \"NFD\", \"NFKC\" or \"NFKD\".
+
     - upper(string) -> string
         Uppercase string.
 
@@ -511,6 +583,78 @@ use the operators in the previous section.
     - mean(numbers) -> number?
         Return the means of the given numbers.
 
+    - median(numbers) -> number?
+        Return the median of the given numbers. If the list has an even
+        number of items, return the mean of the two middle ones.
+
+    - sum(numbers) -> number
+        Return the sum of the given numbers.
+
+## Higher-order functions
+
+Higher-order functions take a list and a lambda, written as
+`x => expr` or `(acc, x) => expr`, to apply to its items.
+
+    - all(list, lambda) -> bool
+        Return whether the lambda returns a truthy value for all items
+        of the list.
+
+    - any(list, lambda) -> bool
+        Return whether the lambda returns a truthy value for at least
+        one item of the list.
+
+    - filter(list, lambda) -> list
+        Return a list containing only the items for which the lambda
+        returns a truthy value.
+
+    - find(list, lambda) -> T?
+        Return the first item for which the lambda returns a truthy
+        value, or nothing if none is found.
+
+    - fold(list, lambda, initial) -> T
+        Reduce the list to a single value by calling the lambda with
+        an accumulator, starting with initial, and each item in turn.
+        Example: `fold(numbers, (acc, x) => acc + x, 0)`.
+
+    - group_by(list, lambda) -> map
+        Group items of the list into a map whose keys are the values
+        returned by the lambda.
+
+    - map(list, lambda) -> list
+        Return a list containing the results of the lambda applied to
+        each item of the list.
+
+    - reduce(list, lambda) -> T
+        Same as fold, but using the first item of the list as initial
+        value. Will error if the list is empty.
+
+    - sort_by(list, lambda) -> list
+        Sort the list using the values returned by the lambda as keys.
+        See sort for how keys are compared.
+
+## List functions
+
+    - enumerate(list) -> list
+        Return a list of [index, item] pairs.
+
+    - flatten(list) -> list
+        Flatten the list by one level.
+
+    - range(start?, end, step?) -> list
+        Return a list of integers from start (defaults to 0), inclusive,
+        to end, exclusive, incremented by step (defaults to 1).
+
+    - sort(list) -> list
+        Sort the list. Numbers are compared numerically, datetimes
+        chronologically and everything else lexicographically.
+
+    - unique(list) -> list
+        Deduplicate the list, keeping the first occurrence of each item.
+
+    - zip(list, *lists) -> list
+        Return a list of lists containing items of same index in given
+        lists. The result is as long as the shortest list.
+
 ## Fuzzy matching & information retrieval
 
     - fingerprint(string) -> string
@@ -525,6 +669,203 @@ use the operators in the previous section.
         Apply a very simple stemmer removing common plural inflexions in
         some languages.
 
+    - damerau_levenshtein(string_a, string_b) -> int
+        Return the Damerau-Levenshtein edit distance between two strings,
+        i.e. the Levenshtein distance where the transposition of two
+        adjacent characters counts as a single edit.
+
+    - dice(string_a, string_b, n?) -> float
+        Return the Dice coefficient between the sets of character n-grams
+        of two strings. n defaults to 2.
+
+    - jaccard(string_a, string_b, n?) -> float
+        Return the Jaccard similarity between the sets of character n-grams
+        of two strings. n defaults to 2.
+
+    - jaro_winkler(string_a, string_b) -> float
+        Return the Jaro-Winkler similarity between two strings, between 0
+        and 1.
+
+    - levenshtein(string_a, string_b) -> int
+        Return the Levenshtein edit distance between two strings.
+
+    - longest_common_substring(string_a, string_b) -> string
+        Return the longest substring shared by two strings.
+
+    - metaphone(string) -> string
+        Return the Metaphone phonetic key of a string, targeting the
+        English language.
+
+    - soundex(string) -> string
+        Return the Soundex phonetic key of a string, targeting the
+        English language.
+
+    - soundex_fr(string) -> string
+        Return the Soundex2 phonetic key of a string, an adaptation of
+        Soundex targeting the French language.
+
+## Html
+
+    - html_escape(string) -> string
+        Escape HTML special characters, i.e. &, <, >, \" and ', as entities.
+
+    - html_links(html, base_url?) -> list[string]
+        Extract the href attribute of all links found in given HTML, in
+        order, with their entities decoded. If base_url is given, relative
+        links will be resolved against it.
+
+    - html_to_text(html) -> string
+        Extract the visible text of given HTML, by dropping its tags,
+        comments and invisible elements such as <script> or <style>,
+        decoding its entities and collapsing its whitespace. Block
+        elements such as paragraphs are separated by line breaks.
+
+    - html_unescape(string) -> string
+        Decode named (e.g. \"&eacute;\") and numeric (e.g. \"&#233;\" or
+        \"&#xE9;\") HTML entities found in string.
+
+    - strip_tags(html) -> string
+        Remove all HTML tags and comments from given string, keeping
+        their text content as-is.
+
+## Ip addresses
+
+    - anonymize_ip(ip, prefix_len?) -> string
+        Anonymize given IPv4 or IPv6 address by zeroing every bit after
+        given prefix length, which defaults to 24 for IPv4 (e.g.
+        \"192.168.12.34\" -> \"192.168.12.0\") and 48 for IPv6.
+
+    - in_cidr(ip, ranges) -> bool
+        Return whether given ip address belongs to given CIDR range, e.g.
+        \"10.0.0.0/8\", or to any range of given list. Blank ranges are
+        ignored, so that a list of ranges can be read from a file once,
+        e.g. in_cidr(ip, split(read(\"ranges.txt\"), \"\\n\")).
+        Ranges that do not change from one row to the next are only parsed
+        once.
+
+    - ip_to_int(ip) -> string
+        Convert given ip address to an integer, e.g. for sorting purposes.
+        Since IPv6 addresses do not fit in 64 bits, the integer is always
+        returned as a string of decimal digits, for IPv4 addresses too.
+
+    - is_ip(string, version?) -> bool
+        Return whether given string is a valid ip address. Version can be
+        given to only accept either IPv4 (4) or IPv6 (6) addresses.
+
+    - parse_ip(ip) -> map
+        Parse given ip address into a map containing its version (4 or 6),
+        normalized address, and whether it is private, loopback, multicast
+        or unspecified. Throws if the address is invalid.
+
+## Urls
+
+    - domain_name(url) -> string?
+        Return the registered domain of given url, i.e. the domain
+        name directly under its public suffix, as found in the public
+        suffix list. E.g. \"https://news.bbc.co.uk/\" -> \"bbc.co.uk\".
+
+    - infer_redirection(url) -> string
+        Return the target of given url if it can be inferred from the
+        url alone, without performing any network request (e.g. youtu.be
+        short links, facebook or google redirection urls), else return
+        the url as-is.
+
+    - is_shortened_url(url) -> bool
+        Return whether given url comes from a known url shortener,
+        e.g. bit.ly, t.co etc.
+
+    - lru(url) -> string
+        Return the LRU (i.e. reversed and hierarchical representation) of
+        given url, e.g. \"s:http|h:com|h:example|p:path|\". LRUs can be
+        sorted or grouped by prefix to aggregate urls by domain, subdomain,
+        path etc.
+
+    - normalize_url(url) -> string
+        Normalize given url so that it can be used as a key for
+        deduplication purposes, by lowercasing its host, dropping
+        \"www.\", its fragment, trailing slashes and tracking query
+        parameters such as \"utm_source\" or \"fbclid\".
+
+    - parse_url(url) -> map
+        Parse given url into a map containing its scheme, host, port,
+        path, query (as a map of parameters) and fragment. Values of
+        parameters repeated in the query, e.g. \"q=1&q=2\", are collected
+        into a list. Urls without scheme, e.g. \"lemonde.fr/path\", are
+        assumed to use http.
+
+## Geospatial
+
+    - bbox(geometry) -> list[number]
+        Return the bounding box of given geometry, as a
+        [min_lon, min_lat, max_lon, max_lat] list. Geometry can be given
+        as a WKT string, a GeoJSON string or a GeoJSON map.
+
+    - geohash(lat, lon, precision?) -> string
+        Return the geohash of given point, using given precision, from 1
+        to 12 characters (default 12). Useful to bucket points spatially.
+
+    - geohash_decode(hash) -> list[number]
+        Return the center of the cell described by given geohash, as a
+        [lat, lon] list.
+
+    - haversine(lat1, lon1, lat2, lon2) -> number
+        Return the great-circle distance, in kilometers, between two points
+        given by their latitude & longitude.
+
+    - in_bbox(lat, lon, bbox) -> bool
+        Return whether given point lies within given bounding box, given as
+        a [min_lon, min_lat, max_lon, max_lat] list, as returned by \"bbox\".
+
+    - in_polygon(lat, lon, geometry) -> bool
+        Return whether given point lies within given polygon or multipolygon
+        (holes are taken into account). Geometry can be given as a WKT
+        string, a GeoJSON string or a GeoJSON map (geometry or feature).
+        Polygons given as constants, e.g. parse_wkt(read(\"zone.wkt\")) or
+        read_json(\"zone.geojson\"), will only be loaded once.
+
+    - parse_wkt(string) -> map
+        Parse given WKT (Well-Known Text) string into a GeoJSON geometry
+        map. Supports POINT, LINESTRING, POLYGON and their MULTI variants.
+
+## Hashing & encoding
+
+    - base64_decode(string) -> bytes
+        Decode given base64 string.
+
+    - base64_encode(string) -> string
+        Encode given string or bytes in base64.
+
+    - crc32(string) -> int
+        Return the CRC32 checksum of given string.
+
+    - hex_decode(string) -> bytes
+        Decode given hexadecimal string.
+
+    - hex_encode(string) -> string
+        Encode given string or bytes in hexadecimal.
+
+    - md5(string) -> string
+        Return the md5 hash of string in hexadecimal representation.
+
+    - sha1(string) -> string
+        Return the sha1 hash of string in hexadecimal representation.
+
+    - sha256(string) -> string
+        Return the sha256 hash of string in hexadecimal representation.
+
+    - urldecode(string) -> string
+        Decode given percent-encoded string.
+
+    - urlencode(string) -> string
+        Percent-encode given string so that it can safely be used as
+        part of a url. Only ASCII alphanumeric characters and \"-\", \"_\",
+        \".\" & \"~\" are kept as-is.
+
+    - xxh3(string) -> string
+        Return the 64-bit xxh3 hash of string in hexadecimal representation.
+        It is a very fast non-cryptographic hash, well suited to build
+        deterministic keys.
+
 ## Utils
 
     - coalesce(*args) -> T
@@ -539,6 +880,10 @@ use the operators in the previous section.
         to another given column by name or position, inclusive.
         Can also be called with a single argument to take a slice from the
         given column to the end, or no argument at all to take all columns.
+        A single name starting or ending with `*` will be used to select
+        columns by prefix or suffix, e.g. `cols(\"count_*\")`, unless a column
+        has this exact name. This works in any expression, not only in
+        aggregations.
 
     - err(msg) -> error
         Make the expression return a custom error.
@@ -548,6 +893,8 @@ use the operators in the previous section.
         to another given column by name or position, inclusive.
         Can also be called with a single argument to take a slice from the
         given column to the end, or no argument at all to return all headers.
+        A single name starting or ending with `*` will be used to select
+        headers by prefix or suffix, e.g. `headers(\"count_*\")`.
 
     - index() -> integer?
         Return the row's index, if applicable.
@@ -561,6 +908,10 @@ use the operators in the previous section.
     - parse_json(string) -> any
         Parse the given string as JSON.
 
+    - try(expr, fallback?) -> T
+        Evaluate expression and return its result, or the fallback value
+        (null if not given) if evaluation raised an error.
+
     - typeof(value) -> string
         Return type of value.
 
@@ -612,9 +963,6 @@ use the operators in the previous section.
 
 ## Random
 
-    - md5(string) -> string
-        Return the md5 hash of string in hexadecimal representation.
-
     - random() -> float
         Return a random float between 0 and 1.
 
@@ -623,11 +971,11 @@ use the operators in the previous section.
 
 ";
 
-    colorize_functions_help(help)
+pub fn get_moonblade_functions_help() -> String {
+    colorize_functions_help(MOONBLADE_FUNCTIONS_HELP)
 }
 
-pub fn get_moonblade_aggregations_function_help() -> String {
-    let help = "
+pub static MOONBLADE_AGGREGATIONS_FUNCTIONS_HELP: &str = "
 # Available aggregation functions
 
 (use --cheatsheet for a reminder of how the scripting language works)
@@ -646,6 +994,19 @@ Finally, note that expressions returning lists will be understood as multiplexed
 This means that computing `cardinality([source, target])`, for instance, will return
 the number of nodes in a graph represented by a CSV edge list.
 
+Any aggregation can also be followed by a `where` clause, filtering the rows
+it will consider. For instance, `count() where n > 3` will only count rows
+where `n` is greater than 3, and `mean(score) where lang eq \"fr\"` will compute
+the mean of the `score` column only for rows whose `lang` column is \"fr\".
+
+Aggregations can also be applied to a selection of columns at once, by giving
+them a star slice, or a call to the `cols` function, as first argument. They
+will then be expanded into one aggregation per selected column. For instance,
+`sum(*[count_a:count_z])` is equivalent to `sum(count_a), sum(count_b), ...`
+and `mean(cols(\"num_*\"))` will compute the mean of every column whose name
+starts with \"num_\". Note that this means that the values returned by `cols`
+will not be multiplexed, as other lists would be.
+
     - all(<expr>) -> bool
         Returns true if all elements returned by given expression are truthy.
 
@@ -718,6 +1079,10 @@ the number of nodes in a graph represented by a CSV edge list.
         Return the sample covariance of series represented by
         the two given expressions.
 
+    - cv(<expr>) -> number
+        Coefficient of variation, i.e. population standard deviation divided
+        by the mean.
+
     - distinct_values(<expr>, separator?) -> string
         List of sorted distinct values joined by a pipe character ('|') by default or by
         the provided separator.
@@ -725,9 +1090,32 @@ the number of nodes in a graph represented by a CSV edge list.
     - earliest(<expr>) -> datetime
         Earliest datetime returned by given expression.
 
+    - entropy(<expr>) -> number
+        Shannon entropy, in bits, of the distribution of values returned
+        by given expression.
+
     - first(<expr>) -> string
         Return first seen non empty element of the values returned by the given expression.
 
+    - geometric_mean(<expr>) -> number
+        Geometric mean of numerical values. Only defined if all values are
+        strictly positive.
+
+    - gini(<expr>) -> number
+        Gini impurity of the distribution of values returned by given
+        expression, i.e. the probability that two values drawn at random
+        are different.
+
+    - harmonic_mean(<expr>) -> number
+        Harmonic mean of numerical values. Only defined if all values are
+        strictly positive.
+
+    - iqr(<expr>) -> number
+        Interquartile range of numerical values, i.e. q3 minus q1.
+
+    - kurtosis(<expr>) -> number
+        Population excess kurtosis of numerical values.
+
     - latest(<expr>) -> datetime
         Latest datetime returned by given expression.
 
@@ -740,6 +1128,45 @@ the number of nodes in a graph represented by a CSV edge list.
     - lex_last(<expr>) -> string
         Return last string in lexicographical order.
 
+    - linreg_count(<expr>, <expr>) -> number
+        Return the number of data points used by the linear regression of
+        the first expression (y) on the second one (x).
+
+    - linreg_intercept(<expr>, <expr>) -> number
+        Return the intercept of the ordinary least squares linear
+        regression of the first expression (y) on the second one (x).
+
+    - linreg_intercept_stderr(<expr>, <expr>) -> number
+        Return the standard error of the intercept of the linear regression
+        of the first expression (y) on the second one (x).
+
+    - linreg_multi(<expr>, <expr>) -> list[number]
+        Return the coefficients of the ordinary least squares multiple
+        linear regression of the first expression (y) on the list of
+        predictors returned by the second one, starting with the intercept.
+        E.g. `linreg_multi(y, [x1, x2])`.
+
+    - linreg_multi_r2(<expr>, <expr>) -> number
+        Return the coefficient of determination (r²) of the multiple linear
+        regression of the first expression (y) on the list of predictors
+        returned by the second one.
+
+    - linreg_r2(<expr>, <expr>) -> number
+        Return the coefficient of determination (r²) of the linear
+        regression of the first expression (y) on the second one (x).
+
+    - linreg_slope(<expr>, <expr>) -> number
+        Return the slope of the ordinary least squares linear regression of
+        the first expression (y) on the second one (x).
+
+    - linreg_slope_stderr(<expr>, <expr>) -> number
+        Return the standard error of the slope of the linear regression of
+        the first expression (y) on the second one (x).
+
+    - mad(<expr>) -> number
+        Median absolute deviation of numerical values, i.e. the median of the
+        absolute differences between the values and their median.
+
     - min(<expr>) -> number | string
         Minimum numerical value.
 
@@ -789,6 +1216,9 @@ the number of nodes in a graph represented by a CSV edge list.
     - ratio(<expr>) -> number
         Return the ratio of truthy values returned by expression.
 
+    - skewness(<expr>) -> number
+        Population skewness of numerical values.
+
     - stddev(<expr>) -> number
         Population standard deviation. Same as `stddev_pop`.
 
@@ -825,9 +1255,48 @@ the number of nodes in a graph represented by a CSV edge list.
 
     - var_sample(<expr>) -> number
         Sample variance (i.e. using Bessel's correction).
+
+    - weighted_count(<expr>, <weight>) -> number
+        Sum of the weights of rows for which given expression is truthy.
+        Rows with an empty weight are ignored, as for every other weighted
+        aggregation function.
+
+    - weighted_mean(<expr>, <weight>) -> number
+        Weighted mean of numerical values.
+
+    - weighted_median(<expr>, <weight>) -> number
+        Weighted median of numerical values, i.e. the first value whose
+        cumulative weight reaches half of the total weight.
+
+    - weighted_quantile(<expr>, <weight>, p) -> number
+        Weighted quantile of numerical values, i.e. the first value whose
+        cumulative weight reaches p times the total weight.
+
+    - weighted_stddev(<expr>, <weight>) -> number
+        Weighted population standard deviation. Same as `weighted_stddev_pop`.
+
+    - weighted_stddev_pop(<expr>, <weight>) -> number
+        Weighted population standard deviation. Same as `weighted_stddev`.
+
+    - weighted_stddev_sample(<expr>, <weight>) -> number
+        Weighted sample standard deviation, considering weights as
+        frequencies.
+
+    - weighted_sum(<expr>, <weight>) -> number
+        Sum of numerical values multiplied by their weights.
+
+    - weighted_var(<expr>, <weight>) -> number
+        Weighted population variance. Same as `weighted_var_pop`.
+
+    - weighted_var_pop(<expr>, <weight>) -> number
+        Weighted population variance. Same as `weighted_var`.
+
+    - weighted_var_sample(<expr>, <weight>) -> number
+        Weighted sample variance, considering weights as frequencies.
 ";
 
-    colorize_functions_help(help)
+pub fn get_moonblade_aggregations_function_help() -> String {
+    colorize_functions_help(MOONBLADE_AGGREGATIONS_FUNCTIONS_HELP)
 }
 
 #[derive(Default)]
@@ -419,11 +419,13 @@ parallel freq options:
 
 parallel stats options:
     -s, --select <cols>    Columns for which to build statistics.
-    -A, --all              Shorthand for -cq.
+    -A, --all              Shorthand for -cqm.
     -c, --cardinality      Show cardinality and modes.
                            This requires storing all CSV data in memory.
     -q, --quartiles        Show quartiles.
                            This requires storing all CSV data in memory.
+    -m, --moments          Show the coefficient of variation, skewness, kurtosis,
+                           and geometric & harmonic means.
     -a, --approx           Show approximated statistics.
     --nulls                Include empty values in the population size for computing
                            mean and standard deviation.
@@ -461,6 +463,7 @@ struct Args {
     flag_all: bool,
     flag_cardinality: bool,
     flag_quartiles: bool,
+    flag_moments: bool,
     flag_approx: bool,
     flag_nulls: bool,
     flag_output: Option<String>,
@@ -486,6 +489,10 @@ impl Args {
             stats.compute_numbers();
         }
 
+        if self.flag_all || self.flag_moments {
+            stats.compute_moments();
+        }
+
         if self.flag_approx {
             stats.compute_approx();
         }
@@ -0,0 +1,286 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+use indexmap::IndexMap;
+
+use crate::config::{Config, Delimiter};
+use crate::select::SelectColumns;
+use crate::util;
+use crate::CliResult;
+
+use crate::moonblade::GroupAggregationProgram;
+
+use crate::cmd::moonblade::{
+    get_moonblade_aggregations_function_help, get_moonblade_cheatsheet,
+    get_moonblade_functions_help, MoonbladeErrorPolicy,
+};
+
+#[derive(Clone, Copy)]
+enum ColumnOrder {
+    First,
+    Asc,
+    Desc,
+    Count,
+}
+
+impl TryFrom<&str> for ColumnOrder {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Ok(match value {
+            "first" => Self::First,
+            "asc" => Self::Asc,
+            "desc" => Self::Desc,
+            "count" => Self::Count,
+            _ => return Err(format!("unknown column order \"{}\"!", value)),
+        })
+    }
+}
+
+static USAGE: &str = "
+Pivot a CSV file, i.e. turn a \"long\" table into a \"wide\" one, by creating
+a new column for each distinct value found in the given pivot column.
+
+This is conceptually the inverse of the \"melt\" command.
+
+Cells of those new columns will be filled by a custom aggregation expression,
+evaluated over the rows of each group, as defined by the -g/--groupby flag, and
+having the corresponding value in the pivot column.
+
+For instance, given the following file:
+
+country,year,count
+fr,2023,10
+fr,2024,12
+de,2023,7
+fr,2024,3
+
+Running the following command:
+
+    $ xan pivot year 'sum(count)' -g country file.csv
+
+Will produce:
+
+country,2023,2024
+fr,10,15
+de,7,
+
+Combinations of groups and pivot values that do not exist in the file will
+be filled with an empty string, or with the value given to --fill.
+
+Aggregation expressions work exactly as with `xan groupby`. If the expression
+contains more than one aggregation, generated columns will be named after both
+the pivot value and the aggregation, e.g. \"2023_sum(count)\".
+
+For a quick review of the capabilities of the script language, use
+the --cheatsheet flag.
+
+For a list of available aggregation functions, use the --aggs flag.
+
+If you want to list available functions, use the --functions flag.
+
+Usage:
+    xan pivot [options] <column> <expression> [<input>]
+    xan pivot --help
+    xan pivot --cheatsheet
+    xan pivot --aggs
+    xan pivot --functions
+
+pivot options:
+    -g, --groupby <cols>    Columns used to define the rows of the output. If not
+                            given, a single row will be produced.
+    --column-order <order>  Order of the generated columns. One of:
+                              - \"first\": order of first appearance
+                              - \"asc\": lexicographic order
+                              - \"desc\": reverse lexicographic order
+                              - \"count\": decreasing number of rows
+                            [default: first]
+    -l, --limit <n>         Only generate columns for the first <n> values of the
+                            pivot column, using the order given to --column-order.
+    --fill <value>          Value used to fill missing combinations. Defaults to
+                            an empty string.
+    -e, --errors <policy>   What to do with evaluation errors. One of:
+                              - \"panic\": exit on first error
+                              - \"ignore\": ignore row altogether
+                              - \"log\": print error to stderr
+                            [default: panic].
+    -p, --parallel          Whether to use parallelization to speed up computations.
+                            Will automatically select a suitable number of threads to use
+                            based on your number of cores.
+
+Common options:
+    -h, --help               Display this message
+    -o, --output <file>      Write output to <file> instead of stdout.
+    -n, --no-headers         When set, the first row will not be evaled
+                             as headers.
+    -d, --delimiter <arg>    The field delimiter for reading CSV data.
+                             Must be a single character.
+";
+
+#[derive(Deserialize)]
+struct Args {
+    arg_column: SelectColumns,
+    arg_expression: String,
+    arg_input: Option<String>,
+    flag_no_headers: bool,
+    flag_output: Option<String>,
+    flag_delimiter: Option<Delimiter>,
+    flag_aggs: bool,
+    flag_cheatsheet: bool,
+    flag_functions: bool,
+    flag_groupby: Option<SelectColumns>,
+    flag_column_order: String,
+    flag_limit: Option<usize>,
+    flag_fill: Option<String>,
+    flag_errors: String,
+    flag_parallel: bool,
+}
+
+pub fn run(argv: &[&str]) -> CliResult<()> {
+    let args: Args = util::get_args(USAGE, argv)?;
+
+    if args.flag_aggs {
+        println!("{}", get_moonblade_aggregations_function_help());
+        return Ok(());
+    }
+
+    if args.flag_cheatsheet {
+        println!("{}", get_moonblade_cheatsheet());
+        return Ok(());
+    }
+
+    if args.flag_functions {
+        println!("{}", get_moonblade_functions_help());
+        return Ok(());
+    }
+
+    let error_policy = MoonbladeErrorPolicy::try_from_restricted(&args.flag_errors)?;
+    let column_order = ColumnOrder::try_from(args.flag_column_order.as_str())?;
+
+    let rconf = Config::new(&args.arg_input)
+        .delimiter(args.flag_delimiter)
+        .no_headers(args.flag_no_headers)
+        .select(args.arg_column);
+
+    let mut rdr = rconf.reader()?;
+    let mut wtr = Config::new(&args.flag_output).writer()?;
+    let headers = rdr.byte_headers()?.clone();
+
+    let pivot_col = rconf.single_selection(&headers)?;
+
+    let groupby_sel_opt = args
+        .flag_groupby
+        .map(|cols| cols.selection(&headers, !args.flag_no_headers))
+        .transpose()?;
+
+    let mut program = GroupAggregationProgram::parse(&args.arg_expression, &headers)?;
+    let agg_names = program
+        .headers()
+        .map(|name| name.to_vec())
+        .collect::<Vec<_>>();
+
+    // NOTE: we keep track of the pivot values in order of first appearance,
+    // along with their number of rows, so we can order the columns afterwards
+    let mut pivot_values: IndexMap<Vec<u8>, u64> = IndexMap::new();
+
+    let mut record = csv::ByteRecord::new();
+    let mut index: usize = 0;
+
+    while rdr.read_byte_record(&mut record)? {
+        let pivot_value = record[pivot_col].to_vec();
+
+        let mut group = match &groupby_sel_opt {
+            Some(sel) => sel.collect(&record),
+            None => vec![],
+        };
+
+        *pivot_values.entry(pivot_value.clone()).or_insert(0) += 1;
+        group.push(pivot_value);
+
+        program
+            .run_with_record(group, index, &record)
+            .or_else(|error| error_policy.handle_row_error(index, error))?;
+
+        index += 1;
+    }
+
+    let mut columns = pivot_values.into_iter().collect::<Vec<_>>();
+
+    match column_order {
+        ColumnOrder::First => (),
+        ColumnOrder::Asc => columns.sort_by(|a, b| a.0.cmp(&b.0)),
+        ColumnOrder::Desc => columns.sort_by(|a, b| b.0.cmp(&a.0)),
+        ColumnOrder::Count => columns.sort_by_key(|(_, count)| Reverse(*count)),
+    }
+
+    if let Some(limit) = args.flag_limit {
+        columns.truncate(limit);
+    }
+
+    let column_positions = columns
+        .iter()
+        .enumerate()
+        .map(|(i, (value, _))| (value.clone(), i))
+        .collect::<HashMap<_, _>>();
+
+    let mut output_headers = csv::ByteRecord::new();
+
+    if let Some(sel) = &groupby_sel_opt {
+        output_headers.extend(sel.select(&headers));
+    }
+
+    for (value, _) in columns.iter() {
+        if agg_names.len() == 1 {
+            output_headers.push_field(value);
+        } else {
+            for agg_name in agg_names.iter() {
+                let mut name = value.clone();
+                name.push(b'_');
+                name.extend(agg_name);
+
+                output_headers.push_field(&name);
+            }
+        }
+    }
+
+    wtr.write_byte_record(&output_headers)?;
+
+    // NOTE: groups are yielded in order of first appearance, so rows will
+    // also be emitted in the order their group was first seen
+    let mut rows: IndexMap<Vec<Vec<u8>>, Vec<Option<csv::ByteRecord>>> = IndexMap::new();
+
+    for result in program.into_byte_records(args.flag_parallel) {
+        let (mut group, group_record) = error_policy.handle_error(result)?;
+        let pivot_value = group.pop().unwrap();
+
+        let cells = rows
+            .entry(group)
+            .or_insert_with(|| vec![None; columns.len()]);
+
+        if let Some(i) = column_positions.get(&pivot_value) {
+            cells[*i] = Some(group_record);
+        }
+    }
+
+    let fill = args.flag_fill.unwrap_or_default();
+
+    for (group, cells) in rows {
+        record.clear();
+        record.extend(group);
+
+        for cell in cells {
+            match cell {
+                Some(group_record) => record.extend(&group_record),
+                None => {
+                    for _ in 0..agg_names.len() {
+                        record.push_field(fill.as_bytes());
+                    }
+                }
+            }
+        }
+
+        wtr.write_byte_record(&record)?;
+    }
+
+    Ok(wtr.flush()?)
+}
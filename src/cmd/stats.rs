@@ -33,6 +33,11 @@ median             (-q, -A)  - Second quartile, i.e. median, of numerical values
 q3                 (-q, -A)  - Third quartile of numerical values
 variance           (default) - Population variance of numerical values
 stddev             (default) - Population standard deviation of numerical values
+cv                 (-m, -A)  - Coefficient of variation, i.e. stddev over mean
+skewness           (-m, -A)  - Population skewness of numerical values
+kurtosis           (-m, -A)  - Population excess kurtosis of numerical values
+geometric_mean     (-m, -A)  - Geometric mean of strictly positive numerical values
+harmonic_mean      (-m, -A)  - Harmonic mean of strictly positive numerical values
 min                (default) - Minimum numerical value
 max                (default) - Maximum numerical value
 approx_cardinality (-a)      - Approximation of the number of distinct string values
@@ -42,6 +47,12 @@ approx_q3          (-a)      - Approximation of the third quartile of numerical
 cardinality        (-c, -A)  - Number of distinct string values
 mode               (-c, -A)  - Most frequent string value (tie breaking is arbitrary & random!)
 tied_for_mode      (-c, -A)  - Number of values tied for mode
+earliest           (-T)      - Earliest date
+latest             (-T)      - Latest date
+granularity        (-T)      - Finest temporal unit found in dates, e.g. \"day\"
+span_days          (-T)      - Number of days between earliest and latest date
+per_year           (-T)      - Pipe-separated number of dates per year, e.g. \"2023:4|2024:7\"
+invalid_dates      (-T)      - Share of non-empty values that could not be parsed as dates
 lex_first          (default) - First string in lexical order
 lex_last           (default) - Last string in lexical order
 min_length         (default) - Minimum string length
@@ -57,12 +68,22 @@ stats options:
                            into 'xan stats' will disable the use of indexing.
     -g, --groupby <cols>   If given, will compute stats per group as defined by
                            the given column selection.
-    -A, --all              Shorthand for -cq.
+    -A, --all              Shorthand for -cqm.
     -c, --cardinality      Show cardinality and modes.
                            This requires storing all CSV data in memory.
     -q, --quartiles        Show quartiles.
                            This requires storing all CSV data in memory.
+    -m, --moments          Show the coefficient of variation, skewness, kurtosis,
+                           and geometric & harmonic means.
     -a, --approx           Compute approximated statistics.
+    -T, --temporal         Show temporal statistics for columns where dates were
+                           found. Dates without timezone information are
+                           considered to be UTC and times are reported in UTC.
+                           Those statistics do not affect inferred types.
+    -w, --weight <col>     Column containing frequency weights used to compute
+                           the sum, mean, quartiles, variance and stddev. Other
+                           statistics remain unweighted. Rows with an empty weight
+                           are ignored.
     --nulls                Include empty values in the population size for computing
                            mean and standard deviation.
 
@@ -84,7 +105,10 @@ struct Args {
     flag_all: bool,
     flag_cardinality: bool,
     flag_quartiles: bool,
+    flag_moments: bool,
     flag_approx: bool,
+    flag_temporal: bool,
+    flag_weight: Option<SelectColumns>,
     flag_nulls: bool,
     flag_output: Option<String>,
     flag_no_headers: bool,
@@ -99,6 +123,10 @@ impl Args {
             stats.include_nulls();
         }
 
+        if self.flag_weight.is_some() {
+            stats.compute_weighted();
+        }
+
         if self.flag_all || self.flag_cardinality {
             stats.compute_frequencies();
         }
@@ -107,10 +135,18 @@ impl Args {
             stats.compute_numbers();
         }
 
+        if self.flag_all || self.flag_moments {
+            stats.compute_moments();
+        }
+
         if self.flag_approx {
             stats.compute_approx();
         }
 
+        if self.flag_temporal {
+            stats.compute_temporal();
+        }
+
         stats
     }
 }
@@ -134,11 +170,29 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
         .map(|cols| cols.selection(&headers, !args.flag_no_headers))
         .transpose()?;
 
+    let weight_index_opt = args
+        .flag_weight
+        .as_ref()
+        .map(|col| col.single_selection(&headers, !args.flag_no_headers))
+        .transpose()?;
+
     // No need to consider the grouping column when aggregating stats
     if let Some(gsel) = &groupby_sel_opt {
         sel.subtract(gsel);
     }
 
+    // Nor the weight column
+    if let Some(weight_index) = weight_index_opt {
+        sel.remove(weight_index);
+    }
+
+    let get_weight = |record: &csv::ByteRecord| -> CliResult<Option<f64>> {
+        match weight_index_opt {
+            None => Ok(Some(1.0)),
+            Some(weight_index) => Ok(util::parse_weight(&record[weight_index])?),
+        }
+    };
+
     // Nothing was selected
     if sel.is_empty() {
         return Ok(());
@@ -168,6 +222,11 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
             ClusteredInsertHashmap::new();
 
         while rdr.read_byte_record(&mut record)? {
+            let weight = match get_weight(&record)? {
+                Some(weight) => weight,
+                None => continue,
+            };
+
             let group_key: Vec<_> = gsel.select(&record).map(|cell| cell.to_vec()).collect();
 
             groups.insert_with_or_else(
@@ -176,14 +235,14 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
                     let mut fields = (0..sel.len()).map(|_| args.new_stats()).collect::<Vec<_>>();
 
                     for (cell, stats) in sel.select(&record).zip(fields.iter_mut()) {
-                        stats.process(cell);
+                        stats.process_with_weight(cell, weight);
                     }
 
                     fields
                 },
                 |fields| {
                     for (cell, stats) in sel.select(&record).zip(fields.iter_mut()) {
-                        stats.process(cell);
+                        stats.process_with_weight(cell, weight);
                     }
                 },
             );
@@ -214,8 +273,13 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
     let mut record = csv::ByteRecord::new();
 
     while rdr.read_byte_record(&mut record)? {
+        let weight = match get_weight(&record)? {
+            Some(weight) => weight,
+            None => continue,
+        };
+
         for (cell, stats) in sel.select(&record).zip(fields.iter_mut()) {
-            stats.process(cell);
+            stats.process_with_weight(cell, weight);
         }
     }
 
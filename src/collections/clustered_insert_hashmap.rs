@@ -147,6 +147,10 @@ impl<K: Eq + Hash, V> ClusteredInsertHashmap<K, V> {
         self.map.get(key)
     }
 
+    pub fn get_index(&self, index: usize) -> Option<(&K, &V)> {
+        self.map.get_index(index)
+    }
+
     // pub fn values(&self) -> impl Iterator<Item = &V> {
     //     self.map.values()
     // }
@@ -112,6 +112,50 @@ impl<K: Eq + Hash + Send + Ord> ApproxCounter<K> {
     }
 }
 
+// NOTE: weighted counts are floats and cannot be ordered totally, so we
+// always sort the whole table before truncating it instead of relying on
+// a fixed heap. This also means totals are summed in a deterministic order.
+pub struct WeightedCounter<K: Eq + Hash + Send + Ord> {
+    map: HashMap<K, f64>,
+}
+
+impl<K: Eq + Hash + Send + Ord> WeightedCounter<K> {
+    pub fn new() -> Self {
+        Self {
+            map: HashMap::new(),
+        }
+    }
+
+    pub fn add(&mut self, key: K, weight: f64) {
+        self.map
+            .entry(key)
+            .and_modify(|count| *count += weight)
+            .or_insert(weight);
+    }
+
+    pub fn into_total_and_items(
+        self,
+        limit: Option<usize>,
+        parallel: bool,
+    ) -> (f64, Vec<(K, f64)>) {
+        let mut items = self.map.into_iter().collect::<Vec<_>>();
+
+        if parallel {
+            items.par_sort_unstable_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        } else {
+            items.sort_unstable_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        }
+
+        let total = items.iter().map(|(_, c)| c).sum();
+
+        if let Some(k) = limit {
+            items.truncate(k);
+        }
+
+        (total, items)
+    }
+}
+
 pub enum Counter<K: Eq + Hash + Send + Ord> {
     Exact(ExactCounter<K>),
     Approximate(Box<ApproxCounter<K>>),
@@ -117,6 +117,10 @@ impl<T: Ord, V> FixedReverseHeapMap<T, V> {
         self.heap.clear();
     }
 
+    pub fn unordered_iter(&self) -> impl Iterator<Item = (&T, &V)> {
+        self.heap.iter().map(|(Reverse(k), Arbitrary(v))| (k, v))
+    }
+
     pub fn into_unordered_iter(self) -> impl Iterator<Item = (T, V)> {
         self.heap
             .into_iter()
@@ -5,7 +5,7 @@ mod incremental_id;
 mod union_find;
 
 pub use clustered_insert_hashmap::ClusteredInsertHashmap;
-pub use counter::Counter;
+pub use counter::{Counter, WeightedCounter};
 pub use fixed_reverse_heap::{FixedReverseHeap, FixedReverseHeapMap, FixedReverseHeapMapWithTies};
 pub use incremental_id::IncrementalId;
 pub use union_find::{UnionFind, UnionFindMap};
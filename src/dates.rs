@@ -1,4 +1,8 @@
-use jiff::{civil::Date, ToSpan, Unit, Zoned};
+use jiff::{
+    civil::{Date, DateTime},
+    tz::TimeZone,
+    Timestamp, ToSpan, Unit, Zoned,
+};
 use lazy_static::lazy_static;
 use regex::Regex;
 
@@ -56,6 +60,66 @@ pub fn format_partial_date(unit: Unit, date: &Date) -> String {
     }
 }
 
+fn infer_datetime_granularity(datetime: &DateTime) -> Unit {
+    if datetime.second() != 0 || datetime.subsec_nanosecond() != 0 {
+        Unit::Second
+    } else if datetime.minute() != 0 {
+        Unit::Minute
+    } else if datetime.hour() != 0 {
+        Unit::Hour
+    } else {
+        Unit::Day
+    }
+}
+
+// NOTE: values without timezone information are considered to be UTC, so
+// that results do not depend on the system's timezone.
+pub fn parse_temporal_value(string: &str) -> Option<(Unit, Zoned)> {
+    if is_partial_date(string) {
+        let (unit, date) = parse_partial_date(string)?;
+
+        return Some((unit, date.to_zoned(TimeZone::UTC).ok()?));
+    }
+
+    let zoned = if let Ok(zoned) = string.parse::<Zoned>() {
+        zoned
+    } else if let Ok(timestamp) = string.parse::<Timestamp>() {
+        timestamp.to_zoned(TimeZone::UTC)
+    } else {
+        string
+            .parse::<DateTime>()
+            .ok()?
+            .to_zoned(TimeZone::UTC)
+            .ok()?
+    };
+
+    Some((infer_datetime_granularity(&zoned.datetime()), zoned))
+}
+
+// NOTE: times are normalized to UTC so that values found with different
+// offsets remain comparable.
+pub fn format_temporal_value(unit: Unit, zoned: &Zoned) -> String {
+    match unit {
+        Unit::Year | Unit::Month | Unit::Day => format_partial_date(unit, &zoned.date()),
+        _ => zoned.timestamp().strftime("%FT%TZ").to_string(),
+    }
+}
+
+pub fn unit_name(unit: Unit) -> &'static str {
+    match unit {
+        Unit::Year => "year",
+        Unit::Month => "month",
+        Unit::Week => "week",
+        Unit::Day => "day",
+        Unit::Hour => "hour",
+        Unit::Minute => "minute",
+        Unit::Second => "second",
+        Unit::Millisecond => "millisecond",
+        Unit::Microsecond => "microsecond",
+        Unit::Nanosecond => "nanosecond",
+    }
+}
+
 const MINUTES_BOUND: i64 = 60;
 const HOURS_BOUND: i64 = MINUTES_BOUND * 60;
 const DAYS_BOUND: i64 = HOURS_BOUND * 24;
@@ -120,4 +184,33 @@ mod tests {
             assert_eq!(parse_partial_date(string), expected, "{}", string);
         }
     }
+
+    #[test]
+    fn test_parse_temporal_value() {
+        let parse = |string: &str| {
+            parse_temporal_value(string)
+                .map(|(unit, zoned)| (unit_name(unit), format_temporal_value(unit, &zoned)))
+        };
+
+        assert_eq!(parse("test"), None);
+        assert_eq!(parse("2023"), Some(("year", "2023".to_string())));
+        assert_eq!(parse("2023-04"), Some(("month", "2023-04".to_string())));
+        assert_eq!(parse("2023-04-12"), Some(("day", "2023-04-12".to_string())));
+        assert_eq!(
+            parse("2023-04-12T14:00:00"),
+            Some(("hour", "2023-04-12T14:00:00Z".to_string()))
+        );
+        assert_eq!(
+            parse("2023-04-12T14:05:12Z"),
+            Some(("second", "2023-04-12T14:05:12Z".to_string()))
+        );
+        assert_eq!(
+            parse("2023-04-12T14:05:12+02:00"),
+            Some(("second", "2023-04-12T12:05:12Z".to_string()))
+        );
+        assert_eq!(
+            parse("2023-04-12T14:05:12+02:00[Europe/Paris]"),
+            Some(("second", "2023-04-12T12:05:12Z".to_string()))
+        );
+    }
 }
@@ -85,6 +85,8 @@ macro_rules! command_list {
     to          Convert a CSV file to a variety of data formats
     reverse     Reverse rows of CSV data
     transpose   Transpose CSV file
+    pivot       Turn a long table into a wide one
+    melt        Turn a wide table into a long one
 
 ## Split a CSV file into multiple
     split       Split CSV data into chunks
@@ -225,11 +227,13 @@ enum Command {
     Join,
     Map,
     Matrix,
+    Melt,
     Merge,
     Network,
     P,
     Parallel,
     Partition,
+    Pivot,
     Plot,
     Progress,
     Range,
@@ -313,9 +317,11 @@ impl Command {
             Command::Network => cmd::network::run(argv),
             Command::Map => cmd::map::run(argv),
             Command::Matrix => cmd::matrix::run(argv),
+            Command::Melt => cmd::melt::run(argv),
             Command::Merge => cmd::merge::run(argv),
             Command::Parallel | Command::P => cmd::parallel::run(argv),
             Command::Partition => cmd::partition::run(argv),
+            Command::Pivot => cmd::pivot::run(argv),
             Command::Plot => cmd::plot::run(argv),
             Command::Progress => cmd::progress::run(argv),
             Command::Range => cmd::range::run(argv),
@@ -475,6 +481,12 @@ impl From<serde_json::Error> for CliError {
     }
 }
 
+impl From<bincode::Error> for CliError {
+    fn from(value: bincode::Error) -> Self {
+        CliError::Other(value.to_string())
+    }
+}
+
 impl From<()> for CliError {
     fn from(_: ()) -> CliError {
         CliError::Other("unknown error".to_string())
@@ -1,23 +1,40 @@
-use std::collections::hash_map::RandomState;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::BuildHasher;
 
 use hyperloglogplus::{HyperLogLog, HyperLogLogPlus};
 
-#[derive(Debug, Clone)]
+// NOTE: rough size of the dense registers of a precision 16 sketch
+const REGISTERS_SIZE: usize = 1 << 16;
+
+// NOTE: sketches can only be merged if they hash values the same way, which
+// is not the case of std's RandomState, so we use a deterministic hasher.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct DeterministicState;
+
+impl BuildHasher for DeterministicState {
+    type Hasher = DefaultHasher;
+
+    fn build_hasher(&self) -> Self::Hasher {
+        DefaultHasher::new()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ApproxCardinality {
-    register: HyperLogLogPlus<String, RandomState>,
+    register: HyperLogLogPlus<String, DeterministicState>,
     count: Option<usize>,
 }
 
 impl ApproxCardinality {
     pub fn new() -> Self {
         Self {
-            register: HyperLogLogPlus::new(16, RandomState::new()).unwrap(),
+            register: HyperLogLogPlus::new(16, DeterministicState).unwrap(),
             count: None,
         }
     }
 
     pub fn clear(&mut self) {
-        self.register = HyperLogLogPlus::new(16, RandomState::new()).unwrap();
+        self.register = HyperLogLogPlus::new(16, DeterministicState).unwrap();
         self.count = None;
     }
 
@@ -33,6 +50,10 @@ impl ApproxCardinality {
         self.count.expect("not finalized!")
     }
 
+    pub fn heap_size(&self) -> usize {
+        REGISTERS_SIZE
+    }
+
     pub fn merge(&mut self, other: Self) {
         self.register.merge(&other.register).unwrap();
     }
@@ -3,7 +3,7 @@ use tdigest::TDigest;
 const DIGEST_SIZE: usize = 100;
 const BUFFER_SIZE: usize = 512;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ApproxQuantiles {
     digest: Option<TDigest>,
     buffer: Vec<f64>,
@@ -53,6 +53,12 @@ impl ApproxQuantiles {
         self.digest.as_ref().unwrap().estimate_quantile(q)
     }
 
+    pub fn heap_size(&self) -> usize {
+        // NOTE: a centroid is made of two floats
+        DIGEST_SIZE * 2 * std::mem::size_of::<f64>()
+            + self.buffer.capacity() * std::mem::size_of::<f64>()
+    }
+
     pub fn merge(&mut self, other: Self) {
         self.flush();
         self.buffer = other.buffer;
@@ -4,7 +4,7 @@ const SECONDS_PER_HOUR: usize = 60 * 60;
 const SECONDS_PER_DAY: usize = SECONDS_PER_HOUR * 24;
 const SECONDS_PER_YEAR: usize = SECONDS_PER_DAY * 365;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ZonedExtent {
     extent: Option<(Zoned, Zoned)>,
 }
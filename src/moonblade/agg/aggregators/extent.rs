@@ -1,11 +1,22 @@
 use std::cmp::Reverse;
 
 use csv::ByteRecord;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 use crate::collections::FixedReverseHeapMap;
 use crate::moonblade::types::DynamicNumber;
 
-#[derive(Debug, Clone)]
+// NOTE: csv::ByteRecord cannot be serialized as is, so records are serialized
+// as their list of fields by ArgExtent & ArgTop.
+fn record_fields(record: &ByteRecord) -> Vec<&[u8]> {
+    record.iter().collect()
+}
+
+fn record_heap_size(record: &ByteRecord) -> usize {
+    record.as_slice().len() + record.len() * std::mem::size_of::<usize>()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Extent<T: Copy + PartialOrd> {
     extent: Option<(T, T)>,
 }
@@ -64,6 +75,7 @@ impl<T: Copy + PartialOrd> Extent<T> {
 pub type NumericExtent = Extent<DynamicNumber>;
 
 type ArgExtentEntry = (DynamicNumber, (usize, ByteRecord));
+type SerializedArgExtentEntry = (DynamicNumber, (usize, Vec<Vec<u8>>));
 
 #[derive(Debug, Clone)]
 pub struct ArgExtent {
@@ -114,6 +126,13 @@ impl ArgExtent {
         self.extent.as_ref().map(|e| &e.1 .1)
     }
 
+    pub fn heap_size(&self) -> usize {
+        self.extent
+            .as_ref()
+            .map(|(min, max)| record_heap_size(&min.1 .1) + record_heap_size(&max.1 .1))
+            .unwrap_or(0)
+    }
+
     pub fn merge(&mut self, other: Self) {
         match self.extent.as_mut() {
             None => {
@@ -136,6 +155,37 @@ impl ArgExtent {
     }
 }
 
+impl Serialize for ArgExtent {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.extent
+            .as_ref()
+            .map(|((min, (i, min_arg)), (max, (j, max_arg)))| {
+                (
+                    (min, (i, record_fields(min_arg))),
+                    (max, (j, record_fields(max_arg))),
+                )
+            })
+            .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for ArgExtent {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let extent = Option::<(SerializedArgExtentEntry, SerializedArgExtentEntry)>::deserialize(
+            deserializer,
+        )?;
+
+        Ok(Self {
+            extent: extent.map(|((min, (i, min_arg)), (max, (j, max_arg)))| {
+                (
+                    (min, (i, ByteRecord::from(min_arg))),
+                    (max, (j, ByteRecord::from(max_arg))),
+                )
+            }),
+        })
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ArgTop {
     heap: FixedReverseHeapMap<(DynamicNumber, Reverse<usize>), ByteRecord>,
@@ -178,6 +228,15 @@ impl ArgTop {
         self.heap.to_sorted_vec().into_iter().map(|((v, _), _)| v)
     }
 
+    pub fn heap_size(&self) -> usize {
+        self.heap.capacity() * std::mem::size_of::<((DynamicNumber, usize), ByteRecord)>()
+            + self
+                .heap
+                .unordered_iter()
+                .map(|(_, record)| record_heap_size(record))
+                .sum::<usize>()
+    }
+
     pub fn merge(&mut self, other: Self) {
         for (k, v) in other.heap.into_unordered_iter() {
             self.heap.push_with(k, || v);
@@ -185,7 +244,35 @@ impl ArgTop {
     }
 }
 
-#[derive(Debug, Clone)]
+impl Serialize for ArgTop {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let items = self
+            .heap
+            .unordered_iter()
+            .map(|((value, Reverse(index)), record)| (value, index, record_fields(record)))
+            .collect::<Vec<_>>();
+
+        (self.heap.capacity(), items).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for ArgTop {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let (capacity, items) =
+            <(usize, Vec<(DynamicNumber, usize, Vec<Vec<u8>>)>)>::deserialize(deserializer)?;
+
+        let mut top = Self::new(capacity);
+
+        for (value, index, fields) in items {
+            top.heap
+                .push_with((value, Reverse(index)), || ByteRecord::from(fields));
+        }
+
+        Ok(top)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LexicographicExtent {
     extent: Option<(String, String)>,
 }
@@ -222,6 +309,13 @@ impl LexicographicExtent {
         self.extent.as_ref().map(|e| e.1.clone())
     }
 
+    pub fn heap_size(&self) -> usize {
+        self.extent
+            .as_ref()
+            .map(|(min, max)| min.capacity() + max.capacity())
+            .unwrap_or(0)
+    }
+
     pub fn merge(&mut self, other: Self) {
         match self.extent.as_mut() {
             None => {
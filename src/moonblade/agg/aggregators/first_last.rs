@@ -1,9 +1,17 @@
 use crate::moonblade::types::DynamicValue;
 
+fn item_heap_size(item: &Option<(usize, DynamicValue)>) -> usize {
+    match item {
+        Some((_, DynamicValue::String(string))) => string.len(),
+        Some((_, DynamicValue::Bytes(bytes))) => bytes.len(),
+        _ => 0,
+    }
+}
+
 // NOTE: I am splitting first and last because first can be more efficient
 // This is typically not the case for extents where the amount of copying
 // is mostly arbitrary
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct First {
     item: Option<(usize, DynamicValue)>,
 }
@@ -27,6 +35,10 @@ impl First {
         self.item.as_ref().map(|p| p.1.clone())
     }
 
+    pub fn heap_size(&self) -> usize {
+        item_heap_size(&self.item)
+    }
+
     pub fn merge(&mut self, other: Self) {
         match self.item.as_ref() {
             None => self.item = other.item,
@@ -41,7 +53,7 @@ impl First {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Last {
     item: Option<(usize, DynamicValue)>,
 }
@@ -63,6 +75,10 @@ impl Last {
         self.item.as_ref().map(|p| p.1.clone())
     }
 
+    pub fn heap_size(&self) -> usize {
+        item_heap_size(&self.item)
+    }
+
     pub fn merge(&mut self, other: Self) {
         match self.item.as_ref() {
             None => self.item = other.item,
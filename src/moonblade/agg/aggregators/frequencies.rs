@@ -1,29 +1,37 @@
 use std::cmp::{Ordering, Reverse};
-use std::collections::HashMap;
+use std::collections::{hash_map::Entry, HashMap};
 
 use crate::collections::FixedReverseHeap;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Frequencies {
     counter: HashMap<String, u64>,
+    keys_size: usize,
 }
 
 impl Frequencies {
     pub fn new() -> Self {
         Self {
             counter: HashMap::new(),
+            keys_size: 0,
         }
     }
 
     pub fn clear(&mut self) {
         self.counter.clear();
+        self.keys_size = 0;
     }
 
     pub fn add_count(&mut self, value: String, count: u64) {
-        self.counter
-            .entry(value)
-            .and_modify(|current| *current += count)
-            .or_insert(count);
+        match self.counter.entry(value) {
+            Entry::Occupied(mut entry) => {
+                *entry.get_mut() += count;
+            }
+            Entry::Vacant(entry) => {
+                self.keys_size += entry.key().len();
+                entry.insert(count);
+            }
+        }
     }
 
     pub fn add(&mut self, value: String) {
@@ -102,12 +110,46 @@ impl Frequencies {
         self.counter.len()
     }
 
+    fn probabilities(&self) -> impl Iterator<Item = f64> + '_ {
+        let total = self.counter.values().sum::<u64>() as f64;
+
+        self.counter
+            .values()
+            .map(move |count| *count as f64 / total)
+    }
+
+    // NOTE: Shannon entropy, in bits
+    pub fn entropy(&self) -> Option<f64> {
+        if self.counter.is_empty() {
+            return None;
+        }
+
+        Some(
+            self.probabilities()
+                .fold(0.0, |entropy, p| entropy - p * p.log2()),
+        )
+    }
+
+    // NOTE: Gini impurity, i.e. the probability that two values drawn at
+    // random are different
+    pub fn gini(&self) -> Option<f64> {
+        if self.counter.is_empty() {
+            return None;
+        }
+
+        Some(1.0 - self.probabilities().map(|p| p * p).sum::<f64>())
+    }
+
     pub fn join(&self, separator: &str) -> String {
         let mut keys: Vec<_> = self.counter.keys().map(|k| k.as_str()).collect();
         keys.sort_unstable();
         keys.join(separator)
     }
 
+    pub fn heap_size(&self) -> usize {
+        self.counter.capacity() * std::mem::size_of::<(String, u64)>() + self.keys_size
+    }
+
     pub fn merge(&mut self, other: Self) {
         for (key, count) in other.counter {
             self.add_count(key, count);
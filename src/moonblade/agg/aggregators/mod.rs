@@ -6,10 +6,13 @@ mod dates;
 mod extent;
 mod first_last;
 mod frequencies;
+mod moments;
 mod numbers;
+mod regression;
 mod sum;
 mod types;
 mod values;
+mod weighted;
 mod welford;
 
 pub use all_any::AllAny;
@@ -20,8 +23,11 @@ pub use dates::ZonedExtent;
 pub use extent::{ArgExtent, ArgTop, Extent, LexicographicExtent, NumericExtent};
 pub use first_last::{First, Last};
 pub use frequencies::Frequencies;
+pub use moments::{Moments, PowerMeans};
 pub use numbers::{MedianType, Numbers};
+pub use regression::LinearRegression;
 pub use sum::Sum;
 pub use types::Types;
 pub use values::Values;
+pub use weighted::{WeightedCount, WeightedNumbers, WeightedWelford};
 pub use welford::{CovarianceWelford, Welford};
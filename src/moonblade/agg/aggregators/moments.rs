@@ -0,0 +1,237 @@
+// NOTE: this is an extension of Welford's online algorithm to the third and
+// fourth central moments, along with the formulas needed to merge them.
+// Ref: https://en.wikipedia.org/wiki/Algorithms_for_calculating_variance#Higher-order_statistics
+// Ref: Pébay, P. (2008). Formulas for robust, one-pass parallel computation of
+// covariances and arbitrary-order statistical moments.
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Moments {
+    count: usize,
+    mean: f64,
+    m2: f64,
+    m3: f64,
+    m4: f64,
+}
+
+impl Moments {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn clear(&mut self) {
+        self.count = 0;
+        self.mean = 0.0;
+        self.m2 = 0.0;
+        self.m3 = 0.0;
+        self.m4 = 0.0;
+    }
+
+    pub fn add(&mut self, value: f64) {
+        let n1 = self.count as f64;
+        self.count += 1;
+        let n = self.count as f64;
+
+        let delta = value - self.mean;
+        let delta_n = delta / n;
+        let delta_n2 = delta_n * delta_n;
+        let term1 = delta * delta_n * n1;
+
+        self.mean += delta_n;
+        self.m4 += term1 * delta_n2 * (n * n - 3.0 * n + 3.0) + 6.0 * delta_n2 * self.m2
+            - 4.0 * delta_n * self.m3;
+        self.m3 += term1 * delta_n * (n - 2.0) - 3.0 * delta_n * self.m2;
+        self.m2 += term1;
+    }
+
+    // NOTE: population skewness, i.e. the Fisher-Pearson coefficient
+    pub fn skewness(&self) -> Option<f64> {
+        if self.count < 1 || self.m2 == 0.0 {
+            return None;
+        }
+
+        Some((self.count as f64).sqrt() * self.m3 / self.m2.powf(1.5))
+    }
+
+    // NOTE: population excess kurtosis, i.e. 0 for a normal distribution
+    pub fn kurtosis(&self) -> Option<f64> {
+        if self.count < 1 || self.m2 == 0.0 {
+            return None;
+        }
+
+        Some(self.count as f64 * self.m4 / (self.m2 * self.m2) - 3.0)
+    }
+
+    pub fn merge(&mut self, other: Self) {
+        if other.count == 0 {
+            return;
+        }
+
+        if self.count == 0 {
+            *self = other;
+            return;
+        }
+
+        let na = self.count as f64;
+        let nb = other.count as f64;
+        let n = na + nb;
+
+        let delta = other.mean - self.mean;
+        let delta2 = delta * delta;
+        let delta3 = delta * delta2;
+        let delta4 = delta2 * delta2;
+
+        let m4 = self.m4
+            + other.m4
+            + delta4 * na * nb * (na * na - na * nb + nb * nb) / (n * n * n)
+            + 6.0 * delta2 * (na * na * other.m2 + nb * nb * self.m2) / (n * n)
+            + 4.0 * delta * (na * other.m3 - nb * self.m3) / n;
+
+        let m3 = self.m3
+            + other.m3
+            + delta3 * na * nb * (na - nb) / (n * n)
+            + 3.0 * delta * (na * other.m2 - nb * self.m2) / n;
+
+        let m2 = self.m2 + other.m2 + delta2 * na * nb / n;
+
+        self.mean = (na * self.mean + nb * other.mean) / n;
+        self.m2 = m2;
+        self.m3 = m3;
+        self.m4 = m4;
+        self.count += other.count;
+    }
+}
+
+// NOTE: geometric and harmonic means are only defined here for strictly
+// positive numbers, and will therefore return nothing as soon as a
+// non-positive number was seen.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PowerMeans {
+    count: usize,
+    log_sum: f64,
+    inverse_sum: f64,
+    non_positive: bool,
+}
+
+impl PowerMeans {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn clear(&mut self) {
+        self.count = 0;
+        self.log_sum = 0.0;
+        self.inverse_sum = 0.0;
+        self.non_positive = false;
+    }
+
+    pub fn add(&mut self, value: f64) {
+        self.count += 1;
+
+        if value <= 0.0 {
+            self.non_positive = true;
+            return;
+        }
+
+        self.log_sum += value.ln();
+        self.inverse_sum += 1.0 / value;
+    }
+
+    pub fn geometric_mean(&self) -> Option<f64> {
+        if self.count == 0 || self.non_positive {
+            return None;
+        }
+
+        Some((self.log_sum / self.count as f64).exp())
+    }
+
+    pub fn harmonic_mean(&self) -> Option<f64> {
+        if self.count == 0 || self.non_positive {
+            return None;
+        }
+
+        Some(self.count as f64 / self.inverse_sum)
+    }
+
+    pub fn merge(&mut self, other: Self) {
+        self.count += other.count;
+        self.log_sum += other.log_sum;
+        self.inverse_sum += other.inverse_sum;
+        self.non_positive = self.non_positive || other.non_positive;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: Option<f64>, b: f64) {
+        let a = a.unwrap();
+        assert!((a - b).abs() < 1e-9, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn test_moments() {
+        let numbers = [2.0, 8.0, 0.0, 4.0, 1.0, 9.0, 9.0, 0.0];
+
+        let mut moments = Moments::new();
+
+        for n in numbers {
+            moments.add(n);
+        }
+
+        // NOTE: values checked against scipy.stats.skew & kurtosis
+        assert_close(moments.skewness(), 0.2650554122698573);
+        assert_close(moments.kurtosis(), -1.6660010752838508);
+
+        // Merging
+        let mut left = Moments::new();
+        let mut right = Moments::new();
+
+        for n in numbers[..3].iter().copied() {
+            left.add(n);
+        }
+
+        for n in numbers[3..].iter().copied() {
+            right.add(n);
+        }
+
+        left.merge(right);
+
+        assert_close(left.skewness(), moments.skewness().unwrap());
+        assert_close(left.kurtosis(), moments.kurtosis().unwrap());
+
+        let mut empty = Moments::new();
+        empty.merge(moments.clone());
+        assert_eq!(empty, moments);
+
+        // Constant series
+        let mut constant = Moments::new();
+        constant.add(3.0);
+        constant.add(3.0);
+        assert_eq!(constant.skewness(), None);
+        assert_eq!(Moments::new().kurtosis(), None);
+    }
+
+    #[test]
+    fn test_power_means() {
+        let mut means = PowerMeans::new();
+
+        for n in [1.0, 4.0, 16.0] {
+            means.add(n);
+        }
+
+        assert_close(means.geometric_mean(), 4.0);
+        assert_close(means.harmonic_mean(), 3.0 / (1.0 + 0.25 + 0.0625));
+
+        let mut other = PowerMeans::new();
+        other.add(4.0);
+        means.merge(other);
+
+        assert_close(means.geometric_mean(), 4.0);
+
+        means.add(0.0);
+        assert_eq!(means.geometric_mean(), None);
+        assert_eq!(means.harmonic_mean(), None);
+        assert_eq!(PowerMeans::new().geometric_mean(), None);
+    }
+}
@@ -11,7 +11,7 @@ pub enum MedianType {
     High,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Numbers {
     numbers: Vec<DynamicNumber>,
 }
@@ -139,6 +139,22 @@ impl Numbers {
         }
     }
 
+    pub fn iqr(&self) -> Option<DynamicNumber> {
+        self.quartiles().map(|q| q[2] - q[0])
+    }
+
+    // NOTE: median absolute deviation, without any scaling constant
+    pub fn mad(&self) -> Option<DynamicNumber> {
+        let median = self.median(&MedianType::Interpolation)?;
+
+        let mut deviations = Self {
+            numbers: self.numbers.iter().map(|n| (*n - median).abs()).collect(),
+        };
+
+        deviations.finalize(false);
+        deviations.median(&MedianType::Interpolation)
+    }
+
     pub fn sparkline(&self, bins: usize) -> String {
         if self.numbers.is_empty() {
             return " ".repeat(bins);
@@ -178,6 +194,10 @@ impl Numbers {
         line
     }
 
+    pub fn heap_size(&self) -> usize {
+        self.numbers.capacity() * std::mem::size_of::<DynamicNumber>()
+    }
+
     pub fn merge(&mut self, other: Self) {
         self.numbers.extend(other.numbers);
     }
@@ -311,4 +331,19 @@ mod tests {
             ])
         );
     }
+
+    #[test]
+    fn test_robust_statistics() {
+        let mut numbers = Numbers::from(vec![1, 1, 2, 2, 4, 6, 9]);
+        numbers.finalize(false);
+
+        assert_eq!(numbers.mad(), Some(DynamicNumber::Integer(1)));
+        assert_eq!(numbers.iqr(), Some(DynamicNumber::Float(3.5)));
+
+        let mut empty = Numbers::new();
+        empty.finalize(false);
+
+        assert_eq!(empty.mad(), None);
+        assert_eq!(empty.iqr(), None);
+    }
 }
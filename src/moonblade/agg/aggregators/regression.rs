@@ -0,0 +1,273 @@
+// NOTE: ordinary least squares multiple regression, computed by accumulating
+// the normal equations, which are then solved using Gaussian elimination
+// with partial pivoting. This is fine for exploratory modeling, with a small
+// number of predictors, but can be numerically unstable when predictors are
+// strongly collinear.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LinearRegression {
+    count: usize,
+    xtx: Vec<f64>,
+    xty: Vec<f64>,
+    sum_y: f64,
+    sum_y2: f64,
+    // NOTE: expected and actual number of predictors, recorded when merging
+    // regressions fitted on a different number of predictors, e.g. when
+    // processing chunks of a file in parallel.
+    inconsistent_predictors: Option<(usize, usize)>,
+}
+
+impl LinearRegression {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn clear(&mut self) {
+        self.count = 0;
+        self.xtx.clear();
+        self.xty.clear();
+        self.sum_y = 0.0;
+        self.sum_y2 = 0.0;
+        self.inconsistent_predictors = None;
+    }
+
+    // NOTE: the intercept is counted as a dimension
+    fn dimension(&self) -> usize {
+        self.xty.len()
+    }
+
+    pub fn predictors(&self) -> Option<usize> {
+        if self.count == 0 {
+            None
+        } else {
+            Some(self.dimension() - 1)
+        }
+    }
+
+    pub fn inconsistent_predictors(&self) -> Option<(usize, usize)> {
+        self.inconsistent_predictors
+    }
+
+    pub fn add(&mut self, y: f64, xs: &[f64]) {
+        let d = xs.len() + 1;
+
+        if self.count == 0 {
+            self.xtx = vec![0.0; d * d];
+            self.xty = vec![0.0; d];
+        }
+
+        let row = |i: usize| if i == 0 { 1.0 } else { xs[i - 1] };
+
+        for i in 0..d {
+            let xi = row(i);
+
+            self.xty[i] += xi * y;
+
+            for j in 0..d {
+                self.xtx[i * d + j] += xi * row(j);
+            }
+        }
+
+        self.count += 1;
+        self.sum_y += y;
+        self.sum_y2 += y * y;
+    }
+
+    pub fn coefficients(&self) -> Option<Vec<f64>> {
+        let d = self.dimension();
+
+        if self.count < d || d == 0 {
+            return None;
+        }
+
+        // Augmented matrix
+        let mut m = (0..d)
+            .map(|i| {
+                let mut row = self.xtx[i * d..(i + 1) * d].to_vec();
+                row.push(self.xty[i]);
+                row
+            })
+            .collect::<Vec<_>>();
+
+        for col in 0..d {
+            let pivot = (col..d).max_by(|a, b| m[*a][col].abs().total_cmp(&m[*b][col].abs()))?;
+
+            if m[pivot][col].abs() < 1e-12 {
+                return None;
+            }
+
+            m.swap(col, pivot);
+
+            let (upper, lower) = m.split_at_mut(col + 1);
+            let pivot_row = &upper[col];
+
+            for row in lower.iter_mut() {
+                let factor = row[col] / pivot_row[col];
+
+                for (a, b) in row[col..].iter_mut().zip(&pivot_row[col..]) {
+                    *a -= factor * b;
+                }
+            }
+        }
+
+        let mut coefficients = vec![0.0; d];
+
+        for row in (0..d).rev() {
+            let mut value = m[row][d];
+
+            for k in (row + 1)..d {
+                value -= m[row][k] * coefficients[k];
+            }
+
+            coefficients[row] = value / m[row][row];
+        }
+
+        Some(coefficients)
+    }
+
+    pub fn r_squared(&self) -> Option<f64> {
+        let coefficients = self.coefficients()?;
+
+        let n = self.count as f64;
+        let total_sum_of_squares = self.sum_y2 - self.sum_y * self.sum_y / n;
+
+        if total_sum_of_squares <= 0.0 {
+            return Some(1.0);
+        }
+
+        let explained = coefficients
+            .iter()
+            .zip(self.xty.iter())
+            .map(|(b, xy)| b * xy)
+            .sum::<f64>();
+
+        let residual_sum_of_squares = (self.sum_y2 - explained).max(0.0);
+
+        Some(1.0 - residual_sum_of_squares / total_sum_of_squares)
+    }
+
+    pub fn merge(&mut self, other: Self) {
+        if other.count == 0 {
+            return;
+        }
+
+        if self.count == 0 {
+            *self = other;
+            return;
+        }
+
+        self.inconsistent_predictors = self
+            .inconsistent_predictors
+            .or(other.inconsistent_predictors);
+
+        if self.dimension() != other.dimension() {
+            self.inconsistent_predictors
+                .get_or_insert((self.dimension() - 1, other.dimension() - 1));
+            return;
+        }
+
+        for (a, b) in self.xtx.iter_mut().zip(other.xtx) {
+            *a += b;
+        }
+
+        for (a, b) in self.xty.iter_mut().zip(other.xty) {
+            *a += b;
+        }
+
+        self.count += other.count;
+        self.sum_y += other.sum_y;
+        self.sum_y2 += other.sum_y2;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() < 1e-9, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn test_linear_regression() {
+        // y = 1 + 2 * x1 - x2
+        let data = [(1.0, 0.0), (2.0, 1.0), (3.0, 5.0), (4.0, 2.0), (5.0, 3.0)];
+
+        let mut regression = LinearRegression::new();
+
+        assert_eq!(regression.predictors(), None);
+        assert_eq!(regression.coefficients(), None);
+
+        for (x1, x2) in data {
+            regression.add(1.0 + 2.0 * x1 - x2, &[x1, x2]);
+        }
+
+        assert_eq!(regression.predictors(), Some(2));
+
+        let coefficients = regression.coefficients().unwrap();
+
+        assert_close(coefficients[0], 1.0);
+        assert_close(coefficients[1], 2.0);
+        assert_close(coefficients[2], -1.0);
+        assert_close(regression.r_squared().unwrap(), 1.0);
+
+        // Same results as simple regression
+        let mut simple = LinearRegression::new();
+
+        for (x, y) in [(1.0, 2.0), (2.0, 4.0), (3.0, 5.0), (4.0, 4.0), (5.0, 5.0)] {
+            simple.add(y, &[x]);
+        }
+
+        let coefficients = simple.coefficients().unwrap();
+
+        assert_close(coefficients[0], 2.2);
+        assert_close(coefficients[1], 0.6);
+        assert_close(simple.r_squared().unwrap(), 0.6);
+
+        // Merging
+        let mut left = LinearRegression::new();
+        let mut right = LinearRegression::new();
+
+        for (i, (x, y)) in [(1.0, 2.0), (2.0, 4.0), (3.0, 5.0), (4.0, 4.0), (5.0, 5.0)]
+            .into_iter()
+            .enumerate()
+        {
+            if i < 2 {
+                left.add(y, &[x]);
+            } else {
+                right.add(y, &[x]);
+            }
+        }
+
+        left.merge(right);
+
+        let coefficients = left.coefficients().unwrap();
+
+        assert_close(coefficients[0], 2.2);
+        assert_close(coefficients[1], 0.6);
+
+        // Collinear predictors
+        let mut collinear = LinearRegression::new();
+
+        for x in [1.0, 2.0, 3.0, 4.0] {
+            collinear.add(x, &[x, 2.0 * x]);
+        }
+
+        assert_eq!(collinear.coefficients(), None);
+
+        // Merging regressions with a different number of predictors
+        let mut two_predictors = regression.clone();
+        two_predictors.merge(simple.clone());
+
+        assert_eq!(two_predictors.inconsistent_predictors(), Some((2, 1)));
+        assert_eq!(two_predictors.count, regression.count);
+
+        let mut empty = LinearRegression::new();
+        empty.merge(two_predictors);
+
+        assert_eq!(empty.inconsistent_predictors(), Some((2, 1)));
+
+        simple.clear();
+
+        assert_eq!(simple.inconsistent_predictors(), None);
+    }
+}
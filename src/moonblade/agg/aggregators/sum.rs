@@ -4,7 +4,7 @@ use crate::moonblade::types::DynamicNumber;
 // NOTE: this sum implementation is using the Kahan-Babuska routine for precision
 // Ref: https://en.wikipedia.org/wiki/Kahan_summation_algorithm
 // Ref: https://github.com/simple-statistics/simple-statistics/blob/main/src/sum.js
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Sum {
     current: Option<DynamicNumber>,
     correction: f64,
@@ -24,52 +24,27 @@ impl Sum {
     }
 
     pub fn add(&mut self, value: DynamicNumber) {
-        if let Some(current_sum) = self.current.as_mut() {
-            match current_sum {
-                DynamicNumber::Float(a) => match value {
-                    DynamicNumber::Float(b) => {
-                        let transition = *a + b;
-
-                        if a.abs() > b.abs() {
-                            self.correction += *a - transition + b;
-                        } else {
-                            self.correction += b - transition + *a;
-                        }
-
-                        *a = transition;
-                    }
-                    DynamicNumber::Integer(b) => {
-                        let b = b as f64;
-
-                        let transition = *a + b;
+        if let Some(current_sum) = self.current {
+            match (current_sum, value) {
+                (DynamicNumber::Integer(a), DynamicNumber::Integer(b)) => {
+                    self.current = a.checked_add(b).map(DynamicNumber::Integer)
+                }
+                (DynamicNumber::Float(_), _) | (_, DynamicNumber::Float(_)) => {
+                    let a = current_sum.as_float();
+                    let b = value.as_float();
 
-                        if a.abs() > b.abs() {
-                            self.correction += *a - transition + b;
-                        } else {
-                            self.correction += b - transition + *a;
-                        }
+                    let transition = a + b;
 
-                        *a = transition;
+                    if a.abs() > b.abs() {
+                        self.correction += a - transition + b;
+                    } else {
+                        self.correction += b - transition + a;
                     }
-                },
-                DynamicNumber::Integer(a) => match value {
-                    DynamicNumber::Float(b) => {
-                        let a = *a as f64;
-
-                        let transition = a + b;
 
-                        if a.abs() > b.abs() {
-                            self.correction += a - transition + b;
-                        } else {
-                            self.correction += b - transition + a;
-                        }
-
-                        self.current = Some(DynamicNumber::Float(transition));
-                    }
-                    DynamicNumber::Integer(b) => {
-                        self.current = a.checked_add(b).map(DynamicNumber::Integer)
-                    }
-                },
+                    self.current = Some(DynamicNumber::Float(transition));
+                }
+                // NOTE: decimals are summed exactly
+                _ => self.current = Some(current_sum + value),
             };
         }
     }
@@ -100,6 +75,12 @@ impl Sum {
     }
 }
 
+impl Default for Sum {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -119,4 +100,18 @@ mod tests {
 
         assert_eq!(sum.get(), Some(DynamicNumber::Float(10005.85987)));
     }
+
+    #[test]
+    fn test_decimal_summation() {
+        let mut sum = Sum::new();
+        sum.add(DynamicNumber::Decimal("0.1".parse().unwrap()));
+        sum.add(DynamicNumber::Decimal("0.2".parse().unwrap()));
+        sum.add(DynamicNumber::Integer(1));
+
+        assert_eq!(sum.get().unwrap().to_string(), "1.3");
+
+        sum.add(DynamicNumber::Float(0.5));
+
+        assert_eq!(sum.get(), Some(DynamicNumber::Float(1.8)));
+    }
 }
@@ -5,7 +5,7 @@ const TYPE_INT: u8 = 3;
 const TYPE_DATE: u8 = 4;
 const TYPE_URL: u8 = 5;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Types {
     bitset: u8,
 }
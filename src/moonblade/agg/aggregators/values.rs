@@ -1,18 +1,24 @@
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Values {
     values: Vec<String>,
+    strings_size: usize,
 }
 
 impl Values {
     pub fn new() -> Self {
-        Self { values: Vec::new() }
+        Self {
+            values: Vec::new(),
+            strings_size: 0,
+        }
     }
 
     pub fn clear(&mut self) {
-        self.values.clear()
+        self.values.clear();
+        self.strings_size = 0;
     }
 
     pub fn add(&mut self, string: String) {
+        self.strings_size += string.len();
         self.values.push(string);
     }
 
@@ -20,7 +26,12 @@ impl Values {
         self.values.join(separator)
     }
 
+    pub fn heap_size(&self) -> usize {
+        self.values.capacity() * std::mem::size_of::<String>() + self.strings_size
+    }
+
     pub fn merge(&mut self, other: Self) {
+        self.strings_size += other.strings_size;
         self.values.extend(other.values);
     }
 }
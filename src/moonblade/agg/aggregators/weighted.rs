@@ -0,0 +1,279 @@
+// NOTE: weights are understood here as frequency weights, i.e. a value having
+// a weight of 3 counts as if it was seen 3 times.
+use rayon::prelude::*;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WeightedCount {
+    truthy: f64,
+}
+
+impl WeightedCount {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn clear(&mut self) {
+        self.truthy = 0.0;
+    }
+
+    pub fn add(&mut self, truthy: bool, weight: f64) {
+        if truthy {
+            self.truthy += weight;
+        }
+    }
+
+    pub fn get_truthy(&self) -> f64 {
+        self.truthy
+    }
+
+    pub fn merge(&mut self, other: Self) {
+        self.truthy += other.truthy;
+    }
+}
+
+// NOTE: this is West's weighted incremental variant of Welford's algorithm
+// Ref: https://en.wikipedia.org/wiki/Algorithms_for_calculating_variance#Weighted_incremental_algorithm
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WeightedWelford {
+    weight: f64,
+    sum: f64,
+    mean: f64,
+    m2: f64,
+}
+
+impl WeightedWelford {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn clear(&mut self) {
+        self.weight = 0.0;
+        self.sum = 0.0;
+        self.mean = 0.0;
+        self.m2 = 0.0;
+    }
+
+    pub fn add(&mut self, value: f64, weight: f64) {
+        if weight == 0.0 {
+            return;
+        }
+
+        self.weight += weight;
+        self.sum += value * weight;
+
+        let delta = value - self.mean;
+        self.mean += (weight / self.weight) * delta;
+        self.m2 += weight * delta * (value - self.mean);
+    }
+
+    pub fn sum(&self) -> Option<f64> {
+        if self.weight == 0.0 {
+            return None;
+        }
+
+        Some(self.sum)
+    }
+
+    pub fn mean(&self) -> Option<f64> {
+        if self.weight == 0.0 {
+            return None;
+        }
+
+        Some(self.mean)
+    }
+
+    pub fn variance(&self) -> Option<f64> {
+        if self.weight == 0.0 {
+            return None;
+        }
+
+        Some(self.m2 / self.weight)
+    }
+
+    pub fn sample_variance(&self) -> Option<f64> {
+        if self.weight <= 1.0 {
+            return None;
+        }
+
+        Some(self.m2 / (self.weight - 1.0))
+    }
+
+    pub fn stdev(&self) -> Option<f64> {
+        self.variance().map(|v| v.sqrt())
+    }
+
+    pub fn sample_stdev(&self) -> Option<f64> {
+        self.sample_variance().map(|v| v.sqrt())
+    }
+
+    pub fn merge(&mut self, other: Self) {
+        if other.weight == 0.0 {
+            return;
+        }
+
+        if self.weight == 0.0 {
+            *self = other;
+            return;
+        }
+
+        let weight = self.weight + other.weight;
+        let delta = other.mean - self.mean;
+
+        self.mean += delta * other.weight / weight;
+        self.m2 += other.m2 + delta * delta * self.weight * other.weight / weight;
+        self.sum += other.sum;
+        self.weight = weight;
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WeightedNumbers {
+    numbers: Vec<(f64, f64)>,
+}
+
+impl WeightedNumbers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn clear(&mut self) {
+        self.numbers.clear();
+    }
+
+    pub fn add(&mut self, value: f64, weight: f64) {
+        if weight == 0.0 {
+            return;
+        }
+
+        self.numbers.push((value, weight));
+    }
+
+    pub fn finalize(&mut self, parallel: bool) {
+        let cmp = |a: &(f64, f64), b: &(f64, f64)| a.0.total_cmp(&b.0);
+
+        if parallel {
+            self.numbers.par_sort_unstable_by(cmp);
+        } else {
+            self.numbers.sort_unstable_by(cmp);
+        }
+    }
+
+    // NOTE: returns the first value whose cumulative weight reaches the
+    // requested fraction of the total weight. When this fraction is reached
+    // exactly, the mean of this value and the next one is returned, so that
+    // results are consistent with the unweighted median when all weights are
+    // equal.
+    pub fn quantile(&self, p: f64) -> Option<f64> {
+        if self.numbers.is_empty() || !(0.0..=1.0).contains(&p) {
+            return None;
+        }
+
+        let total = self.numbers.iter().map(|(_, w)| w).sum::<f64>();
+        let target = total * p;
+
+        let mut cumulative = 0.0;
+
+        for (i, (value, weight)) in self.numbers.iter().enumerate() {
+            cumulative += weight;
+
+            if cumulative >= target {
+                if cumulative == target {
+                    if let Some((next, _)) = self.numbers.get(i + 1) {
+                        return Some((value + next) / 2.0);
+                    }
+                }
+
+                return Some(*value);
+            }
+        }
+
+        self.numbers.last().map(|(value, _)| *value)
+    }
+
+    pub fn quartiles(&self) -> Option<Vec<f64>> {
+        [0.25, 0.5, 0.75]
+            .into_iter()
+            .map(|p| self.quantile(p))
+            .collect()
+    }
+
+    pub fn heap_size(&self) -> usize {
+        self.numbers.capacity() * std::mem::size_of::<(f64, f64)>()
+    }
+
+    pub fn merge(&mut self, other: Self) {
+        self.numbers.extend(other.numbers);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: Option<f64>, b: f64) {
+        let a = a.unwrap();
+        assert!((a - b).abs() < 1e-9, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn test_weighted_welford() {
+        let mut weighted = WeightedWelford::new();
+
+        for (value, weight) in [(1.0, 2.0), (2.0, 1.0), (4.0, 1.0)] {
+            weighted.add(value, weight);
+        }
+
+        assert_eq!(weighted.sum(), Some(8.0));
+        assert_eq!(weighted.mean(), Some(2.0));
+        assert_close(weighted.variance(), 1.5);
+        assert_close(weighted.sample_variance(), 2.0);
+
+        // Equivalence with repeated values
+        let mut repeated = WeightedWelford::new();
+
+        for value in [1.0, 1.0, 2.0, 4.0] {
+            repeated.add(value, 1.0);
+        }
+
+        assert_close(repeated.mean(), 2.0);
+        assert_close(repeated.variance(), 1.5);
+
+        // Merging
+        let mut left = WeightedWelford::new();
+        left.add(1.0, 2.0);
+
+        let mut right = WeightedWelford::new();
+        right.add(2.0, 1.0);
+        right.add(4.0, 1.0);
+
+        left.merge(right);
+
+        assert_eq!(left.sum(), Some(8.0));
+        assert_close(left.mean(), 2.0);
+        assert_close(left.variance(), 1.5);
+        assert_eq!(WeightedWelford::new().mean(), None);
+    }
+
+    #[test]
+    fn test_weighted_numbers() {
+        let mut numbers = WeightedNumbers::new();
+
+        for (value, weight) in [(3.0, 1.0), (1.0, 1.0), (2.0, 1.0), (4.0, 1.0)] {
+            numbers.add(value, weight);
+        }
+
+        numbers.finalize(false);
+
+        assert_eq!(numbers.quantile(0.5), Some(2.5));
+        assert_eq!(numbers.quantile(0.0), Some(1.0));
+        assert_eq!(numbers.quantile(1.0), Some(4.0));
+
+        numbers.add(1.0, 4.0);
+        numbers.finalize(false);
+
+        assert_eq!(numbers.quantile(0.5), Some(1.0));
+        assert_eq!(numbers.quartiles(), Some(vec![1.0, 1.0, 2.5]));
+        assert_eq!(numbers.quantile(1.5), None);
+        assert_eq!(WeightedNumbers::new().quantile(0.5), None);
+    }
+}
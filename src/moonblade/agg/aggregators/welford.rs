@@ -1,11 +1,17 @@
 // NOTE: this is an implementation of Welford's online algorithm
 // Ref: https://en.wikipedia.org/wiki/Algorithms_for_calculating_variance
 // Ref: https://en.wikipedia.org/wiki/Standard_deviation
-#[derive(Debug, Clone, Default, PartialEq)]
+use super::Sum;
+use crate::moonblade::decimal::Decimal;
+use crate::moonblade::types::DynamicNumber;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct Welford {
     count: usize,
     mean: f64,
     m2: f64,
+    // NOTE: exact sum, only relevant to compute the mean of decimals
+    sum: Sum,
 }
 
 impl Welford {
@@ -17,6 +23,7 @@ impl Welford {
         self.count = 0;
         self.mean = 0.0;
         self.m2 = 0.0;
+        self.sum.clear();
     }
 
     pub fn add(&mut self, value: f64) {
@@ -32,6 +39,11 @@ impl Welford {
         self.m2 = m2;
     }
 
+    pub fn add_number(&mut self, value: DynamicNumber) {
+        self.sum.add(value);
+        self.add(value.as_float());
+    }
+
     pub fn mean(&self) -> Option<f64> {
         if self.count == 0 {
             return None;
@@ -40,6 +52,15 @@ impl Welford {
         Some(self.mean)
     }
 
+    // NOTE: the mean is only computed exactly when all values were either
+    // decimals or integers, with at least one decimal.
+    pub fn decimal_mean(&self) -> Option<Decimal> {
+        match self.sum.get()? {
+            DynamicNumber::Decimal(sum) => sum.checked_div(Decimal::from(self.count as i64)),
+            _ => None,
+        }
+    }
+
     pub fn variance(&self) -> Option<f64> {
         if self.count < 1 {
             return None;
@@ -64,13 +85,22 @@ impl Welford {
         self.sample_variance().map(|v| v.sqrt())
     }
 
+    // NOTE: population standard deviation over the mean
+    pub fn coefficient_of_variation(&self) -> Option<f64> {
+        match (self.stdev(), self.mean()) {
+            (Some(stdev), Some(mean)) if mean != 0.0 => Some(stdev / mean),
+            _ => None,
+        }
+    }
+
     pub fn merge(&mut self, other: Self) {
         if other.count == 0 {
             return;
         }
 
         if self.count == 0 {
-            other.clone_into(self);
+            *self = other;
+            return;
         }
 
         let count1 = self.count as f64;
@@ -84,11 +114,12 @@ impl Welford {
         self.m2 = self.m2 + other.m2 + ((count1 * count2 * mean_diff_squared) / total);
 
         self.count += other.count;
+        self.sum.merge(other.sum);
     }
 }
 
 // NOTE: https://stackoverflow.com/questions/45773857/merging-covariance-from-two-sets-to-create-new-covariance
-#[derive(Debug, Clone, Default, PartialEq)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct CovarianceWelford {
     count: usize,
     mean_x: f64,
@@ -177,13 +208,66 @@ impl CovarianceWelford {
         Some(covariance / (stdev_x * stdev_y))
     }
 
+    // NOTE: the following methods compute the ordinary least squares
+    // regression of y on x.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    pub fn slope(&self) -> Option<f64> {
+        if self.count < 2 || self.m2_x == 0.0 {
+            return None;
+        }
+
+        Some(self.c / self.m2_x)
+    }
+
+    pub fn intercept(&self) -> Option<f64> {
+        self.slope().map(|slope| self.mean_y - slope * self.mean_x)
+    }
+
+    pub fn r_squared(&self) -> Option<f64> {
+        if self.count < 2 || self.m2_x == 0.0 {
+            return None;
+        }
+
+        if self.m2_y == 0.0 {
+            return Some(1.0);
+        }
+
+        Some((self.c * self.c) / (self.m2_x * self.m2_y))
+    }
+
+    fn residual_variance(&self) -> Option<f64> {
+        if self.count < 3 {
+            return None;
+        }
+
+        let slope = self.slope()?;
+        let residual_sum_of_squares = (self.m2_y - slope * self.c).max(0.0);
+
+        Some(residual_sum_of_squares / (self.count - 2) as f64)
+    }
+
+    pub fn slope_stderr(&self) -> Option<f64> {
+        self.residual_variance()
+            .map(|variance| (variance / self.m2_x).sqrt())
+    }
+
+    pub fn intercept_stderr(&self) -> Option<f64> {
+        self.residual_variance().map(|variance| {
+            (variance * (1.0 / self.count as f64 + self.mean_x * self.mean_x / self.m2_x)).sqrt()
+        })
+    }
+
     pub fn merge(&mut self, other: Self) {
         if other.count == 0 {
             return;
         }
 
         if self.count == 0 {
-            other.clone_into(self);
+            *self = other;
+            return;
         }
 
         let count1 = self.count as f64;
@@ -298,4 +382,57 @@ mod tests {
         assert_eq!(welford, welford_left);
         assert_eq!(covariance_welford, covariance_left);
     }
+
+    #[test]
+    fn test_merge_into_empty() {
+        let mut welford = Welford::new();
+        let mut covariance_welford = CovarianceWelford::new();
+
+        for (x, y) in [(1.0, 2.0), (2.0, 4.0), (3.0, 5.0)] {
+            welford.add(x);
+            covariance_welford.add(x, y);
+        }
+
+        let mut welford_empty = Welford::new();
+        welford_empty.merge(welford.clone());
+
+        let mut covariance_empty = CovarianceWelford::new();
+        covariance_empty.merge(covariance_welford.clone());
+
+        assert_eq!(welford_empty, welford);
+        assert_eq!(covariance_empty, covariance_welford);
+        assert_eq!(welford_empty.variance(), Some(2.0 / 3.0));
+    }
+
+    #[test]
+    fn test_linear_regression() {
+        fn assert_close(a: Option<f64>, b: f64) {
+            let a = a.unwrap();
+            assert!((a - b).abs() < 1e-9, "{} != {}", a, b);
+        }
+
+        let xs = [1.0, 2.0, 3.0, 4.0, 5.0];
+        let ys = [2.0, 4.0, 5.0, 4.0, 5.0];
+
+        let mut covariance_welford = CovarianceWelford::new();
+
+        for (x, y) in xs.iter().copied().zip(ys.iter().copied()) {
+            covariance_welford.add(x, y);
+        }
+
+        assert_eq!(covariance_welford.count(), 5);
+        assert_close(covariance_welford.slope(), 0.6);
+        assert_close(covariance_welford.intercept(), 2.2);
+        assert_close(covariance_welford.r_squared(), 0.6);
+        assert_close(covariance_welford.slope_stderr(), 0.08_f64.sqrt());
+        assert_close(covariance_welford.intercept_stderr(), 0.88_f64.sqrt());
+
+        // Degenerate cases
+        covariance_welford.clear();
+        covariance_welford.add(1.0, 2.0);
+        covariance_welford.add(1.0, 3.0);
+
+        assert_eq!(covariance_welford.slope(), None);
+        assert_eq!(covariance_welford.slope_stderr(), None);
+    }
 }
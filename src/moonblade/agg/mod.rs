@@ -3,5 +3,5 @@ mod program;
 mod stats;
 
 pub use aggregators::CovarianceWelford;
-pub use program::{AggregationProgram, GroupAggregationProgram};
+pub use program::{AggregationProgram, GroupAggregationProgram, GroupState};
 pub use stats::Stats;
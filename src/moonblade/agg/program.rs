@@ -1,3 +1,5 @@
+use std::collections::VecDeque;
+use std::rc::Rc;
 use std::sync::Arc;
 
 use csv::ByteRecord;
@@ -5,20 +7,24 @@ use jiff::{civil::DateTime, Unit};
 
 use super::aggregators::{
     AllAny, ApproxCardinality, ApproxQuantiles, ArgExtent, ArgTop, Count, CovarianceWelford, First,
-    Frequencies, Last, LexicographicExtent, MedianType, Numbers, NumericExtent, Sum, Types, Values,
+    Frequencies, Last, LexicographicExtent, LinearRegression, MedianType, Moments, Numbers,
+    NumericExtent, PowerMeans, Sum, Types, Values, WeightedCount, WeightedNumbers, WeightedWelford,
     Welford, ZonedExtent,
 };
 use crate::collections::ClusteredInsertHashmap;
+use crate::moonblade::bytecode::Bytecode;
 use crate::moonblade::error::{ConcretizationError, EvaluationError, SpecifiedEvaluationError};
-use crate::moonblade::interpreter::{
-    concretize_expression, eval_expression, ConcreteExpr, EvaluationContext,
+use crate::moonblade::fuzzy::did_you_mean;
+use crate::moonblade::interpreter::{concretize_expression, ConcreteExpr, EvaluationContext};
+use crate::moonblade::parser::{
+    parse_aggregations, star_slice_to_cols_call, Aggregation, Aggregations, Expr, FunctionCall,
 };
-use crate::moonblade::parser::{parse_aggregations, Aggregations};
 use crate::moonblade::types::{DynamicNumber, DynamicValue, FunctionArguments};
+use crate::moonblade::typing;
 
 macro_rules! build_aggregation_method_enum {
     ($($variant: ident,)+) => {
-        #[derive(Debug, Clone)]
+        #[derive(Debug, Clone, Serialize, Deserialize)]
         enum Aggregator {
             $(
                 $variant($variant),
@@ -54,9 +60,30 @@ macro_rules! build_aggregation_method_enum {
                     Self::Numbers(inner) => {
                         inner.finalize(parallel);
                     }
+                    Self::WeightedNumbers(inner) => {
+                        inner.finalize(parallel);
+                    }
                     _ => (),
                 }
             }
+
+            fn memory_size(&self) -> usize {
+                std::mem::size_of::<Self>()
+                    + match self {
+                        Self::ApproxCardinality(inner) => inner.heap_size(),
+                        Self::ApproxQuantiles(inner) => inner.heap_size(),
+                        Self::ArgExtent(inner) => inner.heap_size(),
+                        Self::ArgTop(inner) => inner.heap_size(),
+                        Self::First(inner) => inner.heap_size(),
+                        Self::Frequencies(inner) => inner.heap_size(),
+                        Self::Last(inner) => inner.heap_size(),
+                        Self::LexicographicExtent(inner) => inner.heap_size(),
+                        Self::Numbers(inner) => inner.heap_size(),
+                        Self::Values(inner) => inner.heap_size(),
+                        Self::WeightedNumbers(inner) => inner.heap_size(),
+                        _ => 0,
+                    }
+            }
         }
     };
 }
@@ -75,9 +102,15 @@ build_aggregation_method_enum!(
     Values,
     LexicographicExtent,
     Frequencies,
+    LinearRegression,
+    Moments,
     Numbers,
+    PowerMeans,
     Sum,
     Types,
+    WeightedCount,
+    WeightedNumbers,
+    WeightedWelford,
     Welford,
     ZonedExtent,
 );
@@ -112,7 +145,7 @@ impl Aggregator {
                         let mut strings = Vec::new();
 
                         for (index, record) in inner.top_records() {
-                            let value = eval_expression(expr, Some(index), &record, context)?;
+                            let value = expr.run(Some(index), &record, context)?;
 
                             strings.push(
                                 value
@@ -132,6 +165,9 @@ impl Aggregator {
             (ConcreteAggregationMethod::Correlation, Self::CovarianceWelford(inner)) => {
                 DynamicValue::from(inner.correlation())
             }
+            (ConcreteAggregationMethod::CoefficientOfVariation, Self::Welford(inner)) => {
+                DynamicValue::from(inner.coefficient_of_variation())
+            }
             (ConcreteAggregationMethod::Count, Self::Count(inner)) => {
                 DynamicValue::from(inner.get_truthy())
             }
@@ -141,6 +177,39 @@ impl Aggregator {
             (ConcreteAggregationMethod::CovarianceSample, Self::CovarianceWelford(inner)) => {
                 DynamicValue::from(inner.sample_covariance())
             }
+            (ConcreteAggregationMethod::LinregCount, Self::CovarianceWelford(inner)) => {
+                DynamicValue::from(inner.count())
+            }
+            (ConcreteAggregationMethod::LinregIntercept, Self::CovarianceWelford(inner)) => {
+                DynamicValue::from(inner.intercept())
+            }
+            (ConcreteAggregationMethod::LinregInterceptStderr, Self::CovarianceWelford(inner)) => {
+                DynamicValue::from(inner.intercept_stderr())
+            }
+            (ConcreteAggregationMethod::LinregR2, Self::CovarianceWelford(inner)) => {
+                DynamicValue::from(inner.r_squared())
+            }
+            (ConcreteAggregationMethod::LinregSlope, Self::CovarianceWelford(inner)) => {
+                DynamicValue::from(inner.slope())
+            }
+            (ConcreteAggregationMethod::LinregSlopeStderr, Self::CovarianceWelford(inner)) => {
+                DynamicValue::from(inner.slope_stderr())
+            }
+            (ConcreteAggregationMethod::LinregMulti, Self::LinearRegression(inner)) => {
+                check_linear_regression(inner).map_err(|err| err.specify("linreg_multi"))?;
+
+                DynamicValue::from(inner.coefficients().map(|coefficients| {
+                    coefficients
+                        .into_iter()
+                        .map(DynamicValue::from)
+                        .collect::<Vec<_>>()
+                }))
+            }
+            (ConcreteAggregationMethod::LinregMultiR2, Self::LinearRegression(inner)) => {
+                check_linear_regression(inner).map_err(|err| err.specify("linreg_multi_r2"))?;
+
+                DynamicValue::from(inner.r_squared())
+            }
             (ConcreteAggregationMethod::Ratio, Self::Count(inner)) => {
                 DynamicValue::from(inner.ratio())
             }
@@ -150,12 +219,30 @@ impl Aggregator {
             (ConcreteAggregationMethod::DistinctValues(separator), Self::Frequencies(inner)) => {
                 DynamicValue::from(inner.join(separator))
             }
+            (ConcreteAggregationMethod::Entropy, Self::Frequencies(inner)) => {
+                DynamicValue::from(inner.entropy())
+            }
             (ConcreteAggregationMethod::First, Self::First(inner)) => {
                 DynamicValue::from(inner.first())
             }
             (ConcreteAggregationMethod::Last, Self::Last(inner)) => {
                 DynamicValue::from(inner.last())
             }
+            (ConcreteAggregationMethod::GeometricMean, Self::PowerMeans(inner)) => {
+                DynamicValue::from(inner.geometric_mean())
+            }
+            (ConcreteAggregationMethod::Gini, Self::Frequencies(inner)) => {
+                DynamicValue::from(inner.gini())
+            }
+            (ConcreteAggregationMethod::HarmonicMean, Self::PowerMeans(inner)) => {
+                DynamicValue::from(inner.harmonic_mean())
+            }
+            (ConcreteAggregationMethod::Iqr, Self::Numbers(inner)) => {
+                DynamicValue::from(inner.iqr())
+            }
+            (ConcreteAggregationMethod::Kurtosis, Self::Moments(inner)) => {
+                DynamicValue::from(inner.kurtosis())
+            }
             (ConcreteAggregationMethod::LexFirst, Self::LexicographicExtent(inner)) => {
                 DynamicValue::from(inner.first())
             }
@@ -181,14 +268,18 @@ impl Aggregator {
                 if let Some((index, record)) = inner.argmin() {
                     match expr_opt {
                         None => DynamicValue::from(*index),
-                        Some(expr) => return eval_expression(expr, Some(*index), record, context),
+                        Some(expr) => return expr.run(Some(*index), record, context),
                     }
                 } else {
                     DynamicValue::None
                 }
             }
-            (ConcreteAggregationMethod::Mean, Self::Welford(inner)) => {
-                DynamicValue::from(inner.mean())
+            (ConcreteAggregationMethod::Mean, Self::Welford(inner)) => match inner.decimal_mean() {
+                Some(mean) => DynamicValue::from(mean),
+                None => DynamicValue::from(inner.mean()),
+            },
+            (ConcreteAggregationMethod::Mad, Self::Numbers(inner)) => {
+                DynamicValue::from(inner.mad())
             }
             (ConcreteAggregationMethod::Median(median_type), Self::Numbers(inner)) => {
                 DynamicValue::from(inner.median(median_type))
@@ -209,7 +300,7 @@ impl Aggregator {
                 if let Some((index, record)) = inner.argmax() {
                     match expr_opt {
                         None => DynamicValue::from(*index),
-                        Some(expr) => return eval_expression(expr, Some(*index), record, context),
+                        Some(expr) => return expr.run(Some(*index), record, context),
                     }
                 } else {
                     DynamicValue::None
@@ -236,6 +327,9 @@ impl Aggregator {
                 ConcreteAggregationMethod::MostCommonValues(k, separator),
                 Self::Frequencies(inner),
             ) => DynamicValue::from(inner.most_common(*k).join(separator)),
+            (ConcreteAggregationMethod::Skewness, Self::Moments(inner)) => {
+                DynamicValue::from(inner.skewness())
+            }
             (ConcreteAggregationMethod::Sparkline(bins), Self::Numbers(inner)) => {
                 DynamicValue::from(inner.sparkline(*bins))
             }
@@ -270,6 +364,30 @@ impl Aggregator {
             (ConcreteAggregationMethod::Values(separator), Self::Values(inner)) => {
                 DynamicValue::from(inner.join(separator))
             }
+            (ConcreteAggregationMethod::WeightedCount, Self::WeightedCount(inner)) => {
+                DynamicValue::from(inner.get_truthy())
+            }
+            (ConcreteAggregationMethod::WeightedMean, Self::WeightedWelford(inner)) => {
+                DynamicValue::from(inner.mean())
+            }
+            (ConcreteAggregationMethod::WeightedQuantile(p), Self::WeightedNumbers(inner)) => {
+                DynamicValue::from(inner.quantile(*p))
+            }
+            (ConcreteAggregationMethod::WeightedStddevPop, Self::WeightedWelford(inner)) => {
+                DynamicValue::from(inner.stdev())
+            }
+            (ConcreteAggregationMethod::WeightedStddevSample, Self::WeightedWelford(inner)) => {
+                DynamicValue::from(inner.sample_stdev())
+            }
+            (ConcreteAggregationMethod::WeightedSum, Self::WeightedWelford(inner)) => {
+                DynamicValue::from(inner.sum())
+            }
+            (ConcreteAggregationMethod::WeightedVarPop, Self::WeightedWelford(inner)) => {
+                DynamicValue::from(inner.variance())
+            }
+            (ConcreteAggregationMethod::WeightedVarSample, Self::WeightedWelford(inner)) => {
+                DynamicValue::from(inner.sample_variance())
+            }
             _ => unreachable!(),
         })
     }
@@ -303,7 +421,7 @@ impl Aggregator {
 // inferred from aggregating sum and count. Also if the user asks for both
 // sum and mean, the sum will only be aggregated once.
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 struct CompositeAggregator {
     methods: Vec<Aggregator>,
 }
@@ -327,6 +445,15 @@ impl CompositeAggregator {
         }
     }
 
+    fn memory_size(&self) -> usize {
+        std::mem::size_of::<Self>()
+            + self
+                .methods
+                .iter()
+                .map(|method| method.memory_size())
+                .sum::<usize>()
+    }
+
     fn add_method(&mut self, method: &ConcreteAggregationMethod) -> usize {
         macro_rules! upsert_aggregator {
             ($variant: ident) => {
@@ -362,9 +489,18 @@ impl CompositeAggregator {
             }
             ConcreteAggregationMethod::CovariancePop
             | ConcreteAggregationMethod::CovarianceSample
-            | ConcreteAggregationMethod::Correlation => {
+            | ConcreteAggregationMethod::Correlation
+            | ConcreteAggregationMethod::LinregCount
+            | ConcreteAggregationMethod::LinregIntercept
+            | ConcreteAggregationMethod::LinregInterceptStderr
+            | ConcreteAggregationMethod::LinregR2
+            | ConcreteAggregationMethod::LinregSlope
+            | ConcreteAggregationMethod::LinregSlopeStderr => {
                 upsert_aggregator!(CovarianceWelford)
             }
+            ConcreteAggregationMethod::LinregMulti | ConcreteAggregationMethod::LinregMultiR2 => {
+                upsert_aggregator!(LinearRegression)
+            }
             ConcreteAggregationMethod::Min | ConcreteAggregationMethod::Max => {
                 // NOTE: if some ArgExtent already exists, we merge into it.
                 match self
@@ -419,11 +555,21 @@ impl CompositeAggregator {
             ConcreteAggregationMethod::Median(_)
             | ConcreteAggregationMethod::Quantile(_)
             | ConcreteAggregationMethod::Quartile(_)
+            | ConcreteAggregationMethod::Iqr
+            | ConcreteAggregationMethod::Mad
             | ConcreteAggregationMethod::Sparkline(_) => {
                 upsert_aggregator!(Numbers)
             }
+            ConcreteAggregationMethod::Skewness | ConcreteAggregationMethod::Kurtosis => {
+                upsert_aggregator!(Moments)
+            }
+            ConcreteAggregationMethod::GeometricMean | ConcreteAggregationMethod::HarmonicMean => {
+                upsert_aggregator!(PowerMeans)
+            }
             ConcreteAggregationMethod::Mode
             | ConcreteAggregationMethod::Modes(_)
+            | ConcreteAggregationMethod::Entropy
+            | ConcreteAggregationMethod::Gini
             | ConcreteAggregationMethod::Cardinality
             | ConcreteAggregationMethod::DistinctValues(_)
             | ConcreteAggregationMethod::MostCommonCounts(_, _)
@@ -434,6 +580,7 @@ impl CompositeAggregator {
                 upsert_aggregator!(Sum)
             }
             ConcreteAggregationMethod::Mean
+            | ConcreteAggregationMethod::CoefficientOfVariation
             | ConcreteAggregationMethod::VarPop
             | ConcreteAggregationMethod::VarSample
             | ConcreteAggregationMethod::StddevPop
@@ -446,6 +593,20 @@ impl CompositeAggregator {
             ConcreteAggregationMethod::Values(_) => {
                 upsert_aggregator!(Values)
             }
+            ConcreteAggregationMethod::WeightedCount => {
+                upsert_aggregator!(WeightedCount)
+            }
+            ConcreteAggregationMethod::WeightedQuantile(_) => {
+                upsert_aggregator!(WeightedNumbers)
+            }
+            ConcreteAggregationMethod::WeightedMean
+            | ConcreteAggregationMethod::WeightedStddevPop
+            | ConcreteAggregationMethod::WeightedStddevSample
+            | ConcreteAggregationMethod::WeightedSum
+            | ConcreteAggregationMethod::WeightedVarPop
+            | ConcreteAggregationMethod::WeightedVarSample => {
+                upsert_aggregator!(WeightedWelford)
+            }
         }
     }
 
@@ -474,7 +635,9 @@ impl CompositeAggregator {
                     Aggregator::Count(count) => {
                         count.add(value.is_truthy());
                     }
-                    Aggregator::CovarianceWelford(_) => unreachable!(),
+                    Aggregator::CovarianceWelford(_) | Aggregator::LinearRegression(_) => {
+                        unreachable!()
+                    }
                     Aggregator::NumericExtent(extent) => {
                         if !value.is_nullish() {
                             extent.add(value.try_as_number()?);
@@ -520,6 +683,16 @@ impl CompositeAggregator {
                             numbers.add(value.try_as_number()?);
                         }
                     }
+                    Aggregator::Moments(moments) => {
+                        if !value.is_nullish() {
+                            moments.add(value.try_as_f64()?);
+                        }
+                    }
+                    Aggregator::PowerMeans(means) => {
+                        if !value.is_nullish() {
+                            means.add(value.try_as_f64()?);
+                        }
+                    }
                     Aggregator::Sum(sum) => {
                         if !value.is_nullish() {
                             sum.add(value.try_as_number()?);
@@ -527,7 +700,7 @@ impl CompositeAggregator {
                     }
                     Aggregator::Welford(variance) => {
                         if !value.is_nullish() {
-                            variance.add(value.try_as_f64()?);
+                            variance.add_number(value.try_as_number()?);
                         }
                     }
                     Aggregator::Types(types) => {
@@ -535,7 +708,9 @@ impl CompositeAggregator {
                             types.set_empty();
                         } else if let Ok(n) = value.try_as_number() {
                             match n {
-                                DynamicNumber::Float(_) => types.set_float(),
+                                DynamicNumber::Float(_) | DynamicNumber::Decimal(_) => {
+                                    types.set_float()
+                                }
                                 DynamicNumber::Integer(_) => types.set_int(),
                             };
                         } else {
@@ -553,6 +728,9 @@ impl CompositeAggregator {
                             values.add(value.try_as_str()?.into_owned());
                         }
                     }
+                    Aggregator::WeightedCount(_)
+                    | Aggregator::WeightedNumbers(_)
+                    | Aggregator::WeightedWelford(_) => unreachable!(),
                 },
                 None => match method {
                     Aggregator::Count(count) => {
@@ -576,13 +754,35 @@ impl CompositeAggregator {
             match method {
                 Aggregator::CovarianceWelford(covariance_welford) => {
                     match (first.is_nullish(), second.is_nullish()) {
-                        (true, false) | (false, true) => return Err(EvaluationError::Custom("unaligned series where given to covariance or correlation functions (both series must have the same number of data points)".to_string())),
+                        (true, false) | (false, true) => return Err(EvaluationError::Custom("unaligned series were given to covariance, correlation or linreg functions (both series must have the same number of data points)".to_string())),
                         (false, false) => {
                             covariance_welford.add(first.try_as_f64()?, second.try_as_f64()?);
                         }
                         _ => ()
                     }
                 }
+                Aggregator::LinearRegression(regression) => {
+                    process_linear_regression(regression, &first, &second)?;
+                }
+                Aggregator::WeightedCount(count) => {
+                    if let Some(weight) = cast_as_weight(&second)? {
+                        count.add(first.is_truthy(), weight);
+                    }
+                }
+                Aggregator::WeightedNumbers(numbers) => {
+                    if !first.is_nullish() {
+                        if let Some(weight) = cast_as_weight(&second)? {
+                            numbers.add(first.try_as_f64()?, weight);
+                        }
+                    }
+                }
+                Aggregator::WeightedWelford(welford) => {
+                    if !first.is_nullish() {
+                        if let Some(weight) = cast_as_weight(&second)? {
+                            welford.add(first.try_as_f64()?, weight);
+                        }
+                    }
+                }
                 _ => unreachable!(),
             }
         }
@@ -606,6 +806,82 @@ impl CompositeAggregator {
     }
 }
 
+// NOTE: rows where the response and all the predictors are empty are ignored.
+// A single predictor can be given without wrapping it in a list.
+fn inconsistent_predictors_error(expected: usize, got: usize) -> EvaluationError {
+    EvaluationError::Custom(format!(
+        "inconsistent number of predictors given to linreg_multi functions: expected {}, got {}",
+        expected, got
+    ))
+}
+
+fn check_linear_regression(regression: &LinearRegression) -> Result<(), EvaluationError> {
+    match regression.inconsistent_predictors() {
+        None => Ok(()),
+        Some((expected, got)) => Err(inconsistent_predictors_error(expected, got)),
+    }
+}
+
+fn process_linear_regression(
+    regression: &mut LinearRegression,
+    response: &DynamicValue,
+    predictors: &DynamicValue,
+) -> Result<(), EvaluationError> {
+    let predictors = match predictors {
+        DynamicValue::List(list) => list.as_slice(),
+        _ => std::slice::from_ref(predictors),
+    };
+
+    let empty = predictors.iter().filter(|x| x.is_nullish()).count();
+
+    if response.is_nullish() && empty == predictors.len() {
+        return Ok(());
+    }
+
+    if response.is_nullish() || empty > 0 {
+        return Err(EvaluationError::Custom("unaligned series were given to linreg_multi functions (response and predictors must all have the same number of data points)".to_string()));
+    }
+
+    if predictors.is_empty() {
+        return Err(EvaluationError::Custom(
+            "linreg_multi functions expect at least one predictor".to_string(),
+        ));
+    }
+
+    if let Some(expected) = regression.predictors() {
+        if expected != predictors.len() {
+            return Err(inconsistent_predictors_error(expected, predictors.len()));
+        }
+    }
+
+    let xs = predictors
+        .iter()
+        .map(|x| x.try_as_f64())
+        .collect::<Result<Vec<_>, _>>()?;
+
+    regression.add(response.try_as_f64()?, &xs);
+
+    Ok(())
+}
+
+// NOTE: rows with an empty weight are ignored
+fn cast_as_weight(value: &DynamicValue) -> Result<Option<f64>, EvaluationError> {
+    if value.is_nullish() {
+        return Ok(None);
+    }
+
+    let weight = value.try_as_f64()?;
+
+    if weight < 0.0 {
+        return Err(EvaluationError::Custom(format!(
+            "weights cannot be negative, got {}",
+            weight
+        )));
+    }
+
+    Ok(Some(weight))
+}
+
 fn cast_as_static_value<T>(
     arg: &ConcreteExpr,
     cast_fn: fn(&DynamicValue) -> Result<T, EvaluationError>,
@@ -636,6 +912,90 @@ fn cast_as_separator(arg_opt: Option<&ConcreteExpr>) -> Result<String, Concretiz
 
 type ArgumentParser = fn(&[ConcreteExpr]) -> Result<ConcreteAggregationMethod, ConcretizationError>;
 
+static AGGREGATION_FUNCTION_NAMES: &[&str] = &[
+    "all",
+    "any",
+    "approx_cardinality",
+    "approx_quantile",
+    "argmax",
+    "argmin",
+    "argtop",
+    "avg",
+    "cardinality",
+    "correlation",
+    "count",
+    "count_days",
+    "count_hours",
+    "count_seconds",
+    "count_years",
+    "covariance",
+    "covariance_pop",
+    "covariance_sample",
+    "cv",
+    "distinct_values",
+    "earliest",
+    "entropy",
+    "first",
+    "geometric_mean",
+    "gini",
+    "harmonic_mean",
+    "iqr",
+    "kurtosis",
+    "last",
+    "latest",
+    "lex_first",
+    "lex_last",
+    "linreg_count",
+    "linreg_intercept",
+    "linreg_intercept_stderr",
+    "linreg_multi",
+    "linreg_multi_r2",
+    "linreg_r2",
+    "linreg_slope",
+    "linreg_slope_stderr",
+    "mad",
+    "max",
+    "mean",
+    "median",
+    "median_high",
+    "median_low",
+    "min",
+    "mode",
+    "modes",
+    "most_common",
+    "most_common_counts",
+    "percentage",
+    "q1",
+    "q2",
+    "q3",
+    "quantile",
+    "ratio",
+    "skewness",
+    "sparkline",
+    "stddev",
+    "stddev_pop",
+    "stddev_sample",
+    "sum",
+    "top",
+    "type",
+    "types",
+    "values",
+    "var",
+    "var_pop",
+    "var_sample",
+    "weighted_count",
+    "weighted_mean",
+    "weighted_median",
+    "weighted_quantile",
+    "weighted_stddev",
+    "weighted_stddev_pop",
+    "weighted_stddev_sample",
+    "weighted_sum",
+    "weighted_var",
+    "weighted_var_pop",
+    "weighted_var_sample",
+];
+
 fn get_function_arguments_parser(name: &str) -> Option<(FunctionArguments, ArgumentParser)> {
     use ConcreteAggregationMethod::*;
 
@@ -650,36 +1010,52 @@ fn get_function_arguments_parser(name: &str) -> Option<(FunctionArguments, Argum
             )?))
         }),
         "argmin" => (FunctionArguments::with_range(1..=2), |args| {
-            Ok(ArgMin(args.last().cloned()))
+            Ok(ArgMin(args.last().map(Bytecode::compile)))
         }),
         "argmax" => (FunctionArguments::with_range(1..=2), |args| {
-            Ok(ArgMax(args.last().cloned()))
+            Ok(ArgMax(args.last().map(Bytecode::compile)))
         }),
         "argtop" => (FunctionArguments::with_range(1..=4), |args| {
             Ok(ArgTop(
                 cast_as_static_value(args.first().unwrap(), DynamicValue::try_as_usize)?,
-                args.get(1).cloned(),
+                args.get(1).map(Bytecode::compile),
                 cast_as_separator(args.get(2))?,
             ))
         }),
         "cardinality" => (FunctionArguments::unary(), |_| Ok(Cardinality)),
-        "correlation" => (FunctionArguments::unary(), |_| Ok(Correlation)),
+        "correlation" => (FunctionArguments::binary(), |_| Ok(Correlation)),
         "count" => (FunctionArguments::unary(), |_| Ok(Count)),
         "count_seconds" => (FunctionArguments::unary(), |_| Ok(CountTime(Unit::Second))),
         "count_hours" => (FunctionArguments::unary(), |_| Ok(CountTime(Unit::Hour))),
         "count_days" => (FunctionArguments::unary(), |_| Ok(CountTime(Unit::Day))),
         "count_years" => (FunctionArguments::unary(), |_| Ok(CountTime(Unit::Year))),
-        "covariance" | "covariance_pop" => (FunctionArguments::unary(), |_| Ok(CovariancePop)),
-        "covariance_sample" => (FunctionArguments::unary(), |_| Ok(CovarianceSample)),
+        "covariance" | "covariance_pop" => (FunctionArguments::binary(), |_| Ok(CovariancePop)),
+        "covariance_sample" => (FunctionArguments::binary(), |_| Ok(CovarianceSample)),
+        "cv" => (FunctionArguments::unary(), |_| Ok(CoefficientOfVariation)),
         "distinct_values" => (FunctionArguments::with_range(1..=2), |args| {
             Ok(DistinctValues(cast_as_separator(args.first())?))
         }),
         "earliest" => (FunctionArguments::unary(), |_| Ok(Earliest)),
+        "entropy" => (FunctionArguments::unary(), |_| Ok(Entropy)),
         "first" => (FunctionArguments::unary(), |_| Ok(First)),
+        "geometric_mean" => (FunctionArguments::unary(), |_| Ok(GeometricMean)),
+        "gini" => (FunctionArguments::unary(), |_| Ok(Gini)),
+        "harmonic_mean" => (FunctionArguments::unary(), |_| Ok(HarmonicMean)),
+        "iqr" => (FunctionArguments::unary(), |_| Ok(Iqr)),
+        "kurtosis" => (FunctionArguments::unary(), |_| Ok(Kurtosis)),
         "latest" => (FunctionArguments::unary(), |_| Ok(Latest)),
         "last" => (FunctionArguments::unary(), |_| Ok(Last)),
         "lex_first" => (FunctionArguments::unary(), |_| Ok(LexFirst)),
         "lex_last" => (FunctionArguments::unary(), |_| Ok(LexLast)),
+        "linreg_count" => (FunctionArguments::binary(), |_| Ok(LinregCount)),
+        "linreg_intercept" => (FunctionArguments::binary(), |_| Ok(LinregIntercept)),
+        "linreg_intercept_stderr" => (FunctionArguments::binary(), |_| Ok(LinregInterceptStderr)),
+        "linreg_multi" => (FunctionArguments::binary(), |_| Ok(LinregMulti)),
+        "linreg_multi_r2" => (FunctionArguments::binary(), |_| Ok(LinregMultiR2)),
+        "linreg_r2" => (FunctionArguments::binary(), |_| Ok(LinregR2)),
+        "linreg_slope" => (FunctionArguments::binary(), |_| Ok(LinregSlope)),
+        "linreg_slope_stderr" => (FunctionArguments::binary(), |_| Ok(LinregSlopeStderr)),
+        "mad" => (FunctionArguments::unary(), |_| Ok(Mad)),
         "min" => (FunctionArguments::unary(), |_| Ok(Min)),
         "max" => (FunctionArguments::unary(), |_| Ok(Max)),
         "avg" | "mean" => (FunctionArguments::unary(), |_| Ok(Mean)),
@@ -720,6 +1096,7 @@ fn get_function_arguments_parser(name: &str) -> Option<(FunctionArguments, Argum
         "var" | "var_pop" => (FunctionArguments::unary(), |_| Ok(VarPop)),
         "var_sample" => (FunctionArguments::unary(), |_| Ok(VarSample)),
         "ratio" => (FunctionArguments::unary(), |_| Ok(Ratio)),
+        "skewness" => (FunctionArguments::unary(), |_| Ok(Skewness)),
         "sparkline" => (FunctionArguments::with_range(1..=2), |args| {
             Ok(Sparkline(match args.first() {
                 Some(arg) => cast_as_static_value(arg, DynamicValue::try_as_usize)?,
@@ -737,6 +1114,24 @@ fn get_function_arguments_parser(name: &str) -> Option<(FunctionArguments, Argum
         }),
         "type" => (FunctionArguments::unary(), |_| Ok(Type)),
         "types" => (FunctionArguments::unary(), |_| Ok(Types)),
+        "weighted_count" => (FunctionArguments::binary(), |_| Ok(WeightedCount)),
+        "weighted_mean" => (FunctionArguments::binary(), |_| Ok(WeightedMean)),
+        "weighted_median" => (FunctionArguments::binary(), |_| Ok(WeightedQuantile(0.5))),
+        "weighted_quantile" => (FunctionArguments::nary(3), |args| {
+            Ok(WeightedQuantile(cast_as_static_value(
+                args.first().unwrap(),
+                DynamicValue::try_as_f64,
+            )?))
+        }),
+        "weighted_stddev" | "weighted_stddev_pop" => {
+            (FunctionArguments::binary(), |_| Ok(WeightedStddevPop))
+        }
+        "weighted_stddev_sample" => (FunctionArguments::binary(), |_| Ok(WeightedStddevSample)),
+        "weighted_sum" => (FunctionArguments::binary(), |_| Ok(WeightedSum)),
+        "weighted_var" | "weighted_var_pop" => {
+            (FunctionArguments::binary(), |_| Ok(WeightedVarPop))
+        }
+        "weighted_var_sample" => (FunctionArguments::binary(), |_| Ok(WeightedVarSample)),
         _ => return None,
     })
 }
@@ -747,10 +1142,11 @@ enum ConcreteAggregationMethod {
     Any,
     ApproxCardinality,
     ApproxQuantile(f64),
-    ArgMin(Option<ConcreteExpr>),
-    ArgMax(Option<ConcreteExpr>),
-    ArgTop(usize, Option<ConcreteExpr>, String),
+    ArgMin(Option<Bytecode>),
+    ArgMax(Option<Bytecode>),
+    ArgTop(usize, Option<Bytecode>, String),
     Cardinality,
+    CoefficientOfVariation,
     Correlation,
     Count,
     CountTime(Unit),
@@ -758,11 +1154,26 @@ enum ConcreteAggregationMethod {
     CovarianceSample,
     DistinctValues(String),
     Earliest,
+    Entropy,
     First,
+    GeometricMean,
+    Gini,
+    HarmonicMean,
+    Iqr,
+    Kurtosis,
     Latest,
     Last,
     LexFirst,
     LexLast,
+    LinregCount,
+    LinregIntercept,
+    LinregInterceptStderr,
+    LinregMulti,
+    LinregMultiR2,
+    LinregR2,
+    LinregSlope,
+    LinregSlopeStderr,
+    Mad,
     Min,
     Max,
     Mean,
@@ -775,6 +1186,7 @@ enum ConcreteAggregationMethod {
     Quartile(usize),
     Quantile(f64),
     Ratio,
+    Skewness,
     Sparkline(usize),
     Sum,
     Values(String),
@@ -785,15 +1197,32 @@ enum ConcreteAggregationMethod {
     Top(usize, String),
     Type,
     Types,
+    WeightedCount,
+    WeightedMean,
+    WeightedQuantile(f64),
+    WeightedStddevPop,
+    WeightedStddevSample,
+    WeightedSum,
+    WeightedVarPop,
+    WeightedVarSample,
 }
 
 impl ConcreteAggregationMethod {
-    fn parse(name: &str, args: &[ConcreteExpr]) -> Result<Self, ConcretizationError> {
+    // NOTE: `skipped` is the number of leading arguments that were already
+    // consumed as expressions to aggregate, and is needed to validate arity.
+    fn parse(
+        name: &str,
+        skipped: usize,
+        args: &[ConcreteExpr],
+    ) -> Result<Self, ConcretizationError> {
         match get_function_arguments_parser(name) {
-            None => Err(ConcretizationError::UnknownFunction(name.to_string())),
+            None => Err(ConcretizationError::UnknownFunction(
+                name.to_string(),
+                did_you_mean(name, AGGREGATION_FUNCTION_NAMES.iter().copied()).map(String::from),
+            )),
             Some((function_arguments, parser)) => {
                 function_arguments
-                    .validate_arity(args.len() + 1)
+                    .validate_arity(args.len() + skipped)
                     .map_err(|invalid_arity| {
                         ConcretizationError::InvalidArity(name.to_string(), invalid_arity)
                     })?;
@@ -810,11 +1239,18 @@ struct ConcreteAggregation {
     method: ConcreteAggregationMethod,
     expr: Option<ConcreteExpr>,
     pair_expr: Option<ConcreteExpr>,
+    filter: Option<ConcreteExpr>,
 }
 
+type ExecutionKey<'a> = (
+    &'a Option<ConcreteExpr>,
+    &'a Option<ConcreteExpr>,
+    &'a Option<ConcreteExpr>,
+);
+
 impl ConcreteAggregation {
-    fn key(&self) -> (&Option<ConcreteExpr>, &Option<ConcreteExpr>) {
-        (&self.expr, &self.pair_expr)
+    fn key(&self) -> ExecutionKey<'_> {
+        (&self.expr, &self.pair_expr, &self.filter)
     }
 }
 
@@ -833,6 +1269,15 @@ fn concretize_aggregations(
             aggregation.args.swap(0, 1);
         }
 
+        // NOTE: simple linear regressions rely on the same aggregator as
+        // covariance, which expects x before y.
+        if aggregation.func_name.starts_with("linreg_")
+            && !aggregation.func_name.starts_with("linreg_multi")
+            && aggregation.args.len() == 2
+        {
+            aggregation.args.swap(0, 1);
+        }
+
         let expr = aggregation
             .args
             .first()
@@ -842,13 +1287,15 @@ fn concretize_aggregations(
         let mut skip: usize = 1;
 
         let pair_expr = if aggregation.args.len() > 1
-            && [
-                "covariance",
-                "covariance_pop",
-                "covariance_sample",
-                "correlation",
-            ]
-            .contains(&aggregation.func_name.as_str())
+            && (aggregation.func_name.starts_with("weighted_")
+                || aggregation.func_name.starts_with("linreg_")
+                || [
+                    "covariance",
+                    "covariance_pop",
+                    "covariance_sample",
+                    "correlation",
+                ]
+                .contains(&aggregation.func_name.as_str()))
         {
             skip = 2;
             Some(concretize_expression(
@@ -859,19 +1306,29 @@ fn concretize_aggregations(
             None
         };
 
+        let filter = aggregation
+            .filter
+            .map(|arg| concretize_expression(arg, headers))
+            .transpose()?;
+
+        for concrete_expr in expr.iter().chain(pair_expr.iter()).chain(filter.iter()) {
+            typing::check(concrete_expr)?;
+        }
+
         let mut args: Vec<ConcreteExpr> = Vec::new();
 
         for arg in aggregation.args.into_iter().skip(skip) {
             args.push(concretize_expression(arg, headers)?);
         }
 
-        let method = ConcreteAggregationMethod::parse(&aggregation.func_name, &args)?;
+        let method = ConcreteAggregationMethod::parse(&aggregation.func_name, skip, &args)?;
 
         let concrete_aggregation = ConcreteAggregation {
             agg_name: aggregation.agg_name,
             method,
             expr,
             pair_expr,
+            filter,
         };
 
         concrete_aggregations.push(concrete_aggregation);
@@ -880,26 +1337,92 @@ fn concretize_aggregations(
     Ok(concrete_aggregations)
 }
 
+// NOTE: aggregations whose first argument is a star slice, or a call to `cols`,
+// are expanded into one aggregation per selected column.
+fn expand_aggregations(
+    aggregations: Aggregations,
+    headers: &ByteRecord,
+) -> Result<Aggregations, ConcretizationError> {
+    let mut expanded_aggregations = Aggregations::new();
+
+    for aggregation in aggregations {
+        let selection = match aggregation.args.first() {
+            Some(Expr::StarSlice(slice)) => star_slice_to_cols_call(slice),
+            Some(expr @ Expr::Func(call)) if call.name == "cols" => expr.clone(),
+            _ => {
+                expanded_aggregations.push(aggregation);
+                continue;
+            }
+        };
+
+        let columns = match concretize_expression(selection, headers)? {
+            ConcreteExpr::List(items) => items
+                .into_iter()
+                .map(|item| match item {
+                    ConcreteExpr::Column(i) => Ok(i),
+                    _ => Err(ConcretizationError::NotStaticallyAnalyzable),
+                })
+                .collect::<Result<Vec<_>, _>>()?,
+            _ => return Err(ConcretizationError::NotStaticallyAnalyzable),
+        };
+
+        for i in columns {
+            let column_name = String::from_utf8_lossy(&headers[i]);
+
+            let agg_name = match aggregation.first_arg_span {
+                Some((start, end)) => format!(
+                    "{}{}{}",
+                    &aggregation.agg_name[..start],
+                    column_name,
+                    &aggregation.agg_name[end..]
+                ),
+                None => format!("{}_{}", aggregation.agg_name, column_name),
+            };
+
+            let mut args = aggregation.args.clone();
+            args[0] = Expr::Func(FunctionCall {
+                name: "col".to_string(),
+                args: vec![(None, Expr::Int(i as i64))],
+            });
+
+            expanded_aggregations.push(Aggregation {
+                agg_name,
+                args,
+                func_name: aggregation.func_name.clone(),
+                filter: aggregation.filter.clone(),
+                first_arg_span: None,
+            });
+        }
+    }
+
+    Ok(expanded_aggregations)
+}
+
 fn prepare(code: &str, headers: &ByteRecord) -> Result<ConcreteAggregations, ConcretizationError> {
-    let parsed_aggregations =
-        parse_aggregations(code).map_err(|_| ConcretizationError::ParseError(code.to_string()))?;
+    let parsed_aggregations = parse_aggregations(code)
+        .map_err(|err| ConcretizationError::ParseError(err.render(code)))?;
 
-    concretize_aggregations(parsed_aggregations, headers)
+    concretize_aggregations(expand_aggregations(parsed_aggregations, headers)?, headers)
 }
 
 // NOTE: each execution unit is iterated upon linearly to aggregate values
 // all while running a minimum number of operations (batched by 1. expression
-// keys and 2. composite aggregation atom).
+// keys, including the optional `where` filter, and 2. composite aggregation atom).
+// Expressions are kept around to compare keys, but are run as bytecode.
 #[derive(Debug, Clone)]
 struct PlannerExecutionUnit {
     expr: Option<ConcreteExpr>,
     pair_expr: Option<ConcreteExpr>,
+    filter: Option<ConcreteExpr>,
+    expr_bytecode: Option<Bytecode>,
+    pair_expr_bytecode: Option<Bytecode>,
+    filter_bytecode: Option<Bytecode>,
     aggregator_blueprint: CompositeAggregator,
 }
 
 impl PlannerExecutionUnit {
-    fn key(&self) -> (&Option<ConcreteExpr>, &Option<ConcreteExpr>) {
-        (&self.expr, &self.pair_expr)
+    fn key(&self) -> ExecutionKey<'_> {
+        (&self.expr, &self.pair_expr, &self.filter)
     }
 }
 
@@ -946,8 +1469,12 @@ impl From<ConcreteAggregations> for ConcreteAggregationPlanner {
                 let aggregator_index = aggregator_blueprint.add_method(&agg.method);
 
                 execution_plan.push(PlannerExecutionUnit {
+                    expr_bytecode: agg.expr.as_ref().map(Bytecode::compile),
+                    pair_expr_bytecode: agg.pair_expr.as_ref().map(Bytecode::compile),
+                    filter_bytecode: agg.filter.as_ref().map(Bytecode::compile),
                     expr: agg.expr,
                     pair_expr: agg.pair_expr,
+                    filter: agg.filter,
                     aggregator_blueprint,
                 });
 
@@ -979,6 +1506,25 @@ impl ConcreteAggregationPlanner {
         self.output_plan.iter().map(|unit| unit.agg_name.as_bytes())
     }
 
+    fn finalize(
+        &self,
+        aggregators: &mut [CompositeAggregator],
+        context: &EvaluationContext,
+        parallel: bool,
+    ) -> Result<ByteRecord, SpecifiedEvaluationError> {
+        for aggregator in aggregators.iter_mut() {
+            aggregator.finalize(parallel);
+        }
+
+        let mut record = ByteRecord::new();
+
+        for value in self.results(aggregators, context) {
+            record.push_field(&value?.serialize_as_bytes());
+        }
+
+        Ok(record)
+    }
+
     fn results<'a>(
         &'a self,
         aggregators: &'a [CompositeAggregator],
@@ -1005,17 +1551,25 @@ fn run_with_record_on_aggregators(
     context: &EvaluationContext,
 ) -> Result<(), SpecifiedEvaluationError> {
     for (unit, aggregator) in planner.execution_plan.iter().zip(aggregators) {
-        let value = match &unit.expr {
+        if let Some(filter) = &unit.filter_bytecode {
+            if !filter.run(Some(index), record, context)?.is_truthy() {
+                continue;
+            }
+        }
+
+        let value = match &unit.expr_bytecode {
             None => None,
-            Some(expr) => Some(eval_expression(expr, Some(index), record, context)?),
+            Some(expr) => Some(expr.run(Some(index), record, context)?),
         };
 
-        if let Some(pair_expr) = &unit.pair_expr {
-            let second_value = eval_expression(pair_expr, Some(index), record, context)?;
+        if let Some(pair_expr) = &unit.pair_expr_bytecode {
+            let second_value = pair_expr.run(Some(index), record, context)?;
 
-            return aggregator
+            aggregator
                 .process_pair(index, value.unwrap(), second_value)
-                .map_err(|err| err.specify("<agg-expr>"));
+                .map_err(|err| err.specify("<agg-expr>"))?;
+
+            continue;
         }
 
         if let Some(DynamicValue::List(list)) = value {
@@ -1087,27 +1641,58 @@ impl AggregationProgram {
     }
 
     pub fn finalize(&mut self, parallel: bool) -> Result<ByteRecord, SpecifiedEvaluationError> {
-        for aggregator in self.aggregators.iter_mut() {
-            aggregator.finalize(parallel);
-        }
+        self.planner
+            .finalize(&mut self.aggregators, &self.context, parallel)
+    }
+}
 
-        let mut record = ByteRecord::new();
+type GroupKey = Vec<Vec<u8>>;
 
-        for value in self.planner.results(&self.aggregators, &self.context) {
-            record.push_field(&value?.serialize_as_bytes());
-        }
+fn group_memory_size(group: &GroupKey, aggregators: &[CompositeAggregator]) -> usize {
+    std::mem::size_of::<(GroupKey, GroupState)>()
+        + group
+            .iter()
+            .map(|cell| std::mem::size_of::<Vec<u8>>() + cell.len())
+            .sum::<usize>()
+        + aggregators_memory_size(aggregators)
+}
 
-        Ok(record)
-    }
+fn aggregators_memory_size(aggregators: &[CompositeAggregator]) -> usize {
+    aggregators
+        .iter()
+        .map(|aggregator| aggregator.memory_size())
+        .sum()
 }
 
-type GroupKey = Vec<Vec<u8>>;
+// NOTE: the aggregation state of a single group, which can be serialized to be
+// spilled to disk, then merged back into a program later on. The index of the
+// first row of the group is kept because the order of the groups map is not
+// the insertion order, and groups must sometimes be merged in the order they
+// were first seen, for order-sensitive aggregations such as values().
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GroupState {
+    first_index: usize,
+    aggregators: Vec<CompositeAggregator>,
+}
+
+impl GroupState {
+    fn merge(&mut self, other: Self) {
+        self.first_index = self.first_index.min(other.first_index);
+
+        for (self_aggregator, other_aggregator) in
+            self.aggregators.iter_mut().zip(other.aggregators)
+        {
+            self_aggregator.merge(other_aggregator);
+        }
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct GroupAggregationProgram {
     planner: ConcreteAggregationPlanner,
-    groups: ClusteredInsertHashmap<GroupKey, Vec<CompositeAggregator>>,
+    groups: ClusteredInsertHashmap<GroupKey, GroupState>,
     context: EvaluationContext,
+    memory: usize,
 }
 
 impl GroupAggregationProgram {
@@ -1119,22 +1704,16 @@ impl GroupAggregationProgram {
             planner,
             groups: ClusteredInsertHashmap::new(),
             context: EvaluationContext::new(headers),
+            memory: 0,
         })
     }
 
     pub fn merge(&mut self, other: Self) {
-        for (key, other_aggregators) in other.groups.into_iter() {
-            self.groups.insert_or_update_with(
-                key,
-                other_aggregators,
-                |self_aggregators, other_aggregators| {
-                    for (self_aggregator, other_aggregator) in
-                        self_aggregators.iter_mut().zip(other_aggregators)
-                    {
-                        self_aggregator.merge(other_aggregator);
-                    }
-                },
-            );
+        for (key, other_state) in other.groups.into_iter() {
+            self.groups
+                .insert_or_update_with(key, other_state, |self_state, other_state| {
+                    self_state.merge(other_state);
+                });
         }
     }
 
@@ -1146,11 +1725,103 @@ impl GroupAggregationProgram {
     ) -> Result<(), SpecifiedEvaluationError> {
         let planner = &self.planner;
 
-        let aggregators = self
+        let state = self.groups.insert_with(group, || GroupState {
+            first_index: index,
+            aggregators: planner.instantiate_aggregators(),
+        });
+
+        run_with_record_on_aggregators(
+            &self.planner,
+            &mut state.aggregators,
+            index,
+            record,
+            &self.context,
+        )
+    }
+
+    // NOTE: same as run_with_record, but also keeping track of the estimated
+    // memory used by the groups, which is only relevant when spilling them.
+    pub fn run_with_record_and_track_memory(
+        &mut self,
+        group: GroupKey,
+        index: usize,
+        record: &ByteRecord,
+    ) -> Result<(), SpecifiedEvaluationError> {
+        let planner = &self.planner;
+        let mut inserted = false;
+
+        let group_size = group_memory_size(&group, &[]);
+
+        let aggregators = &mut self
             .groups
-            .insert_with(group, || planner.instantiate_aggregators());
+            .insert_with(group, || {
+                inserted = true;
+
+                GroupState {
+                    first_index: index,
+                    aggregators: planner.instantiate_aggregators(),
+                }
+            })
+            .aggregators;
+
+        let size_before = if inserted {
+            self.memory += group_size;
+            0
+        } else {
+            aggregators_memory_size(aggregators)
+        };
+
+        let result = run_with_record_on_aggregators(
+            &self.planner,
+            aggregators,
+            index,
+            record,
+            &self.context,
+        );
+
+        self.memory = (self.memory + aggregators_memory_size(aggregators)) - size_before;
+
+        result
+    }
+
+    // NOTE: states must be merged in the same order they were drained in, for
+    // order-sensitive aggregations such as values() to remain correct.
+    pub fn merge_group_state(&mut self, group: GroupKey, state: GroupState) {
+        let group_size = group_memory_size(&group, &state.aggregators);
+        let mut merged_sizes: Option<(usize, usize)> = None;
+
+        self.groups
+            .insert_or_update_with(group, state, |self_state, other_state| {
+                let size_before = aggregators_memory_size(&self_state.aggregators);
+
+                self_state.merge(other_state);
+
+                merged_sizes = Some((
+                    size_before,
+                    aggregators_memory_size(&self_state.aggregators),
+                ));
+            });
+
+        match merged_sizes {
+            None => self.memory += group_size,
+            Some((size_before, size_after)) => {
+                self.memory = (self.memory + size_after) - size_before;
+            }
+        }
+    }
+
+    pub fn drain_group_states(&mut self) -> impl Iterator<Item = (GroupKey, GroupState)> {
+        self.memory = 0;
+
+        std::mem::take(&mut self.groups).into_iter()
+    }
+
+    pub fn groups_count(&self) -> usize {
+        self.groups.len()
+    }
 
-        run_with_record_on_aggregators(&self.planner, aggregators, index, record, &self.context)
+    pub fn estimated_memory(&self) -> usize {
+        self.memory
     }
 
     pub fn headers(&self) -> impl Iterator<Item = &[u8]> {
@@ -1164,20 +1835,155 @@ impl GroupAggregationProgram {
         let planner = self.planner;
         let context = self.context;
 
-        self.groups
-            .into_iter()
-            .map(move |(group, mut aggregators)| {
-                for aggregator in aggregators.iter_mut() {
-                    aggregator.finalize(parallel);
-                }
+        self.groups.into_iter().map(move |(group, mut state)| {
+            let record = planner.finalize(&mut state.aggregators, &context, parallel)?;
+
+            Ok((group, record))
+        })
+    }
+
+    // NOTE: grouping sets are given as the positions, in the group keys, of
+    // the values to keep. Other values will be replaced by the given marker.
+    // Each grouping set is computed by merging the aggregator states of the
+    // finest groups, so we only need to read the data once. Sets are computed,
+    // emitted and dropped one after the other, so that we never hold more than
+    // the finest groups and a single coarser set in memory at once. The finest
+    // groups are consumed when computing the last set. Finest groups are
+    // merged in the order they were first seen, so that order-sensitive
+    // aggregations such as values() remain consistent with the input order.
+    pub fn into_grouping_sets_byte_records(
+        self,
+        grouping_sets: &[Vec<usize>],
+        marker: &[u8],
+        parallel: bool,
+    ) -> impl Iterator<Item = Result<(GroupKey, ByteRecord), SpecifiedEvaluationError>> {
+        let planner = self.planner;
+        let context = self.context;
+        let marker = marker.to_vec();
+
+        let key_len = self.groups.iter().next().map(|(group, _)| group.len());
+        let mut first_seen_order = (0..self.groups.len()).collect::<Vec<_>>();
+        first_seen_order.sort_by_key(|i| self.groups.get_index(*i).unwrap().1.first_index);
+
+        let mut finest_groups = Rc::new(self.groups);
+        let mut remaining_sets: VecDeque<Vec<usize>> = grouping_sets.iter().cloned().collect();
+        let mut current_set: Box<dyn Iterator<Item = (GroupKey, GroupState)>> =
+            Box::new(std::iter::empty());
+
+        std::iter::from_fn(move || loop {
+            if let Some((group, mut state)) = current_set.next() {
+                return Some(
+                    planner
+                        .finalize(&mut state.aggregators, &context, parallel)
+                        .map(|record| (group, record)),
+                );
+            }
+
+            let grouping_set = remaining_sets.pop_front()?;
 
-                let mut record = ByteRecord::new();
+            let is_identity =
+                key_len.is_some_and(|len| (0..len).all(|i| grouping_set.contains(&i)));
 
-                for value in planner.results(&aggregators, &context) {
-                    record.push_field(&value?.serialize_as_bytes());
+            // NOTE: dropping the exhausted set so that we may own the finest groups
+            current_set = Box::new(std::iter::empty());
+
+            current_set = if remaining_sets.is_empty() {
+                let groups = match Rc::try_unwrap(std::mem::take(&mut finest_groups)) {
+                    Ok(groups) => groups,
+                    Err(_) => unreachable!(),
+                };
+
+                if is_identity {
+                    Box::new(groups.into_iter())
+                } else {
+                    let mut groups = groups.into_iter().collect::<Vec<_>>();
+                    groups.sort_by_key(|(_, state)| state.first_index);
+
+                    Box::new(roll_up(groups.into_iter(), &grouping_set, &marker).into_iter())
                 }
+            } else if is_identity {
+                let groups = finest_groups.clone();
 
-                Ok((group, record))
+                Box::new((0..groups.len()).map(move |i| {
+                    let (group, state) = groups.get_index(i).unwrap();
+
+                    (group.clone(), state.clone())
+                }))
+            } else {
+                let groups = first_seen_order.iter().map(|i| {
+                    let (group, state) = finest_groups.get_index(*i).unwrap();
+
+                    (group.clone(), state.clone())
+                });
+
+                Box::new(roll_up(groups, &grouping_set, &marker).into_iter())
+            };
+        })
+    }
+}
+
+fn roll_up<I>(
+    groups: I,
+    grouping_set: &[usize],
+    marker: &[u8],
+) -> ClusteredInsertHashmap<GroupKey, GroupState>
+where
+    I: Iterator<Item = (GroupKey, GroupState)>,
+{
+    let mut rolled_up_groups: ClusteredInsertHashmap<GroupKey, GroupState> =
+        ClusteredInsertHashmap::new();
+
+    for (group, state) in groups {
+        let key = group
+            .into_iter()
+            .enumerate()
+            .map(|(i, value)| {
+                if grouping_set.contains(&i) {
+                    value
+                } else {
+                    marker.to_vec()
+                }
             })
+            .collect();
+
+        rolled_up_groups.insert_or_update_with(key, state, |self_state, other_state| {
+            self_state.merge(other_state);
+        });
+    }
+
+    rolled_up_groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use regex::Regex;
+
+    use crate::cmd::moonblade::MOONBLADE_AGGREGATIONS_FUNCTIONS_HELP;
+
+    #[test]
+    fn test_aggregation_function_names() {
+        for name in AGGREGATION_FUNCTION_NAMES {
+            assert!(
+                get_function_arguments_parser(name).is_some(),
+                "unknown aggregation function {}",
+                name
+            );
+        }
+
+        assert!(AGGREGATION_FUNCTION_NAMES.windows(2).all(|w| w[0] < w[1]));
+
+        let documented = Regex::new(r"(?m)^\s+- ([a-z0-9_]+)\(").unwrap();
+
+        for caps in documented.captures_iter(MOONBLADE_AGGREGATIONS_FUNCTIONS_HELP) {
+            let name = &caps[1];
+
+            assert!(
+                AGGREGATION_FUNCTION_NAMES.contains(&name),
+                "documented aggregation function {} is missing from AGGREGATION_FUNCTION_NAMES",
+                name
+            );
+        }
     }
 }
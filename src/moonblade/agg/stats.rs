@@ -1,10 +1,14 @@
+use std::collections::BTreeMap;
+
 use csv::ByteRecord;
-use jiff::civil::DateTime;
+use jiff::{civil::DateTime, Unit, Zoned};
 
 use super::aggregators::{
-    ApproxCardinality, ApproxQuantiles, Count, Extent, Frequencies, LexicographicExtent, Numbers,
-    NumericExtent, Sum, Types, Welford,
+    ApproxCardinality, ApproxQuantiles, Count, Extent, Frequencies, LexicographicExtent, Moments,
+    Numbers, NumericExtent, PowerMeans, Sum, Types, WeightedNumbers, WeightedWelford, Welford,
+    ZonedExtent,
 };
+use crate::dates::{format_temporal_value, parse_temporal_value, unit_name};
 use crate::moonblade::types::DynamicNumber;
 
 fn map_to_field<T: ToString>(opt: Option<T>) -> Vec<u8> {
@@ -12,6 +16,72 @@ fn map_to_field<T: ToString>(opt: Option<T>) -> Vec<u8> {
         .unwrap_or(b"".to_vec())
 }
 
+#[derive(Debug)]
+struct Temporal {
+    extent: ZonedExtent,
+    granularity: Option<Unit>,
+    years: BTreeMap<i16, u64>,
+    invalid: u64,
+    found_dates: bool,
+}
+
+impl Temporal {
+    fn new() -> Self {
+        Self {
+            extent: ZonedExtent::new(),
+            granularity: None,
+            years: BTreeMap::new(),
+            invalid: 0,
+            found_dates: false,
+        }
+    }
+
+    fn add(&mut self, value: Option<(Unit, Zoned)>) {
+        match value {
+            None => self.invalid += 1,
+            Some((unit, zoned)) => {
+                // NOTE: granularity is the finest unit witnessed
+                self.granularity = Some(match self.granularity {
+                    Some(granularity) => granularity.min(unit),
+                    None => unit,
+                });
+
+                *self.years.entry(zoned.year()).or_insert(0) += 1;
+                self.extent.add(&zoned);
+            }
+        }
+    }
+
+    fn invalid_share(&self) -> Option<f64> {
+        let valid: u64 = self.years.values().sum();
+        let total = valid + self.invalid;
+
+        if total == 0 {
+            None
+        } else {
+            Some(self.invalid as f64 / total as f64)
+        }
+    }
+
+    fn merge(&mut self, other: Self) {
+        self.extent.merge(other.extent);
+
+        if let Some(unit) = other.granularity {
+            self.granularity = Some(match self.granularity {
+                Some(granularity) => granularity.min(unit),
+                None => unit,
+            });
+        }
+
+        for (year, count) in other.years {
+            *self.years.entry(year).or_insert(0) += count;
+        }
+
+        self.invalid += other.invalid;
+        self.found_dates |= other.found_dates;
+    }
+}
+
 #[derive(Debug)]
 pub struct Stats {
     nulls: bool,
@@ -26,6 +96,10 @@ pub struct Stats {
     numbers: Option<Numbers>,
     approx_cardinality: Option<ApproxCardinality>,
     approx_quantiles: Option<ApproxQuantiles>,
+    moments: Option<(Moments, PowerMeans)>,
+    weighted: Option<WeightedWelford>,
+    weighted_numbers: Option<WeightedNumbers>,
+    temporal: Option<Temporal>,
 }
 
 impl Stats {
@@ -43,6 +117,10 @@ impl Stats {
             numbers: None,
             approx_cardinality: None,
             approx_quantiles: None,
+            moments: None,
+            weighted: None,
+            weighted_numbers: None,
+            temporal: None,
         }
     }
 
@@ -69,6 +147,24 @@ impl Stats {
         if let Some(approx_quantiles) = &mut self.approx_quantiles {
             approx_quantiles.merge(other.approx_quantiles.unwrap());
         }
+
+        if let Some((moments, means)) = &mut self.moments {
+            let (other_moments, other_means) = other.moments.unwrap();
+            moments.merge(other_moments);
+            means.merge(other_means);
+        }
+
+        if let Some(weighted) = &mut self.weighted {
+            weighted.merge(other.weighted.unwrap());
+        }
+
+        if let Some(weighted_numbers) = &mut self.weighted_numbers {
+            weighted_numbers.merge(other.weighted_numbers.unwrap());
+        }
+
+        if let Some(temporal) = &mut self.temporal {
+            temporal.merge(other.temporal.unwrap());
+        }
     }
 
     pub fn include_nulls(&mut self) {
@@ -80,7 +176,11 @@ impl Stats {
     }
 
     pub fn compute_numbers(&mut self) {
-        self.numbers = Some(Numbers::new());
+        if self.weighted.is_some() {
+            self.weighted_numbers = Some(WeightedNumbers::new());
+        } else {
+            self.numbers = Some(Numbers::new());
+        }
     }
 
     pub fn compute_approx(&mut self) {
@@ -88,6 +188,24 @@ impl Stats {
         self.approx_quantiles = Some(ApproxQuantiles::new());
     }
 
+    pub fn compute_moments(&mut self) {
+        self.moments = Some((Moments::new(), PowerMeans::new()));
+    }
+
+    pub fn compute_temporal(&mut self) {
+        self.temporal = Some(Temporal::new());
+    }
+
+    // NOTE: when weighted, sum, mean, quartiles, variance & stddev will be
+    // computed using the weights given to `process_with_weight`.
+    pub fn compute_weighted(&mut self) {
+        self.weighted = Some(WeightedWelford::new());
+
+        if self.numbers.take().is_some() {
+            self.weighted_numbers = Some(WeightedNumbers::new());
+        }
+    }
+
     pub fn headers(&self) -> ByteRecord {
         let mut headers = ByteRecord::new();
 
@@ -99,7 +217,7 @@ impl Stats {
         headers.push_field(b"sum");
         headers.push_field(b"mean");
 
-        if self.numbers.is_some() {
+        if self.numbers.is_some() || self.weighted_numbers.is_some() {
             headers.push_field(b"q1");
             headers.push_field(b"median");
             headers.push_field(b"q3");
@@ -107,6 +225,15 @@ impl Stats {
 
         headers.push_field(b"variance");
         headers.push_field(b"stddev");
+
+        if self.moments.is_some() {
+            headers.push_field(b"cv");
+            headers.push_field(b"skewness");
+            headers.push_field(b"kurtosis");
+            headers.push_field(b"geometric_mean");
+            headers.push_field(b"harmonic_mean");
+        }
+
         headers.push_field(b"min");
         headers.push_field(b"max");
 
@@ -123,6 +250,15 @@ impl Stats {
             headers.push_field(b"tied_for_mode");
         }
 
+        if self.temporal.is_some() {
+            headers.push_field(b"earliest");
+            headers.push_field(b"latest");
+            headers.push_field(b"granularity");
+            headers.push_field(b"span_days");
+            headers.push_field(b"per_year");
+            headers.push_field(b"invalid_dates");
+        }
+
         headers.push_field(b"lex_first");
         headers.push_field(b"lex_last");
         headers.push_field(b"min_length");
@@ -144,8 +280,16 @@ impl Stats {
                 .unwrap_or(b""),
         );
         record.push_field(self.types.sorted_types().join("|").as_bytes());
-        record.push_field(&map_to_field(self.sum.get()));
-        record.push_field(&map_to_field(self.welford.mean()));
+        match self.weighted.as_ref() {
+            Some(weighted) => {
+                record.push_field(&map_to_field(weighted.sum()));
+                record.push_field(&map_to_field(weighted.mean()));
+            }
+            None => {
+                record.push_field(&map_to_field(self.sum.get()));
+                record.push_field(&map_to_field(self.welford.mean()));
+            }
+        }
 
         if let Some(mut numbers) = self.numbers {
             numbers.finalize(false);
@@ -164,8 +308,42 @@ impl Stats {
             }
         }
 
-        record.push_field(&map_to_field(self.welford.variance()));
-        record.push_field(&map_to_field(self.welford.stdev()));
+        if let Some(mut weighted_numbers) = self.weighted_numbers {
+            weighted_numbers.finalize(false);
+
+            match weighted_numbers.quartiles() {
+                Some(quartiles) => {
+                    for quartile in quartiles {
+                        record.push_field(quartile.to_string().as_bytes());
+                    }
+                }
+                None => {
+                    for _ in 0..3 {
+                        record.push_field(b"");
+                    }
+                }
+            }
+        }
+
+        match self.weighted.as_ref() {
+            Some(weighted) => {
+                record.push_field(&map_to_field(weighted.variance()));
+                record.push_field(&map_to_field(weighted.stdev()));
+            }
+            None => {
+                record.push_field(&map_to_field(self.welford.variance()));
+                record.push_field(&map_to_field(self.welford.stdev()));
+            }
+        }
+
+        if let Some((moments, means)) = self.moments.as_ref() {
+            record.push_field(&map_to_field(self.welford.coefficient_of_variation()));
+            record.push_field(&map_to_field(moments.skewness()));
+            record.push_field(&map_to_field(moments.kurtosis()));
+            record.push_field(&map_to_field(means.geometric_mean()));
+            record.push_field(&map_to_field(means.harmonic_mean()));
+        }
+
         record.push_field(&map_to_field(self.extent.min()));
         record.push_field(&map_to_field(self.extent.max()));
 
@@ -190,6 +368,36 @@ impl Stats {
             record.push_field(&map_to_field(modes.map(|m| m.len())));
         }
 
+        // NOTE: temporal statistics are only reported for columns
+        // where dates were found
+        match self.temporal.as_ref() {
+            Some(temporal) if temporal.found_dates => {
+                let granularity = temporal.granularity.unwrap_or(Unit::Second);
+                let format = |zoned| format_temporal_value(granularity, &zoned);
+
+                record.push_field(&map_to_field(temporal.extent.earliest().map(format)));
+                record.push_field(&map_to_field(temporal.extent.lastest().map(format)));
+                record.push_field(&map_to_field(temporal.granularity.map(unit_name)));
+                record.push_field(&map_to_field(temporal.extent.count_days()));
+                record.push_field(
+                    temporal
+                        .years
+                        .iter()
+                        .map(|(year, count)| format!("{}:{}", year, count))
+                        .collect::<Vec<_>>()
+                        .join("|")
+                        .as_bytes(),
+                );
+                record.push_field(&map_to_field(temporal.invalid_share()));
+            }
+            Some(_) => {
+                for _ in 0..6 {
+                    record.push_field(b"");
+                }
+            }
+            None => (),
+        }
+
         record.push_field(&map_to_field(self.lexicograhic_extent.first()));
         record.push_field(&map_to_field(self.lexicograhic_extent.last()));
         record.push_field(&map_to_field(self.length_extent.min()));
@@ -199,6 +407,10 @@ impl Stats {
     }
 
     pub fn process(&mut self, cell: &[u8]) {
+        self.process_with_weight(cell, 1.0);
+    }
+
+    pub fn process_with_weight(&mut self, cell: &[u8], weight: f64) {
         self.length_extent.add(cell.len());
 
         if cell.is_empty() {
@@ -208,6 +420,19 @@ impl Stats {
             if self.nulls {
                 self.welford.add(0.0);
 
+                if let Some(weighted) = self.weighted.as_mut() {
+                    weighted.add(0.0, weight);
+                }
+
+                if let Some(weighted_numbers) = self.weighted_numbers.as_mut() {
+                    weighted_numbers.add(0.0, weight);
+                }
+
+                if let Some((moments, means)) = self.moments.as_mut() {
+                    moments.add(0.0);
+                    means.add(0.0);
+                }
+
                 if let Some(numbers) = self.numbers.as_mut() {
                     numbers.add(DynamicNumber::Float(0.0));
                 }
@@ -228,7 +453,7 @@ impl Stats {
             self.extent.add(number);
 
             match number {
-                DynamicNumber::Float(_) => self.types.set_float(),
+                DynamicNumber::Float(_) | DynamicNumber::Decimal(_) => self.types.set_float(),
                 DynamicNumber::Integer(_) => self.types.set_int(),
             };
 
@@ -236,15 +461,44 @@ impl Stats {
                 numbers.add(number);
             }
 
+            if let Some(weighted) = self.weighted.as_mut() {
+                weighted.add(float, weight);
+            }
+
+            if let Some(weighted_numbers) = self.weighted_numbers.as_mut() {
+                weighted_numbers.add(float, weight);
+            }
+
             if let Some(approx_quantiles) = self.approx_quantiles.as_mut() {
                 approx_quantiles.add(float);
             }
-        } else if cell.parse::<DateTime>().is_ok() {
-            self.types.set_date();
-        } else if cell.starts_with("http://") || cell.starts_with("https://") {
-            self.types.set_url();
+
+            if let Some((moments, means)) = self.moments.as_mut() {
+                moments.add(float);
+                means.add(float);
+            }
+
+            // NOTE: numbers may represent years
+            if let Some(temporal) = self.temporal.as_mut() {
+                temporal.add(parse_temporal_value(cell));
+            }
         } else {
-            self.types.set_string();
+            if cell.parse::<DateTime>().is_ok() {
+                self.types.set_date();
+            } else if cell.starts_with("http://") || cell.starts_with("https://") {
+                self.types.set_url();
+            } else {
+                self.types.set_string();
+            }
+
+            // NOTE: partial dates & zoned datetimes are only considered when
+            // computing temporal statistics and do not affect inferred types
+            if let Some(temporal) = self.temporal.as_mut() {
+                let value = parse_temporal_value(cell);
+
+                temporal.found_dates |= value.is_some();
+                temporal.add(value);
+            }
         }
 
         if let Some(frequencies) = self.frequencies.as_mut() {
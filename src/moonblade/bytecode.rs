@@ -0,0 +1,680 @@
+// NOTE: concrete expressions are compiled into a linear sequence of
+// instructions run by a small stack machine, which is way cheaper than
+// walking the expression tree for every row. Some very common patterns,
+// such as comparing a column to a constant, or doing some arithmetic on a
+// column, are also compiled into specialized instructions that work directly
+// on the cell bytes, so that no intermediate value needs to be allocated.
+// Special functions (e.g. "map" or "try") need to control the evaluation of
+// their arguments and are therefore still evaluated by walking the tree.
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::{Add, Div, Mul, Sub};
+
+use arrayvec::ArrayVec;
+use csv::ByteRecord;
+
+use super::error::{EvaluationError, SpecifiedEvaluationError};
+use super::functions::Function;
+use super::interpreter::{
+    eval_expression, ConcreteExpr, ConcreteSpecialFunctionCall, EvaluationContext,
+};
+use super::types::{BoundArguments, DynamicNumber, DynamicValue, EvaluationResult};
+
+// NOTE: the stack lives on the call stack, to avoid allocating per row.
+// Expressions that could overflow it are simply not compiled.
+const STACK_CAPACITY: usize = 32;
+
+type Validate = fn(Ordering) -> bool;
+type Operator = fn(DynamicNumber, DynamicNumber) -> DynamicNumber;
+
+fn get_validate(name: &str) -> Option<(Validate, bool)> {
+    Some(match name {
+        "==" => (Ordering::is_eq, true),
+        "!=" => (Ordering::is_ne, true),
+        "<" => (Ordering::is_lt, true),
+        "<=" => (Ordering::is_le, true),
+        ">" => (Ordering::is_gt, true),
+        ">=" => (Ordering::is_ge, true),
+        "eq" => (Ordering::is_eq, false),
+        "ne" => (Ordering::is_ne, false),
+        "lt" => (Ordering::is_lt, false),
+        "le" => (Ordering::is_le, false),
+        "gt" => (Ordering::is_gt, false),
+        "ge" => (Ordering::is_ge, false),
+        _ => return None,
+    })
+}
+
+fn get_operator(name: &str) -> Option<Operator> {
+    Some(match name {
+        "add" => Add::add,
+        "sub" => Sub::sub,
+        "mul" => Mul::mul,
+        "div" => Div::div,
+        _ => return None,
+    })
+}
+
+// Functions only ever casting their arguments to numbers, whose column
+// arguments can therefore be parsed eagerly.
+fn is_numeric_function(name: &str) -> bool {
+    matches!(
+        name,
+        "add" | "sub" | "mul" | "div" | "idiv" | "mod" | "pow" | "neg" | "abs"
+    )
+}
+
+#[derive(Clone)]
+enum Instruction {
+    Value(DynamicValue),
+    // NOTE: columns record the name of the function they are given to, if
+    // any, so that errors are reported consistently with the tree walker
+    Column(usize, Option<String>),
+    NumberColumn(usize, String),
+    List(usize),
+    Map(Vec<String>),
+    Call(String, Function, usize),
+    Special(ConcreteSpecialFunctionCall),
+    Evaluate(Box<ConcreteExpr>),
+    Jump(usize),
+    JumpIfTruthy(usize),
+    JumpIfFalsey(usize),
+    CompareColumnToNumber {
+        name: String,
+        column: usize,
+        number: DynamicNumber,
+        validate: Validate,
+        reversed: bool,
+    },
+    CompareColumnToBytes {
+        name: String,
+        column: usize,
+        bytes: Vec<u8>,
+        check_utf8: bool,
+        validate: Validate,
+        reversed: bool,
+    },
+    ColumnArithmetic {
+        name: String,
+        column: usize,
+        number: DynamicNumber,
+        operator: Operator,
+        reversed: bool,
+    },
+}
+
+// NOTE: in older rust versions, Debug cannot be derived
+// correctly from `fn` and it will not compile without
+// this custom `Debug` implementation
+impl fmt::Debug for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Value(value) => write!(f, "value {:?}", value),
+            Self::Column(column, _) => write!(f, "column {}", column),
+            Self::NumberColumn(column, _) => write!(f, "number_column {}", column),
+            Self::List(count) => write!(f, "list {}", count),
+            Self::Map(keys) => write!(f, "map {:?}", keys),
+            Self::Call(name, _, arity) => write!(f, "call {} {}", name, arity),
+            Self::Special(call) => write!(f, "special {}", call.name),
+            Self::Evaluate(_) => write!(f, "evaluate"),
+            Self::Jump(target) => write!(f, "jump {}", target),
+            Self::JumpIfTruthy(target) => write!(f, "jump_if_truthy {}", target),
+            Self::JumpIfFalsey(target) => write!(f, "jump_if_falsey {}", target),
+            Self::CompareColumnToNumber { name, column, .. } => {
+                write!(f, "compare_column_to_number {} {}", name, column)
+            }
+            Self::CompareColumnToBytes { name, column, .. } => {
+                write!(f, "compare_column_to_bytes {} {}", name, column)
+            }
+            Self::ColumnArithmetic { name, column, .. } => {
+                write!(f, "column_arithmetic {} {}", name, column)
+            }
+        }
+    }
+}
+
+fn column_as_number_value(cell: &[u8]) -> DynamicValue {
+    match DynamicNumber::try_from(cell) {
+        Ok(number) => DynamicValue::from(number),
+        Err(_) => DynamicValue::from_bytes(cell),
+    }
+}
+
+fn column_as_number(cell: &[u8], name: &str) -> Result<DynamicNumber, SpecifiedEvaluationError> {
+    DynamicNumber::try_from(cell).map_err(|_| {
+        EvaluationError::from_cast(&DynamicValue::from_bytes(cell), "number").specify(name)
+    })
+}
+
+fn get_cell<'a>(
+    record: &'a ByteRecord,
+    column: usize,
+    name: Option<&str>,
+) -> Result<&'a [u8], SpecifiedEvaluationError> {
+    record.get(column).ok_or_else(|| {
+        let err = EvaluationError::ColumnOutOfRange(column);
+
+        match name {
+            Some(name) => err.specify(name),
+            None => err.anonymous(),
+        }
+    })
+}
+
+fn compare(ordering: Option<Ordering>, reversed: bool, validate: Validate) -> DynamicValue {
+    let ordering = if reversed {
+        ordering.map(Ordering::reverse)
+    } else {
+        ordering
+    };
+
+    DynamicValue::from(ordering.map(validate).unwrap_or(false))
+}
+
+struct Compiler {
+    instructions: Vec<Instruction>,
+}
+
+impl Compiler {
+    fn emit(&mut self, instruction: Instruction) -> usize {
+        self.instructions.push(instruction);
+        self.instructions.len() - 1
+    }
+
+    fn patch(&mut self, position: usize) {
+        let target = self.instructions.len();
+
+        match &mut self.instructions[position] {
+            Instruction::Jump(t) | Instruction::JumpIfTruthy(t) | Instruction::JumpIfFalsey(t) => {
+                *t = target;
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn compile_specialized(&mut self, name: &str, args: &[ConcreteExpr]) -> bool {
+        let (column, value, reversed) = match args {
+            [ConcreteExpr::Column(column), ConcreteExpr::Value(value)] => (*column, value, false),
+            [ConcreteExpr::Value(value), ConcreteExpr::Column(column)] => (*column, value, true),
+            _ => return false,
+        };
+
+        if let Some((validate, numeric)) = get_validate(name) {
+            let instruction = if numeric {
+                // NOTE: comparing to a datetime has dedicated semantics
+                if matches!(value, DynamicValue::DateTime(_)) {
+                    return false;
+                }
+
+                match value.try_as_number() {
+                    Err(_) => return false,
+                    Ok(number) => Instruction::CompareColumnToNumber {
+                        name: name.to_string(),
+                        column,
+                        number,
+                        validate,
+                        reversed,
+                    },
+                }
+            } else {
+                let (bytes, check_utf8) = match value {
+                    DynamicValue::String(string) => (string.as_bytes().to_vec(), true),
+                    DynamicValue::Bytes(bytes) => (bytes.to_vec(), false),
+                    _ => return false,
+                };
+
+                Instruction::CompareColumnToBytes {
+                    name: name.to_string(),
+                    column,
+                    bytes,
+                    check_utf8,
+                    validate,
+                    reversed,
+                }
+            };
+
+            self.emit(instruction);
+
+            return true;
+        }
+
+        if let Some(operator) = get_operator(name) {
+            let number = match value.try_as_number() {
+                Err(_) => return false,
+                Ok(number) => number,
+            };
+
+            self.emit(Instruction::ColumnArithmetic {
+                name: name.to_string(),
+                column,
+                number,
+                operator,
+                reversed,
+            });
+
+            return true;
+        }
+
+        false
+    }
+
+    fn compile_branches(&mut self, args: &[ConcreteExpr], jump_if_truthy: bool) {
+        self.compile(&args[0], None);
+
+        let jump_to_else = if jump_if_truthy {
+            self.emit(Instruction::JumpIfTruthy(0))
+        } else {
+            self.emit(Instruction::JumpIfFalsey(0))
+        };
+
+        self.compile(&args[1], None);
+
+        let jump_to_end = self.emit(Instruction::Jump(0));
+        self.patch(jump_to_else);
+
+        match args.get(2) {
+            Some(arg) => self.compile(arg, None),
+            None => {
+                self.emit(Instruction::Value(DynamicValue::None));
+            }
+        }
+
+        self.patch(jump_to_end);
+    }
+
+    fn compile(&mut self, expr: &ConcreteExpr, caller: Option<&str>) {
+        match expr {
+            ConcreteExpr::Value(value) => {
+                self.emit(Instruction::Value(value.clone()));
+            }
+            ConcreteExpr::Column(column) => {
+                let instruction = match caller {
+                    Some(name) if is_numeric_function(name) => {
+                        Instruction::NumberColumn(*column, name.to_string())
+                    }
+                    _ => Instruction::Column(*column, caller.map(String::from)),
+                };
+
+                self.emit(instruction);
+            }
+            ConcreteExpr::List(items) => {
+                for item in items {
+                    self.compile(item, None);
+                }
+
+                self.emit(Instruction::List(items.len()));
+            }
+            ConcreteExpr::Map(pairs) => {
+                for (_, value) in pairs {
+                    self.compile(value, None);
+                }
+
+                self.emit(Instruction::Map(
+                    pairs.iter().map(|(key, _)| key.clone()).collect(),
+                ));
+            }
+            ConcreteExpr::Call(call) => {
+                if self.compile_specialized(&call.name, &call.args) {
+                    return;
+                }
+
+                for arg in call.args.iter() {
+                    self.compile(arg, Some(&call.name));
+                }
+
+                self.emit(Instruction::Call(
+                    call.name.clone(),
+                    call.function,
+                    call.args.len(),
+                ));
+            }
+            ConcreteExpr::SpecialCall(call) => match call.name.as_str() {
+                "if" => self.compile_branches(&call.args, false),
+                "unless" => self.compile_branches(&call.args, true),
+                _ => {
+                    self.emit(Instruction::Special(call.clone()));
+                }
+            },
+            // NOTE: lambdas only ever appear as arguments of special functions
+            ConcreteExpr::Lambda(_, _) | ConcreteExpr::LambdaBinding(_) => unreachable!(),
+        }
+    }
+}
+
+// NOTE: jumps are ignored here, which means the depth of both branches of a
+// condition is summed. This overestimates the required depth, which is fine.
+fn max_stack_depth(instructions: &[Instruction]) -> usize {
+    let mut depth: usize = 0;
+    let mut max_depth: usize = 0;
+
+    for instruction in instructions {
+        match instruction {
+            Instruction::List(count) | Instruction::Call(_, _, count) => {
+                depth = depth + 1 - count;
+            }
+            Instruction::Map(keys) => {
+                depth = depth + 1 - keys.len();
+            }
+            Instruction::JumpIfTruthy(_) | Instruction::JumpIfFalsey(_) => {
+                depth -= 1;
+            }
+            Instruction::Jump(_) => (),
+            _ => {
+                depth += 1;
+            }
+        }
+
+        max_depth = max_depth.max(depth);
+    }
+
+    max_depth
+}
+
+#[derive(Debug, Clone)]
+pub struct Bytecode {
+    instructions: Vec<Instruction>,
+}
+
+impl Bytecode {
+    pub fn compile(expr: &ConcreteExpr) -> Self {
+        let mut compiler = Compiler {
+            instructions: Vec::new(),
+        };
+
+        compiler.compile(expr, None);
+
+        let mut instructions = compiler.instructions;
+
+        // NOTE: extremely deep expressions are handled by the tree walker
+        if max_stack_depth(&instructions) > STACK_CAPACITY {
+            instructions = vec![Instruction::Evaluate(Box::new(expr.clone()))];
+        }
+
+        Self { instructions }
+    }
+
+    pub fn run(
+        &self,
+        index: Option<usize>,
+        record: &ByteRecord,
+        context: &EvaluationContext,
+    ) -> EvaluationResult {
+        let mut stack: ArrayVec<DynamicValue, STACK_CAPACITY> = ArrayVec::new();
+        let mut pointer: usize = 0;
+
+        while let Some(instruction) = self.instructions.get(pointer) {
+            pointer += 1;
+
+            let value = match instruction {
+                Instruction::Value(value) => value.clone(),
+                Instruction::Column(column, name) => {
+                    DynamicValue::from_bytes(get_cell(record, *column, name.as_deref())?)
+                }
+                Instruction::NumberColumn(column, name) => {
+                    column_as_number_value(get_cell(record, *column, Some(name))?)
+                }
+                Instruction::List(count) => {
+                    let items = stack.drain(stack.len() - count..).collect::<Vec<_>>();
+
+                    DynamicValue::from(items)
+                }
+                Instruction::Map(keys) => {
+                    let values = stack.drain(stack.len() - keys.len()..);
+
+                    DynamicValue::from(keys.iter().cloned().zip(values).collect::<HashMap<_, _>>())
+                }
+                Instruction::Call(name, function, arity) => {
+                    let mut args = BoundArguments::new();
+
+                    for arg in stack.drain(stack.len() - arity..) {
+                        args.push(arg);
+                    }
+
+                    function(args).map_err(|err| err.specify(name))?
+                }
+                Instruction::Special(call) => call.run(index, record, context, None)?,
+                Instruction::Evaluate(expr) => eval_expression(expr, index, record, context)?,
+                Instruction::Jump(target) => {
+                    pointer = *target;
+                    continue;
+                }
+                Instruction::JumpIfTruthy(target) => {
+                    if stack.pop().unwrap().is_truthy() {
+                        pointer = *target;
+                    }
+                    continue;
+                }
+                Instruction::JumpIfFalsey(target) => {
+                    if stack.pop().unwrap().is_falsey() {
+                        pointer = *target;
+                    }
+                    continue;
+                }
+                Instruction::CompareColumnToNumber {
+                    name,
+                    column,
+                    number,
+                    validate,
+                    reversed,
+                } => {
+                    let cell = column_as_number(get_cell(record, *column, Some(name))?, name)?;
+
+                    compare(cell.partial_cmp(number), *reversed, *validate)
+                }
+                Instruction::CompareColumnToBytes {
+                    name,
+                    column,
+                    bytes,
+                    check_utf8,
+                    validate,
+                    reversed,
+                } => {
+                    let cell = get_cell(record, *column, Some(name))?;
+
+                    if *check_utf8 && std::str::from_utf8(cell).is_err() {
+                        return Err(EvaluationError::UnicodeDecodeError.specify(name));
+                    }
+
+                    compare(Some(cell.cmp(bytes)), *reversed, *validate)
+                }
+                Instruction::ColumnArithmetic {
+                    name,
+                    column,
+                    number,
+                    operator,
+                    reversed,
+                } => {
+                    let cell = column_as_number(get_cell(record, *column, Some(name))?, name)?;
+
+                    DynamicValue::from(if *reversed {
+                        operator(*number, cell)
+                    } else {
+                        operator(cell, *number)
+                    })
+                }
+            };
+
+            stack.push(value);
+        }
+
+        Ok(stack.pop().unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::time::Instant;
+
+    use crate::moonblade::interpreter::concretize_expression;
+    use crate::moonblade::parser::parse_expression;
+
+    fn headers() -> ByteRecord {
+        ByteRecord::from(vec!["name", "count", "price"])
+    }
+
+    fn records() -> Vec<ByteRecord> {
+        vec![
+            ByteRecord::from(vec!["john", "34", "12.5"]),
+            ByteRecord::from(vec!["mary", "-3", "0"]),
+            ByteRecord::from(vec!["", "not a number", "1e3"]),
+            ByteRecord::from(vec![&b"\xff\xfe"[..], b"1", b"2"]),
+            ByteRecord::from(vec!["short"]),
+        ]
+    }
+
+    fn compile(code: &str) -> (ConcreteExpr, Bytecode) {
+        let expr = concretize_expression(parse_expression(code).unwrap(), &headers()).unwrap();
+        let bytecode = Bytecode::compile(&expr);
+
+        (expr, bytecode)
+    }
+
+    fn disassemble(code: &str) -> Vec<String> {
+        compile(code)
+            .1
+            .instructions
+            .iter()
+            .map(|instruction| format!("{:?}", instruction))
+            .collect()
+    }
+
+    #[test]
+    fn test_compilation() {
+        assert_eq!(
+            disassemble("count > 3"),
+            vec!["compare_column_to_number > 1"]
+        );
+        assert_eq!(
+            disassemble("'john' eq name"),
+            vec!["compare_column_to_bytes eq 0"]
+        );
+        assert_eq!(disassemble("2 * price"), vec!["column_arithmetic mul 2"]);
+        assert_eq!(disassemble("upper(name)"), vec!["column 0", "call upper 1"]);
+        assert_eq!(
+            disassemble("count + price"),
+            vec!["number_column 1", "number_column 2", "call add 2"]
+        );
+        assert_eq!(
+            disassemble("if(count > 3, name, 'none')"),
+            vec![
+                "compare_column_to_number > 1",
+                "jump_if_falsey 4",
+                "column 0",
+                "jump 5",
+                "value String(\"none\")"
+            ]
+        );
+        assert_eq!(
+            disassemble("map(split(name, ','), x => upper(x))"),
+            vec!["special map"]
+        );
+        assert_eq!(disassemble("name").len(), 1);
+    }
+
+    #[test]
+    fn test_equivalence() {
+        let context = EvaluationContext::new(&headers());
+
+        let codes = [
+            "name",
+            "count",
+            "count > 3",
+            "3 <= count",
+            "count == 34",
+            "price != '12.5'",
+            "name eq 'john'",
+            "'mary' lt name",
+            "name eq b'john'",
+            "count + 1",
+            "1 - count",
+            "price / 2",
+            "count * price",
+            "-count",
+            "abs(count) + 1",
+            "count // 5",
+            "upper(name)",
+            "if(count > 3, name, 'none')",
+            "unless(name, 'empty')",
+            "if(name eq 'john', count + 1)",
+            "[name, count + 1, 'test']",
+            "{name: name, total: count * 2}['total']",
+            "map(split(price, '.'), x => x + 1)",
+            "try(count + 1) ?? 0",
+            "len(name) > 2 && count < 40",
+            "index()",
+            "fmt('{}: {}', name, count)",
+        ];
+
+        for code in codes {
+            let (expr, bytecode) = compile(code);
+
+            for (index, record) in records().iter().enumerate() {
+                assert_eq!(
+                    bytecode.run(Some(index), record, &context),
+                    eval_expression(&expr, Some(index), record, &context),
+                    "{} on row {}",
+                    code,
+                    index
+                );
+            }
+        }
+    }
+
+    // NOTE: this benchmark is not run by default and compares the tree walker
+    // with the stack machine, on the expressions also used by the command
+    // benchmarks found in benches/moonblade.rs. Use it with:
+    // cargo test --release bench_bytecode -- --ignored --nocapture
+    #[test]
+    #[ignore]
+    fn bench_bytecode() {
+        let context = EvaluationContext::new(&headers());
+
+        let records = (0..1_000_000)
+            .map(|i| {
+                ByteRecord::from(vec![
+                    format!("name{}", i % 1000),
+                    (i % 100).to_string(),
+                    format!("{}.5", i % 37),
+                ])
+            })
+            .collect::<Vec<_>>();
+
+        let codes = [
+            "count > 50",
+            "name eq 'name45'",
+            "count > 10 && price < 20",
+            "price * 2",
+            "count + price",
+            "count * 2",
+            "if(count > 50, upper(name), name)",
+            "[name, count + 1]",
+        ];
+
+        for code in codes {
+            let (expr, bytecode) = compile(code);
+
+            let timer = Instant::now();
+            for (index, record) in records.iter().enumerate() {
+                eval_expression(&expr, Some(index), record, &context).unwrap();
+            }
+            let tree = timer.elapsed();
+
+            let timer = Instant::now();
+            for (index, record) in records.iter().enumerate() {
+                bytecode.run(Some(index), record, &context).unwrap();
+            }
+            let compiled = timer.elapsed();
+
+            println!(
+                "{:<40} tree: {:>8.2?}  bytecode: {:>8.2?}  speedup: {:.2}x",
+                code,
+                tree,
+                compiled,
+                tree.as_secs_f64() / compiled.as_secs_f64()
+            );
+        }
+    }
+}
@@ -0,0 +1,330 @@
+// NOTE: full Unicode case folding, i.e. lowercasing with the additional
+// mappings used for caseless matching, e.g. "ß" -> "ss" or "ς" -> "σ".
+// The table only lists characters whose folding differs from their
+// lowercase mapping.
+// Ref: https://www.unicode.org/Public/UCD/latest/ucd/CaseFolding.txt
+static CASE_FOLDING: [(char, &str); 297] = [
+    ('\u{B5}', "\u{3BC}"),
+    ('\u{DF}', "\u{73}\u{73}"),
+    ('\u{149}', "\u{2BC}\u{6E}"),
+    ('\u{17F}', "\u{73}"),
+    ('\u{1F0}', "\u{6A}\u{30C}"),
+    ('\u{345}', "\u{3B9}"),
+    ('\u{390}', "\u{3B9}\u{308}\u{301}"),
+    ('\u{3B0}', "\u{3C5}\u{308}\u{301}"),
+    ('\u{3C2}', "\u{3C3}"),
+    ('\u{3D0}', "\u{3B2}"),
+    ('\u{3D1}', "\u{3B8}"),
+    ('\u{3D5}', "\u{3C6}"),
+    ('\u{3D6}', "\u{3C0}"),
+    ('\u{3F0}', "\u{3BA}"),
+    ('\u{3F1}', "\u{3C1}"),
+    ('\u{3F5}', "\u{3B5}"),
+    ('\u{587}', "\u{565}\u{582}"),
+    ('\u{13A0}', "\u{13A0}"),
+    ('\u{13A1}', "\u{13A1}"),
+    ('\u{13A2}', "\u{13A2}"),
+    ('\u{13A3}', "\u{13A3}"),
+    ('\u{13A4}', "\u{13A4}"),
+    ('\u{13A5}', "\u{13A5}"),
+    ('\u{13A6}', "\u{13A6}"),
+    ('\u{13A7}', "\u{13A7}"),
+    ('\u{13A8}', "\u{13A8}"),
+    ('\u{13A9}', "\u{13A9}"),
+    ('\u{13AA}', "\u{13AA}"),
+    ('\u{13AB}', "\u{13AB}"),
+    ('\u{13AC}', "\u{13AC}"),
+    ('\u{13AD}', "\u{13AD}"),
+    ('\u{13AE}', "\u{13AE}"),
+    ('\u{13AF}', "\u{13AF}"),
+    ('\u{13B0}', "\u{13B0}"),
+    ('\u{13B1}', "\u{13B1}"),
+    ('\u{13B2}', "\u{13B2}"),
+    ('\u{13B3}', "\u{13B3}"),
+    ('\u{13B4}', "\u{13B4}"),
+    ('\u{13B5}', "\u{13B5}"),
+    ('\u{13B6}', "\u{13B6}"),
+    ('\u{13B7}', "\u{13B7}"),
+    ('\u{13B8}', "\u{13B8}"),
+    ('\u{13B9}', "\u{13B9}"),
+    ('\u{13BA}', "\u{13BA}"),
+    ('\u{13BB}', "\u{13BB}"),
+    ('\u{13BC}', "\u{13BC}"),
+    ('\u{13BD}', "\u{13BD}"),
+    ('\u{13BE}', "\u{13BE}"),
+    ('\u{13BF}', "\u{13BF}"),
+    ('\u{13C0}', "\u{13C0}"),
+    ('\u{13C1}', "\u{13C1}"),
+    ('\u{13C2}', "\u{13C2}"),
+    ('\u{13C3}', "\u{13C3}"),
+    ('\u{13C4}', "\u{13C4}"),
+    ('\u{13C5}', "\u{13C5}"),
+    ('\u{13C6}', "\u{13C6}"),
+    ('\u{13C7}', "\u{13C7}"),
+    ('\u{13C8}', "\u{13C8}"),
+    ('\u{13C9}', "\u{13C9}"),
+    ('\u{13CA}', "\u{13CA}"),
+    ('\u{13CB}', "\u{13CB}"),
+    ('\u{13CC}', "\u{13CC}"),
+    ('\u{13CD}', "\u{13CD}"),
+    ('\u{13CE}', "\u{13CE}"),
+    ('\u{13CF}', "\u{13CF}"),
+    ('\u{13D0}', "\u{13D0}"),
+    ('\u{13D1}', "\u{13D1}"),
+    ('\u{13D2}', "\u{13D2}"),
+    ('\u{13D3}', "\u{13D3}"),
+    ('\u{13D4}', "\u{13D4}"),
+    ('\u{13D5}', "\u{13D5}"),
+    ('\u{13D6}', "\u{13D6}"),
+    ('\u{13D7}', "\u{13D7}"),
+    ('\u{13D8}', "\u{13D8}"),
+    ('\u{13D9}', "\u{13D9}"),
+    ('\u{13DA}', "\u{13DA}"),
+    ('\u{13DB}', "\u{13DB}"),
+    ('\u{13DC}', "\u{13DC}"),
+    ('\u{13DD}', "\u{13DD}"),
+    ('\u{13DE}', "\u{13DE}"),
+    ('\u{13DF}', "\u{13DF}"),
+    ('\u{13E0}', "\u{13E0}"),
+    ('\u{13E1}', "\u{13E1}"),
+    ('\u{13E2}', "\u{13E2}"),
+    ('\u{13E3}', "\u{13E3}"),
+    ('\u{13E4}', "\u{13E4}"),
+    ('\u{13E5}', "\u{13E5}"),
+    ('\u{13E6}', "\u{13E6}"),
+    ('\u{13E7}', "\u{13E7}"),
+    ('\u{13E8}', "\u{13E8}"),
+    ('\u{13E9}', "\u{13E9}"),
+    ('\u{13EA}', "\u{13EA}"),
+    ('\u{13EB}', "\u{13EB}"),
+    ('\u{13EC}', "\u{13EC}"),
+    ('\u{13ED}', "\u{13ED}"),
+    ('\u{13EE}', "\u{13EE}"),
+    ('\u{13EF}', "\u{13EF}"),
+    ('\u{13F0}', "\u{13F0}"),
+    ('\u{13F1}', "\u{13F1}"),
+    ('\u{13F2}', "\u{13F2}"),
+    ('\u{13F3}', "\u{13F3}"),
+    ('\u{13F4}', "\u{13F4}"),
+    ('\u{13F5}', "\u{13F5}"),
+    ('\u{13F8}', "\u{13F0}"),
+    ('\u{13F9}', "\u{13F1}"),
+    ('\u{13FA}', "\u{13F2}"),
+    ('\u{13FB}', "\u{13F3}"),
+    ('\u{13FC}', "\u{13F4}"),
+    ('\u{13FD}', "\u{13F5}"),
+    ('\u{1C80}', "\u{432}"),
+    ('\u{1C81}', "\u{434}"),
+    ('\u{1C82}', "\u{43E}"),
+    ('\u{1C83}', "\u{441}"),
+    ('\u{1C84}', "\u{442}"),
+    ('\u{1C85}', "\u{442}"),
+    ('\u{1C86}', "\u{44A}"),
+    ('\u{1C87}', "\u{463}"),
+    ('\u{1C88}', "\u{A64B}"),
+    ('\u{1E96}', "\u{68}\u{331}"),
+    ('\u{1E97}', "\u{74}\u{308}"),
+    ('\u{1E98}', "\u{77}\u{30A}"),
+    ('\u{1E99}', "\u{79}\u{30A}"),
+    ('\u{1E9A}', "\u{61}\u{2BE}"),
+    ('\u{1E9B}', "\u{1E61}"),
+    ('\u{1E9E}', "\u{73}\u{73}"),
+    ('\u{1F50}', "\u{3C5}\u{313}"),
+    ('\u{1F52}', "\u{3C5}\u{313}\u{300}"),
+    ('\u{1F54}', "\u{3C5}\u{313}\u{301}"),
+    ('\u{1F56}', "\u{3C5}\u{313}\u{342}"),
+    ('\u{1F80}', "\u{1F00}\u{3B9}"),
+    ('\u{1F81}', "\u{1F01}\u{3B9}"),
+    ('\u{1F82}', "\u{1F02}\u{3B9}"),
+    ('\u{1F83}', "\u{1F03}\u{3B9}"),
+    ('\u{1F84}', "\u{1F04}\u{3B9}"),
+    ('\u{1F85}', "\u{1F05}\u{3B9}"),
+    ('\u{1F86}', "\u{1F06}\u{3B9}"),
+    ('\u{1F87}', "\u{1F07}\u{3B9}"),
+    ('\u{1F88}', "\u{1F00}\u{3B9}"),
+    ('\u{1F89}', "\u{1F01}\u{3B9}"),
+    ('\u{1F8A}', "\u{1F02}\u{3B9}"),
+    ('\u{1F8B}', "\u{1F03}\u{3B9}"),
+    ('\u{1F8C}', "\u{1F04}\u{3B9}"),
+    ('\u{1F8D}', "\u{1F05}\u{3B9}"),
+    ('\u{1F8E}', "\u{1F06}\u{3B9}"),
+    ('\u{1F8F}', "\u{1F07}\u{3B9}"),
+    ('\u{1F90}', "\u{1F20}\u{3B9}"),
+    ('\u{1F91}', "\u{1F21}\u{3B9}"),
+    ('\u{1F92}', "\u{1F22}\u{3B9}"),
+    ('\u{1F93}', "\u{1F23}\u{3B9}"),
+    ('\u{1F94}', "\u{1F24}\u{3B9}"),
+    ('\u{1F95}', "\u{1F25}\u{3B9}"),
+    ('\u{1F96}', "\u{1F26}\u{3B9}"),
+    ('\u{1F97}', "\u{1F27}\u{3B9}"),
+    ('\u{1F98}', "\u{1F20}\u{3B9}"),
+    ('\u{1F99}', "\u{1F21}\u{3B9}"),
+    ('\u{1F9A}', "\u{1F22}\u{3B9}"),
+    ('\u{1F9B}', "\u{1F23}\u{3B9}"),
+    ('\u{1F9C}', "\u{1F24}\u{3B9}"),
+    ('\u{1F9D}', "\u{1F25}\u{3B9}"),
+    ('\u{1F9E}', "\u{1F26}\u{3B9}"),
+    ('\u{1F9F}', "\u{1F27}\u{3B9}"),
+    ('\u{1FA0}', "\u{1F60}\u{3B9}"),
+    ('\u{1FA1}', "\u{1F61}\u{3B9}"),
+    ('\u{1FA2}', "\u{1F62}\u{3B9}"),
+    ('\u{1FA3}', "\u{1F63}\u{3B9}"),
+    ('\u{1FA4}', "\u{1F64}\u{3B9}"),
+    ('\u{1FA5}', "\u{1F65}\u{3B9}"),
+    ('\u{1FA6}', "\u{1F66}\u{3B9}"),
+    ('\u{1FA7}', "\u{1F67}\u{3B9}"),
+    ('\u{1FA8}', "\u{1F60}\u{3B9}"),
+    ('\u{1FA9}', "\u{1F61}\u{3B9}"),
+    ('\u{1FAA}', "\u{1F62}\u{3B9}"),
+    ('\u{1FAB}', "\u{1F63}\u{3B9}"),
+    ('\u{1FAC}', "\u{1F64}\u{3B9}"),
+    ('\u{1FAD}', "\u{1F65}\u{3B9}"),
+    ('\u{1FAE}', "\u{1F66}\u{3B9}"),
+    ('\u{1FAF}', "\u{1F67}\u{3B9}"),
+    ('\u{1FB2}', "\u{1F70}\u{3B9}"),
+    ('\u{1FB3}', "\u{3B1}\u{3B9}"),
+    ('\u{1FB4}', "\u{3AC}\u{3B9}"),
+    ('\u{1FB6}', "\u{3B1}\u{342}"),
+    ('\u{1FB7}', "\u{3B1}\u{342}\u{3B9}"),
+    ('\u{1FBC}', "\u{3B1}\u{3B9}"),
+    ('\u{1FBE}', "\u{3B9}"),
+    ('\u{1FC2}', "\u{1F74}\u{3B9}"),
+    ('\u{1FC3}', "\u{3B7}\u{3B9}"),
+    ('\u{1FC4}', "\u{3AE}\u{3B9}"),
+    ('\u{1FC6}', "\u{3B7}\u{342}"),
+    ('\u{1FC7}', "\u{3B7}\u{342}\u{3B9}"),
+    ('\u{1FCC}', "\u{3B7}\u{3B9}"),
+    ('\u{1FD2}', "\u{3B9}\u{308}\u{300}"),
+    ('\u{1FD3}', "\u{3B9}\u{308}\u{301}"),
+    ('\u{1FD6}', "\u{3B9}\u{342}"),
+    ('\u{1FD7}', "\u{3B9}\u{308}\u{342}"),
+    ('\u{1FE2}', "\u{3C5}\u{308}\u{300}"),
+    ('\u{1FE3}', "\u{3C5}\u{308}\u{301}"),
+    ('\u{1FE4}', "\u{3C1}\u{313}"),
+    ('\u{1FE6}', "\u{3C5}\u{342}"),
+    ('\u{1FE7}', "\u{3C5}\u{308}\u{342}"),
+    ('\u{1FF2}', "\u{1F7C}\u{3B9}"),
+    ('\u{1FF3}', "\u{3C9}\u{3B9}"),
+    ('\u{1FF4}', "\u{3CE}\u{3B9}"),
+    ('\u{1FF6}', "\u{3C9}\u{342}"),
+    ('\u{1FF7}', "\u{3C9}\u{342}\u{3B9}"),
+    ('\u{1FFC}', "\u{3C9}\u{3B9}"),
+    ('\u{AB70}', "\u{13A0}"),
+    ('\u{AB71}', "\u{13A1}"),
+    ('\u{AB72}', "\u{13A2}"),
+    ('\u{AB73}', "\u{13A3}"),
+    ('\u{AB74}', "\u{13A4}"),
+    ('\u{AB75}', "\u{13A5}"),
+    ('\u{AB76}', "\u{13A6}"),
+    ('\u{AB77}', "\u{13A7}"),
+    ('\u{AB78}', "\u{13A8}"),
+    ('\u{AB79}', "\u{13A9}"),
+    ('\u{AB7A}', "\u{13AA}"),
+    ('\u{AB7B}', "\u{13AB}"),
+    ('\u{AB7C}', "\u{13AC}"),
+    ('\u{AB7D}', "\u{13AD}"),
+    ('\u{AB7E}', "\u{13AE}"),
+    ('\u{AB7F}', "\u{13AF}"),
+    ('\u{AB80}', "\u{13B0}"),
+    ('\u{AB81}', "\u{13B1}"),
+    ('\u{AB82}', "\u{13B2}"),
+    ('\u{AB83}', "\u{13B3}"),
+    ('\u{AB84}', "\u{13B4}"),
+    ('\u{AB85}', "\u{13B5}"),
+    ('\u{AB86}', "\u{13B6}"),
+    ('\u{AB87}', "\u{13B7}"),
+    ('\u{AB88}', "\u{13B8}"),
+    ('\u{AB89}', "\u{13B9}"),
+    ('\u{AB8A}', "\u{13BA}"),
+    ('\u{AB8B}', "\u{13BB}"),
+    ('\u{AB8C}', "\u{13BC}"),
+    ('\u{AB8D}', "\u{13BD}"),
+    ('\u{AB8E}', "\u{13BE}"),
+    ('\u{AB8F}', "\u{13BF}"),
+    ('\u{AB90}', "\u{13C0}"),
+    ('\u{AB91}', "\u{13C1}"),
+    ('\u{AB92}', "\u{13C2}"),
+    ('\u{AB93}', "\u{13C3}"),
+    ('\u{AB94}', "\u{13C4}"),
+    ('\u{AB95}', "\u{13C5}"),
+    ('\u{AB96}', "\u{13C6}"),
+    ('\u{AB97}', "\u{13C7}"),
+    ('\u{AB98}', "\u{13C8}"),
+    ('\u{AB99}', "\u{13C9}"),
+    ('\u{AB9A}', "\u{13CA}"),
+    ('\u{AB9B}', "\u{13CB}"),
+    ('\u{AB9C}', "\u{13CC}"),
+    ('\u{AB9D}', "\u{13CD}"),
+    ('\u{AB9E}', "\u{13CE}"),
+    ('\u{AB9F}', "\u{13CF}"),
+    ('\u{ABA0}', "\u{13D0}"),
+    ('\u{ABA1}', "\u{13D1}"),
+    ('\u{ABA2}', "\u{13D2}"),
+    ('\u{ABA3}', "\u{13D3}"),
+    ('\u{ABA4}', "\u{13D4}"),
+    ('\u{ABA5}', "\u{13D5}"),
+    ('\u{ABA6}', "\u{13D6}"),
+    ('\u{ABA7}', "\u{13D7}"),
+    ('\u{ABA8}', "\u{13D8}"),
+    ('\u{ABA9}', "\u{13D9}"),
+    ('\u{ABAA}', "\u{13DA}"),
+    ('\u{ABAB}', "\u{13DB}"),
+    ('\u{ABAC}', "\u{13DC}"),
+    ('\u{ABAD}', "\u{13DD}"),
+    ('\u{ABAE}', "\u{13DE}"),
+    ('\u{ABAF}', "\u{13DF}"),
+    ('\u{ABB0}', "\u{13E0}"),
+    ('\u{ABB1}', "\u{13E1}"),
+    ('\u{ABB2}', "\u{13E2}"),
+    ('\u{ABB3}', "\u{13E3}"),
+    ('\u{ABB4}', "\u{13E4}"),
+    ('\u{ABB5}', "\u{13E5}"),
+    ('\u{ABB6}', "\u{13E6}"),
+    ('\u{ABB7}', "\u{13E7}"),
+    ('\u{ABB8}', "\u{13E8}"),
+    ('\u{ABB9}', "\u{13E9}"),
+    ('\u{ABBA}', "\u{13EA}"),
+    ('\u{ABBB}', "\u{13EB}"),
+    ('\u{ABBC}', "\u{13EC}"),
+    ('\u{ABBD}', "\u{13ED}"),
+    ('\u{ABBE}', "\u{13EE}"),
+    ('\u{ABBF}', "\u{13EF}"),
+    ('\u{FB00}', "\u{66}\u{66}"),
+    ('\u{FB01}', "\u{66}\u{69}"),
+    ('\u{FB02}', "\u{66}\u{6C}"),
+    ('\u{FB03}', "\u{66}\u{66}\u{69}"),
+    ('\u{FB04}', "\u{66}\u{66}\u{6C}"),
+    ('\u{FB05}', "\u{73}\u{74}"),
+    ('\u{FB06}', "\u{73}\u{74}"),
+    ('\u{FB13}', "\u{574}\u{576}"),
+    ('\u{FB14}', "\u{574}\u{565}"),
+    ('\u{FB15}', "\u{574}\u{56B}"),
+    ('\u{FB16}', "\u{57E}\u{576}"),
+    ('\u{FB17}', "\u{574}\u{56D}"),
+];
+
+pub fn casefold(string: &str) -> String {
+    let mut folded = String::with_capacity(string.len());
+
+    for c in string.chars() {
+        match CASE_FOLDING.binary_search_by_key(&c, |(k, _)| *k) {
+            Ok(i) => folded.push_str(CASE_FOLDING[i].1),
+            Err(_) => folded.extend(c.to_lowercase()),
+        }
+    }
+
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_casefold() {
+        assert_eq!(casefold("Straße"), "strasse");
+        assert_eq!(casefold("ΣΊΣΥΦΟΣ"), casefold("σίσυφος"));
+        assert_eq!(casefold("ﬁne"), "fine");
+        assert_eq!(casefold("HELLO"), "hello");
+    }
+}
@@ -0,0 +1,435 @@
+// NOTE: a minimal fixed-point decimal type, used to perform exact arithmetic
+// on monetary amounts and the like, where binary floats would yield artefacts
+// such as 0.1 + 0.2 = 0.30000000000000004. Values are represented by an i64
+// mantissa scaled by a power of ten, e.g. 12.50 is (1250, 2).
+// NOTE: the struct is packed so that DynamicNumber and DynamicValue remain
+// 16 bytes large.
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+// NOTE: scale used when a division cannot be represented exactly
+pub const MAX_SCALE: u32 = 18;
+
+#[derive(Debug, Clone, Copy)]
+#[repr(C, packed(4))]
+pub struct Decimal {
+    mantissa: i64,
+    scale: u32,
+}
+
+fn pow10(exponent: u32) -> Option<i64> {
+    10i64.checked_pow(exponent)
+}
+
+// Integer division rounding half away from zero
+fn div_round(numerator: i64, denominator: i64) -> i64 {
+    let quotient = numerator / denominator;
+    let remainder = numerator % denominator;
+
+    if remainder.unsigned_abs() >= denominator.unsigned_abs() - remainder.unsigned_abs() {
+        if (numerator < 0) != (denominator < 0) {
+            quotient - 1
+        } else {
+            quotient + 1
+        }
+    } else {
+        quotient
+    }
+}
+
+impl Decimal {
+    pub fn new(mantissa: i64, scale: u32) -> Self {
+        Self { mantissa, scale }
+    }
+
+    pub fn from_f64(value: f64) -> Option<Self> {
+        if !value.is_finite() {
+            return None;
+        }
+
+        // NOTE: the Display implementation of f64 yields the shortest
+        // representation that roundtrips, which is what users expect
+        value.to_string().parse().ok()
+    }
+
+    pub fn scale(&self) -> u32 {
+        self.scale
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.mantissa == 0
+    }
+
+    pub fn to_f64(self) -> f64 {
+        self.to_string().parse().unwrap()
+    }
+
+    pub fn checked_abs(self) -> Option<Self> {
+        let mantissa = self.mantissa;
+
+        mantissa
+            .checked_abs()
+            .map(|mantissa| Self::new(mantissa, self.scale))
+    }
+
+    pub fn checked_neg(self) -> Option<Self> {
+        let mantissa = self.mantissa;
+
+        mantissa
+            .checked_neg()
+            .map(|mantissa| Self::new(mantissa, self.scale))
+    }
+
+    // Return the integral part of the decimal, using given rounding for the
+    // fractional part: -1 for floor, 0 for trunc, 1 for ceil and None for
+    // rounding half away from zero.
+    fn integral(self, direction: Option<i64>) -> i64 {
+        let mantissa = self.mantissa;
+        let divisor = match pow10(self.scale) {
+            Some(divisor) => divisor,
+            None => return 0,
+        };
+
+        let truncated = mantissa / divisor;
+        let has_fraction = mantissa % divisor != 0;
+
+        match direction {
+            None => div_round(mantissa, divisor),
+            Some(-1) if has_fraction && mantissa < 0 => truncated - 1,
+            Some(1) if has_fraction && mantissa > 0 => truncated + 1,
+            _ => truncated,
+        }
+    }
+
+    pub fn trunc(self) -> i64 {
+        self.integral(Some(0))
+    }
+
+    pub fn floor(self) -> i64 {
+        self.integral(Some(-1))
+    }
+
+    pub fn ceil(self) -> i64 {
+        self.integral(Some(1))
+    }
+
+    pub fn round(self) -> i64 {
+        self.integral(None)
+    }
+
+    // Round to given number of decimal places, half away from zero
+    pub fn round_dp(self, places: u32) -> Self {
+        let (mantissa, scale) = (self.mantissa, self.scale);
+
+        if places >= scale {
+            return self;
+        }
+
+        match pow10(scale - places) {
+            Some(divisor) => Self::new(div_round(mantissa, divisor), places),
+            None => Self::new(0, places),
+        }
+    }
+
+    pub fn rescale(self, scale: u32) -> Option<Self> {
+        let (mantissa, current_scale) = (self.mantissa, self.scale);
+
+        if scale <= current_scale {
+            return Some(self.round_dp(scale));
+        }
+
+        mantissa
+            .checked_mul(pow10(scale - current_scale)?)
+            .map(|mantissa| Self::new(mantissa, scale))
+    }
+
+    // Drop trailing zeros of the fractional part
+    pub fn normalize(self) -> Self {
+        let (mut mantissa, mut scale) = (self.mantissa, self.scale);
+
+        while scale > 0 && mantissa % 10 == 0 {
+            mantissa /= 10;
+            scale -= 1;
+        }
+
+        Self::new(mantissa, scale)
+    }
+
+    fn align(self, other: Self) -> Option<(i64, i64, u32)> {
+        let scale = self.scale.max(other.scale);
+
+        Some((
+            self.rescale(scale)?.mantissa,
+            other.rescale(scale)?.mantissa,
+            scale,
+        ))
+    }
+
+    pub fn checked_add(self, other: Self) -> Option<Self> {
+        let (a, b, scale) = self.align(other)?;
+
+        a.checked_add(b).map(|mantissa| Self::new(mantissa, scale))
+    }
+
+    pub fn checked_sub(self, other: Self) -> Option<Self> {
+        let (a, b, scale) = self.align(other)?;
+
+        a.checked_sub(b).map(|mantissa| Self::new(mantissa, scale))
+    }
+
+    pub fn checked_mul(self, other: Self) -> Option<Self> {
+        let product = Self::new(
+            self.mantissa.checked_mul(other.mantissa)?,
+            self.scale + other.scale,
+        );
+
+        Some(product.round_dp(MAX_SCALE))
+    }
+
+    pub fn checked_rem(self, other: Self) -> Option<Self> {
+        let (a, b, scale) = self.align(other)?;
+
+        a.checked_rem(b).map(|mantissa| Self::new(mantissa, scale))
+    }
+
+    // NOTE: the quotient is computed with the highest scale the mantissa
+    // can accommodate (up to MAX_SCALE) and then normalized.
+    pub fn checked_div(self, other: Self) -> Option<Self> {
+        let (a, b) = (self.mantissa, other.mantissa);
+
+        if b == 0 {
+            return None;
+        }
+
+        for target in (0..=MAX_SCALE).rev() {
+            let shift = match (target + other.scale).checked_sub(self.scale) {
+                Some(shift) => shift,
+                None => break,
+            };
+
+            if let Some(numerator) = pow10(shift).and_then(|p| a.checked_mul(p)) {
+                return Some(Self::new(div_round(numerator, b), target).normalize());
+            }
+        }
+
+        None
+    }
+
+    pub fn checked_pow(self, exponent: u32) -> Option<Self> {
+        let mut result = Self::from(1);
+
+        for _ in 0..exponent {
+            result = result.checked_mul(self)?;
+        }
+
+        Some(result)
+    }
+}
+
+impl From<i64> for Decimal {
+    fn from(value: i64) -> Self {
+        Self::new(value, 0)
+    }
+}
+
+impl PartialEq for Decimal {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Decimal {}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Decimal {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match self.align(*other) {
+            Some((a, b, _)) => a.cmp(&b),
+            None => self.to_f64().total_cmp(&other.to_f64()),
+        }
+    }
+}
+
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (mantissa, scale) = (self.mantissa, self.scale as usize);
+
+        let digits = mantissa.unsigned_abs().to_string();
+        let sign = if mantissa < 0 { "-" } else { "" };
+
+        if scale == 0 {
+            return write!(f, "{}{}", sign, digits);
+        }
+
+        let digits = format!("{:0>width$}", digits, width = scale + 1);
+        let (integer_part, fractional_part) = digits.split_at(digits.len() - scale);
+
+        write!(f, "{}{}.{}", sign, integer_part, fractional_part)
+    }
+}
+
+// NOTE: decimals are serialized as their (mantissa, scale) pair, so that
+// they can be restored exactly, e.g. when spilling aggregation states to disk.
+impl Serialize for Decimal {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let (mantissa, scale) = (self.mantissa, self.scale);
+
+        (mantissa, scale).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Decimal {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let (mantissa, scale) = <(i64, u32)>::deserialize(deserializer)?;
+
+        Ok(Self::new(mantissa, scale))
+    }
+}
+
+impl FromStr for Decimal {
+    type Err = ();
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        let string = string.trim();
+
+        let (number, exponent) = match string.find(['e', 'E']) {
+            Some(i) => (
+                &string[..i],
+                string[i + 1..].parse::<i32>().map_err(|_| ())?,
+            ),
+            None => (string, 0),
+        };
+
+        let (negative, unsigned) = match number.as_bytes().first() {
+            Some(b'-') => (true, &number[1..]),
+            Some(b'+') => (false, &number[1..]),
+            _ => (false, number),
+        };
+
+        let (integer_part, fractional_part) = match unsigned.split_once('.') {
+            Some((i, f)) => (i, f),
+            None => (unsigned, ""),
+        };
+
+        if (integer_part.is_empty() && fractional_part.is_empty())
+            || !integer_part.bytes().all(|b| b.is_ascii_digit())
+            || !fractional_part.bytes().all(|b| b.is_ascii_digit())
+        {
+            return Err(());
+        }
+
+        let mut mantissa: i64 = 0;
+
+        for byte in integer_part.bytes().chain(fractional_part.bytes()) {
+            mantissa = mantissa
+                .checked_mul(10)
+                .and_then(|m| m.checked_add((byte - b'0') as i64))
+                .ok_or(())?;
+        }
+
+        if negative {
+            mantissa = -mantissa;
+        }
+
+        let scale = i32::try_from(fractional_part.len())
+            .ok()
+            .and_then(|len| len.checked_sub(exponent))
+            .ok_or(())?;
+
+        if scale < 0 {
+            return mantissa
+                .checked_mul(pow10(scale.unsigned_abs()).ok_or(())?)
+                .map(Self::from)
+                .ok_or(());
+        }
+
+        Ok(Self::new(mantissa, scale as u32).round_dp(MAX_SCALE))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn d(string: &str) -> Decimal {
+        string.parse().unwrap()
+    }
+
+    #[test]
+    fn test_parse_and_display() {
+        assert_eq!(d("12.50").to_string(), "12.50");
+        assert_eq!(d("-0.05").to_string(), "-0.05");
+        assert_eq!(d(".5").to_string(), "0.5");
+        assert_eq!(d("+3").to_string(), "3");
+        assert_eq!(d("1.5e3").to_string(), "1500");
+        assert_eq!(d("15e-3").to_string(), "0.015");
+        assert_eq!(Decimal::from_f64(0.1).unwrap().to_string(), "0.1");
+        assert!("".parse::<Decimal>().is_err());
+        assert!("1.2.3".parse::<Decimal>().is_err());
+        assert!("abc".parse::<Decimal>().is_err());
+        assert!("99999999999999999999".parse::<Decimal>().is_err());
+        assert!("1e-2147483648".parse::<Decimal>().is_err());
+        assert!("1e2147483647".parse::<Decimal>().is_err());
+        assert_eq!(d("1e-2147483647").to_string(), "0.000000000000000000");
+    }
+
+    #[test]
+    fn test_abs_and_neg() {
+        assert_eq!(d("-1.5").checked_abs().unwrap().to_string(), "1.5");
+        assert_eq!(d("1.5").checked_neg().unwrap().to_string(), "-1.5");
+
+        let min = Decimal::new(i64::MIN, 2);
+        assert_eq!(min.checked_abs(), None);
+        assert_eq!(min.checked_neg(), None);
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        assert_eq!(d("0.1").checked_add(d("0.2")).unwrap().to_string(), "0.3");
+        assert_eq!(
+            d("10.00").checked_sub(d("0.5")).unwrap().to_string(),
+            "9.50"
+        );
+        assert_eq!(d("19.99").checked_mul(d("3")).unwrap().to_string(), "59.97");
+        assert_eq!(d("10").checked_div(d("4")).unwrap().to_string(), "2.5");
+        assert_eq!(
+            d("1").checked_div(d("3")).unwrap().to_string(),
+            "0.333333333333333333"
+        );
+        assert_eq!(
+            d("2").checked_div(d("3")).unwrap().to_string(),
+            "0.666666666666666667"
+        );
+        assert_eq!(d("7.5").checked_rem(d("2")).unwrap().to_string(), "1.5");
+        assert_eq!(d("1.1").checked_pow(2).unwrap().to_string(), "1.21");
+        assert_eq!(d("1").checked_div(d("0")), None);
+        assert_eq!(d("9223372036854775807").checked_add(d("1")), None);
+    }
+
+    #[test]
+    fn test_rounding() {
+        assert_eq!(d("2.675").round_dp(2).to_string(), "2.68");
+        assert_eq!(d("-2.675").round_dp(2).to_string(), "-2.68");
+        assert_eq!(d("2.5").round(), 3);
+        assert_eq!(d("-2.5").round(), -3);
+        assert_eq!(d("-2.5").floor(), -3);
+        assert_eq!(d("-2.5").ceil(), -2);
+        assert_eq!(d("2.5").trunc(), 2);
+        assert_eq!(d("2.5").rescale(3).unwrap().to_string(), "2.500");
+    }
+
+    #[test]
+    fn test_cmp() {
+        assert_eq!(d("1.50"), d("1.5"));
+        assert!(d("-1.5") < d("1"));
+        assert!(d("0.333") > d("0.3"));
+    }
+}
@@ -21,8 +21,9 @@ pub enum ConcretizationError {
     ParseError(String),
     ColumnNotFound(ColumIndexationBy),
     InvalidRegex(String),
-    UnknownFunction(String),
+    UnknownFunction(String, Option<String>),
     InvalidArity(String, InvalidArity),
+    TypeMismatch(String, TypeMismatch),
     TooManyArguments(usize),
     UnknownArgumentName(String),
     StaticEvaluationError(SpecifiedEvaluationError),
@@ -33,11 +34,20 @@ impl Display for ConcretizationError {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
             Self::ColumnNotFound(indexation) => format_column_indexation_error(f, indexation),
-            Self::UnknownFunction(name) => write!(f, "unknown function \"{}\"", name),
+            Self::UnknownFunction(name, suggestion) => {
+                write!(f, "unknown function \"{}\"", name)?;
+
+                if let Some(suggestion) = suggestion {
+                    write!(f, ", did you mean \"{}\"?", suggestion)?;
+                }
+
+                Ok(())
+            }
             Self::UnknownArgumentName(arg_name) => write!(f, "unknown argument \"{}\"", arg_name),
-            Self::ParseError(expr) => write!(f, "could not parse expression: {}", expr),
+            Self::ParseError(msg) => write!(f, "{}", msg),
             Self::InvalidRegex(pattern) => write!(f, "invalid regex {}", pattern),
             Self::InvalidArity(name, arity) => write!(f, "{}: {}", name, arity),
+            Self::TypeMismatch(name, mismatch) => write!(f, "{}: {}", name, mismatch),
             Self::TooManyArguments(actual) => {
                 write!(f, "got {} arguments. Cannot exceed 8.", actual)
             }
@@ -90,6 +100,35 @@ impl Display for InvalidArity {
     }
 }
 
+#[derive(Debug, PartialEq)]
+pub struct TypeMismatch {
+    position: usize,
+    expected: String,
+    got: String,
+}
+
+impl TypeMismatch {
+    pub fn new(position: usize, expected: &str, got: &str) -> Self {
+        Self {
+            position,
+            expected: expected.to_string(),
+            got: got.to_string(),
+        }
+    }
+}
+
+impl Display for TypeMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "expected argument {} to be a {} but got a {}",
+            self.position + 1,
+            self.expected,
+            self.got
+        )
+    }
+}
+
 #[derive(Debug, PartialEq)]
 pub struct SpecifiedEvaluationError {
     pub function_name: String,
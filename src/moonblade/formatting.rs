@@ -0,0 +1,317 @@
+// NOTE: a subset of Python's format specification mini-language, used by
+// f-string placeholders and the format function:
+// [[fill]align][sign][0][width][,][.precision][type]
+// https://docs.python.org/3/library/string.html#format-specification-mini-language
+use std::str::FromStr;
+
+use super::error::EvaluationError;
+use super::types::{DynamicNumber, DynamicValue};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Align {
+    Left,
+    Right,
+    Center,
+}
+
+impl Align {
+    fn from_char(c: char) -> Option<Self> {
+        Some(match c {
+            '<' => Self::Left,
+            '>' => Self::Right,
+            '^' => Self::Center,
+            _ => return None,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum NumberType {
+    Fixed,
+    Percentage,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FormatSpec {
+    fill: char,
+    align: Option<Align>,
+    sign: bool,
+    zero: bool,
+    width: usize,
+    thousands: bool,
+    precision: Option<usize>,
+    number_type: Option<NumberType>,
+}
+
+impl Default for FormatSpec {
+    fn default() -> Self {
+        Self {
+            fill: ' ',
+            align: None,
+            sign: false,
+            zero: false,
+            width: 0,
+            thousands: false,
+            precision: None,
+            number_type: None,
+        }
+    }
+}
+
+fn take_digits(chars: &[char], i: &mut usize) -> Option<usize> {
+    let start = *i;
+
+    while *i < chars.len() && chars[*i].is_ascii_digit() {
+        *i += 1;
+    }
+
+    if start == *i {
+        return None;
+    }
+
+    chars[start..*i].iter().collect::<String>().parse().ok()
+}
+
+impl FromStr for FormatSpec {
+    type Err = String;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        let chars = string.chars().collect::<Vec<_>>();
+        let mut spec = Self::default();
+        let mut i = 0;
+
+        if chars.len() > 1 && Align::from_char(chars[1]).is_some() {
+            spec.fill = chars[0];
+            spec.align = Align::from_char(chars[1]);
+            i = 2;
+        } else if let Some(align) = chars.first().and_then(|c| Align::from_char(*c)) {
+            spec.align = Some(align);
+            i = 1;
+        }
+
+        if chars.get(i) == Some(&'+') {
+            spec.sign = true;
+            i += 1;
+        }
+
+        if chars.get(i) == Some(&'0') {
+            spec.zero = true;
+            i += 1;
+        }
+
+        if let Some(width) = take_digits(&chars, &mut i) {
+            spec.width = width;
+        }
+
+        if chars.get(i) == Some(&',') {
+            spec.thousands = true;
+            i += 1;
+        }
+
+        if chars.get(i) == Some(&'.') {
+            i += 1;
+
+            match take_digits(&chars, &mut i) {
+                None => return Err(format!("missing precision in format spec \"{}\"", string)),
+                Some(precision) => spec.precision = Some(precision),
+            }
+        }
+
+        match chars.get(i) {
+            Some('f') => spec.number_type = Some(NumberType::Fixed),
+            Some('%') => spec.number_type = Some(NumberType::Percentage),
+            _ => (),
+        };
+
+        if spec.number_type.is_some() {
+            i += 1;
+        }
+
+        if i < chars.len() {
+            return Err(format!("invalid format spec \"{}\"", string));
+        }
+
+        Ok(spec)
+    }
+}
+
+fn insert_thousands_separators(digits: &str) -> String {
+    let mut result = String::with_capacity(digits.len() + digits.len() / 3);
+
+    let mut countdown = digits.len() % 3;
+
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && countdown == 0 {
+            result.push(',');
+        }
+
+        countdown = if countdown == 0 { 2 } else { countdown - 1 };
+
+        result.push(c);
+    }
+
+    result
+}
+
+impl FormatSpec {
+    fn is_numeric(&self) -> bool {
+        self.sign
+            || self.zero
+            || self.thousands
+            || self.precision.is_some()
+            || self.number_type.is_some()
+    }
+
+    fn format_number(&self, number: DynamicNumber) -> String {
+        let mut number = number;
+
+        if let Some(NumberType::Percentage) = self.number_type {
+            number = number * DynamicNumber::Integer(100);
+        }
+
+        let mut formatted = match (number, self.precision) {
+            (DynamicNumber::Integer(n), None) => n.to_string(),
+            (DynamicNumber::Float(f), None) => match self.number_type {
+                Some(_) => format!("{:.6}", f),
+                None => f.to_string(),
+            },
+            (DynamicNumber::Decimal(d), None) => d.to_string(),
+            // NOTE: decimals are rounded exactly
+            (DynamicNumber::Decimal(d), Some(precision)) => d
+                .rescale(precision as u32)
+                .map(|d| d.to_string())
+                .unwrap_or_else(|| format!("{:.*}", precision, d.to_f64())),
+            (_, Some(precision)) => format!("{:.*}", precision, number.as_float()),
+        };
+
+        if self.thousands {
+            let (sign, unsigned) = match formatted.strip_prefix('-') {
+                Some(rest) => ("-", rest),
+                None => ("", formatted.as_str()),
+            };
+
+            let (integer_part, rest) = match unsigned.find('.') {
+                Some(i) => unsigned.split_at(i),
+                None => (unsigned, ""),
+            };
+
+            formatted = format!(
+                "{}{}{}",
+                sign,
+                insert_thousands_separators(integer_part),
+                rest
+            );
+        }
+
+        if self.sign && !formatted.starts_with('-') {
+            formatted.insert(0, '+');
+        }
+
+        if let Some(NumberType::Percentage) = self.number_type {
+            formatted.push('%');
+        }
+
+        formatted
+    }
+
+    fn pad(&self, string: String, default_align: Align) -> String {
+        let count = string.chars().count();
+
+        if count >= self.width {
+            return string;
+        }
+
+        let padding = self.width - count;
+
+        // NOTE: zero padding goes between the sign and the digits
+        if self.zero && self.align.is_none() {
+            let (sign, digits) = match string.chars().next() {
+                Some(c @ ('+' | '-')) => (Some(c), &string[1..]),
+                _ => (None, string.as_str()),
+            };
+
+            let mut padded = String::with_capacity(self.width);
+            padded.extend(sign);
+            padded.push_str(&"0".repeat(padding));
+            padded.push_str(digits);
+
+            return padded;
+        }
+
+        let (left, right) = match self.align.unwrap_or(default_align) {
+            Align::Left => (0, padding),
+            Align::Right => (padding, 0),
+            Align::Center => (padding / 2, padding - padding / 2),
+        };
+
+        let fill = self.fill.to_string();
+
+        fill.repeat(left) + &string + &fill.repeat(right)
+    }
+
+    pub fn format(&self, value: &DynamicValue) -> Result<String, EvaluationError> {
+        if self.is_numeric() {
+            let number = value.try_as_number()?;
+
+            return Ok(self.pad(self.format_number(number), Align::Right));
+        }
+
+        let default_align = match value {
+            DynamicValue::Integer(_) | DynamicValue::Float(_) | DynamicValue::Decimal(_) => {
+                Align::Right
+            }
+            _ => Align::Left,
+        };
+
+        Ok(self.pad(value.try_as_str()?.into_owned(), default_align))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn format(spec: &str, value: DynamicValue) -> String {
+        spec.parse::<FormatSpec>().unwrap().format(&value).unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            "*^+08,.2f".parse::<FormatSpec>(),
+            Ok(FormatSpec {
+                fill: '*',
+                align: Some(Align::Center),
+                sign: true,
+                zero: true,
+                width: 8,
+                thousands: true,
+                precision: Some(2),
+                number_type: Some(NumberType::Fixed)
+            })
+        );
+        assert_eq!("".parse::<FormatSpec>(), Ok(FormatSpec::default()));
+        assert!("2x".parse::<FormatSpec>().is_err());
+        assert!(".f".parse::<FormatSpec>().is_err());
+    }
+
+    #[test]
+    fn test_format() {
+        assert_eq!(format(".2", DynamicValue::from(1.23456)), "1.23");
+        assert_eq!(format(".2f", DynamicValue::from("3")), "3.00");
+        assert_eq!(format("05", DynamicValue::from(42)), "00042");
+        assert_eq!(format("+06.1", DynamicValue::from(-4.56)), "-004.6");
+        assert_eq!(format(",", DynamicValue::from(1234567)), "1,234,567");
+        assert_eq!(format(",.1f", DynamicValue::from(-1234.56)), "-1,234.6");
+        assert_eq!(format(".1%", DynamicValue::from(0.256)), "25.6%");
+        assert_eq!(format("5", DynamicValue::from("ab")), "ab   ");
+        assert_eq!(format("5", DynamicValue::from(12)), "   12");
+        assert_eq!(format(">5", DynamicValue::from("ab")), "   ab");
+        assert_eq!(format("-^6", DynamicValue::from("ab")), "--ab--");
+        assert_eq!(format("2", DynamicValue::from("abcd")), "abcd");
+
+        let decimal = DynamicValue::Decimal("1234.565".parse().unwrap());
+        assert_eq!(format(",.2", decimal.clone()), "1,234.57");
+        assert_eq!(format(".1%", decimal), "123456.5%");
+    }
+}
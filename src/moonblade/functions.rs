@@ -1,8 +1,10 @@
 use std::borrow::Cow;
+use std::cell::RefCell;
 use std::cmp::{max, Ordering, PartialOrd};
-use std::collections::HashMap;
+use std::collections::{hash_map::Entry, HashMap, HashSet};
 use std::fs::{self, File};
 use std::io::Read;
+use std::net::IpAddr;
 use std::ops::{Add, Div, Mul, Neg, Rem, Sub};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
@@ -20,17 +22,186 @@ use paltoquet::{
     stemmers::{fr::carry_stemmer, s_stemmer},
     tokenizers::FingerprintTokenizer,
 };
+use percent_encoding::{percent_decode, utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
 use rand::Rng;
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+use unicode_normalization::UnicodeNormalization;
 use unidecode::unidecode;
 use uuid::Uuid;
+use xxhash_rust::xxh3::xxh3_64;
 
 use super::agg::aggregators::Welford;
+use super::casefold;
+use super::decimal::Decimal;
 use super::error::EvaluationError;
+use super::formatting::FormatSpec;
+use super::fuzzy;
+use super::geo::{self, Geometry};
+use super::html;
+use super::ip::{self, Cidr};
 use super::types::{Argument, BoundArguments, DynamicNumber, DynamicValue, FunctionArguments};
+use super::urls;
+use super::utils::ArgumentCache;
 
 type FunctionResult = Result<DynamicValue, EvaluationError>;
 pub type Function = fn(BoundArguments) -> FunctionResult;
 
+// NOTE: names of the functions, excluding operators, used to suggest
+// corrections when an unknown function is called.
+pub static FUNCTION_NAMES: &[&str] = &[
+    "abs",
+    "abspath",
+    "add",
+    "and",
+    "anonymize_ip",
+    "argmax",
+    "argmin",
+    "base64_decode",
+    "base64_encode",
+    "bbox",
+    "bytesize",
+    "captures",
+    "carry_stemmer",
+    "casefold",
+    "ceil",
+    "coalesce",
+    "collapse_whitespace",
+    "compact",
+    "concat",
+    "contains",
+    "copy",
+    "count",
+    "count_matches",
+    "crc32",
+    "damerau_levenshtein",
+    "datetime",
+    "decimal",
+    "dice",
+    "div",
+    "domain_name",
+    "endswith",
+    "enumerate",
+    "eq",
+    "err",
+    "escape_regex",
+    "ext",
+    "filesize",
+    "find_all",
+    "fingerprint",
+    "first",
+    "flatten",
+    "floor",
+    "fmt",
+    "format",
+    "ge",
+    "geohash",
+    "geohash_decode",
+    "get",
+    "gt",
+    "haversine",
+    "hex_decode",
+    "hex_encode",
+    "html_escape",
+    "html_links",
+    "html_to_text",
+    "html_unescape",
+    "idiv",
+    "in_bbox",
+    "in_cidr",
+    "in_polygon",
+    "index_by",
+    "infer_redirection",
+    "ip_to_int",
+    "is_ip",
+    "is_shortened_url",
+    "isfile",
+    "jaccard",
+    "jaro_winkler",
+    "join",
+    "keys",
+    "last",
+    "le",
+    "len",
+    "levenshtein",
+    "log",
+    "longest_common_substring",
+    "lower",
+    "lru",
+    "lt",
+    "ltrim",
+    "match",
+    "max",
+    "md5",
+    "mean",
+    "median",
+    "metaphone",
+    "mime_ext",
+    "min",
+    "mod",
+    "month",
+    "month_day",
+    "move",
+    "mul",
+    "ne",
+    "neg",
+    "normalize_url",
+    "not",
+    "numfmt",
+    "or",
+    "parse_dataurl",
+    "parse_ip",
+    "parse_json",
+    "parse_url",
+    "parse_wkt",
+    "pathjoin",
+    "pjoin",
+    "pow",
+    "random",
+    "range",
+    "read",
+    "read_csv",
+    "read_json",
+    "replace",
+    "round",
+    "rtrim",
+    "s_stemmer",
+    "sha1",
+    "sha256",
+    "slice",
+    "sort",
+    "soundex",
+    "soundex_fr",
+    "split",
+    "sqrt",
+    "startswith",
+    "strftime",
+    "strip_tags",
+    "sub",
+    "sum",
+    "timestamp",
+    "timestamp_ms",
+    "trim",
+    "trunc",
+    "typeof",
+    "unicode_normalize",
+    "unidecode",
+    "unique",
+    "upper",
+    "urldecode",
+    "urlencode",
+    "uuid",
+    "values",
+    "write",
+    "xxh3",
+    "year",
+    "year_month",
+    "year_month_day",
+    "ym",
+    "ymd",
+    "zip",
+];
+
 pub fn get_function(name: &str) -> Option<(Function, FunctionArguments)> {
     Some(match name {
         "==" => (
@@ -67,6 +238,7 @@ pub fn get_function(name: &str) -> Option<(Function, FunctionArguments)> {
             FunctionArguments::variadic(2),
         ),
         "and" => (and, FunctionArguments::variadic(2)),
+        "anonymize_ip" => (anonymize_ip, FunctionArguments::with_range(1..=2)),
         "argmax" => (
             |args| argcompare(args, Ordering::is_gt),
             FunctionArguments::with_range(1..=2),
@@ -75,18 +247,26 @@ pub fn get_function(name: &str) -> Option<(Function, FunctionArguments)> {
             |args| argcompare(args, Ordering::is_lt),
             FunctionArguments::with_range(1..=2),
         ),
+        "base64_decode" => (base64_decode, FunctionArguments::unary()),
+        "base64_encode" => (base64_encode, FunctionArguments::unary()),
+        "bbox" => (bbox, FunctionArguments::unary()),
         "bytesize" => (bytesize, FunctionArguments::unary()),
+        "captures" => (captures, FunctionArguments::binary()),
         "carry_stemmer" => (carry_stemmer_fn, FunctionArguments::unary()),
+        "casefold" => (casefold, FunctionArguments::unary()),
         "ceil" => (
             |args| unary_arithmetic_op(args, DynamicNumber::ceil),
             FunctionArguments::unary(),
         ),
         "coalesce" => (coalesce, FunctionArguments::variadic(2)),
+        "collapse_whitespace" => (collapse_whitespace, FunctionArguments::unary()),
         "compact" => (compact, FunctionArguments::unary()),
         "concat" => (concat, FunctionArguments::variadic(2)),
         "contains" => (contains, FunctionArguments::binary()),
         "copy" => (copy_file, FunctionArguments::binary()),
-        "count" => (count, FunctionArguments::binary()),
+        "count" | "count_matches" => (count, FunctionArguments::binary()),
+        "crc32" => (crc32, FunctionArguments::unary()),
+        "damerau_levenshtein" => (damerau_levenshtein, FunctionArguments::binary()),
         "datetime" => (
             datetime,
             FunctionArguments::complex(vec![
@@ -95,44 +275,83 @@ pub fn get_function(name: &str) -> Option<(Function, FunctionArguments)> {
                 Argument::with_name("timezone"),
             ]),
         ),
+        "decimal" => (decimal, FunctionArguments::with_range(1..=2)),
+        "dice" => (
+            |args| ngram_similarity(args, fuzzy::dice),
+            FunctionArguments::with_range(2..=3),
+        ),
         "div" => (
             |args| variadic_arithmetic_op(args, Div::div),
             FunctionArguments::variadic(2),
         ),
+        "domain_name" => (domain_name, FunctionArguments::unary()),
         "endswith" => (endswith, FunctionArguments::binary()),
+        "enumerate" => (enumerate, FunctionArguments::unary()),
         "err" => (err, FunctionArguments::unary()),
         "escape_regex" => (escape_regex, FunctionArguments::unary()),
         "ext" => (ext, FunctionArguments::unary()),
         "filesize" => (filesize, FunctionArguments::unary()),
+        "find_all" => (find_all, FunctionArguments::with_range(2..=3)),
         "fingerprint" => (fingerprint, FunctionArguments::unary()),
         "first" => (first, FunctionArguments::unary()),
+        "flatten" => (flatten, FunctionArguments::unary()),
         "floor" => (
             |args| unary_arithmetic_op(args, DynamicNumber::floor),
             FunctionArguments::unary(),
         ),
         "fmt" => (fmt, FunctionArguments::variadic(2)),
+        "format" => (format, FunctionArguments::binary()),
         "numfmt" => (fmt_number, FunctionArguments::unary()),
+        "geohash" => (geohash, FunctionArguments::with_range(2..=3)),
+        "geohash_decode" => (geohash_decode, FunctionArguments::unary()),
         "get" => (get, FunctionArguments::with_range(2..=3)),
+        "haversine" => (haversine, FunctionArguments::nary(4)),
+        "hex_decode" => (hex_decode, FunctionArguments::unary()),
+        "hex_encode" => (hex_encode, FunctionArguments::unary()),
+        "html_escape" => (html_escape, FunctionArguments::unary()),
+        "html_links" => (html_links, FunctionArguments::with_range(1..=2)),
+        "html_to_text" => (html_to_text, FunctionArguments::unary()),
+        "html_unescape" => (html_unescape, FunctionArguments::unary()),
         "idiv" => (
             |args| arithmetic_op(args, DynamicNumber::idiv),
             FunctionArguments::binary(),
         ),
+        "in_bbox" => (in_bbox, FunctionArguments::nary(3)),
+        "in_cidr" => (in_cidr, FunctionArguments::binary()),
+        "in_polygon" => (in_polygon, FunctionArguments::nary(3)),
         "index_by" => (index_by, FunctionArguments::binary()),
+        "infer_redirection" => (infer_redirection, FunctionArguments::unary()),
+        "ip_to_int" => (ip_to_int, FunctionArguments::unary()),
+        "is_ip" => (is_ip, FunctionArguments::with_range(1..=2)),
+        "is_shortened_url" => (is_shortened_url, FunctionArguments::unary()),
         "isfile" => (isfile, FunctionArguments::unary()),
+        "jaccard" => (
+            |args| ngram_similarity(args, fuzzy::jaccard),
+            FunctionArguments::with_range(2..=3),
+        ),
+        "jaro_winkler" => (jaro_winkler, FunctionArguments::binary()),
         "join" => (join, FunctionArguments::binary()),
         "keys" => (keys, FunctionArguments::unary()),
         "last" => (last, FunctionArguments::unary()),
         "len" => (len, FunctionArguments::unary()),
+        "levenshtein" => (levenshtein, FunctionArguments::binary()),
         "log" => (
             |args| unary_arithmetic_op(args, DynamicNumber::ln),
             FunctionArguments::unary(),
         ),
+        "longest_common_substring" => (longest_common_substring, FunctionArguments::binary()),
         "ltrim" => (ltrim, FunctionArguments::with_range(1..=2)),
         "lower" => (lower, FunctionArguments::unary()),
+        "lru" => (lru, FunctionArguments::unary()),
         "match" => (regex_match, FunctionArguments::with_range(2..=3)),
         "max" => (variadic_max, FunctionArguments::variadic(2)),
         "md5" => (md5, FunctionArguments::unary()),
         "mean" => (mean, FunctionArguments::unary()),
+        "median" => (median, FunctionArguments::unary()),
+        "metaphone" => (
+            |args| phonetic_key(args, fuzzy::metaphone),
+            FunctionArguments::unary(),
+        ),
         "mime_ext" => (mime_ext, FunctionArguments::unary()),
         "min" => (variadic_min, FunctionArguments::variadic(2)),
         "mod" => (
@@ -156,16 +375,21 @@ pub fn get_function(name: &str) -> Option<(Function, FunctionArguments)> {
             |args| unary_arithmetic_op(args, Neg::neg),
             FunctionArguments::unary(),
         ),
+        "normalize_url" => (normalize_url, FunctionArguments::unary()),
         "not" => (not, FunctionArguments::unary()),
         "or" => (or, FunctionArguments::variadic(2)),
         "parse_dataurl" => (parse_dataurl, FunctionArguments::unary()),
+        "parse_ip" => (parse_ip, FunctionArguments::unary()),
         "parse_json" => (parse_json, FunctionArguments::unary()),
+        "parse_url" => (parse_url, FunctionArguments::unary()),
+        "parse_wkt" => (parse_wkt, FunctionArguments::unary()),
         "pjoin" | "pathjoin" => (pathjoin, FunctionArguments::variadic(2)),
         "pow" => (
             |args| binary_arithmetic_op(args, DynamicNumber::pow),
             FunctionArguments::binary(),
         ),
         "random" => (random, FunctionArguments::nullary()),
+        "range" => (range, FunctionArguments::with_range(1..=3)),
         "read" => (
             read,
             FunctionArguments::complex(vec![
@@ -182,7 +406,18 @@ pub fn get_function(name: &str) -> Option<(Function, FunctionArguments)> {
             FunctionArguments::unary(),
         ),
         "rtrim" => (rtrim, FunctionArguments::with_range(1..=2)),
+        "sha1" => (|args| hexdigest::<Sha1>(args), FunctionArguments::unary()),
+        "sha256" => (|args| hexdigest::<Sha256>(args), FunctionArguments::unary()),
         "slice" => (slice, FunctionArguments::with_range(2..=3)),
+        "sort" => (sort, FunctionArguments::unary()),
+        "soundex" => (
+            |args| phonetic_key(args, fuzzy::soundex),
+            FunctionArguments::unary(),
+        ),
+        "soundex_fr" => (
+            |args| phonetic_key(args, fuzzy::soundex_fr),
+            FunctionArguments::unary(),
+        ),
         "split" => (split, FunctionArguments::with_range(2..=3)),
         "sqrt" => (
             |args| unary_arithmetic_op(args, DynamicNumber::sqrt),
@@ -197,10 +432,12 @@ pub fn get_function(name: &str) -> Option<(Function, FunctionArguments)> {
                 Argument::with_name("timezone"),
             ]),
         ),
+        "strip_tags" => (strip_tags, FunctionArguments::unary()),
         "sub" => (
             |args| variadic_arithmetic_op(args, Sub::sub),
             FunctionArguments::variadic(2),
         ),
+        "sum" => (sum, FunctionArguments::unary()),
         "s_stemmer" => (s_stemmer_fn, FunctionArguments::unary()),
         "eq" => (
             |args| sequence_compare(args, Ordering::is_eq),
@@ -234,11 +471,16 @@ pub fn get_function(name: &str) -> Option<(Function, FunctionArguments)> {
             FunctionArguments::unary(),
         ),
         "typeof" => (type_of, FunctionArguments::unary()),
+        "unicode_normalize" => (unicode_normalize, FunctionArguments::with_range(1..=2)),
         "unidecode" => (apply_unidecode, FunctionArguments::unary()),
+        "unique" => (unique, FunctionArguments::unary()),
         "upper" => (upper, FunctionArguments::unary()),
+        "urldecode" => (urldecode, FunctionArguments::unary()),
+        "urlencode" => (urlencode, FunctionArguments::unary()),
         "uuid" => (uuid, FunctionArguments::nullary()),
         "values" => (values, FunctionArguments::unary()),
         "write" => (write, FunctionArguments::binary()),
+        "xxh3" => (xxh3, FunctionArguments::unary()),
         "year" => (
             |args| custom_strftime(args, "%Y"),
             FunctionArguments::complex(vec![Argument::Positional, Argument::with_name("timezone")]),
@@ -251,6 +493,7 @@ pub fn get_function(name: &str) -> Option<(Function, FunctionArguments)> {
             |args| custom_strftime(args, "%Y-%m"),
             FunctionArguments::complex(vec![Argument::Positional, Argument::with_name("timezone")]),
         ),
+        "zip" => (zip, FunctionArguments::variadic(2)),
         _ => return None,
     })
 }
@@ -308,25 +551,31 @@ fn escape_regex(args: BoundArguments) -> FunctionResult {
     Ok(DynamicValue::from(regex::escape(args.get1_str()?.as_ref())))
 }
 
-fn md5(args: BoundArguments) -> FunctionResult {
-    Ok(DynamicValue::from(format!(
-        "{:x}",
-        md5::compute(args.get1().try_as_bytes()?)
-    )))
-}
-
 fn split(args: BoundArguments) -> FunctionResult {
     let to_split = args.get(0).unwrap().try_as_str()?;
-    let pattern = args.get(1).unwrap().try_as_str()?;
+    let pattern_arg = args.get(1).unwrap();
     let count = args.get(2);
 
-    let splitted: Vec<DynamicValue> = if let Some(c) = count {
-        to_split
-            .splitn(c.try_as_usize()? + 1, pattern.as_ref())
-            .map(DynamicValue::from)
-            .collect()
+    let splitted: Vec<DynamicValue> = if let DynamicValue::Regex(regex) = pattern_arg {
+        if let Some(c) = count {
+            regex
+                .splitn(&to_split, c.try_as_usize()? + 1)
+                .map(DynamicValue::from)
+                .collect()
+        } else {
+            regex.split(&to_split).map(DynamicValue::from).collect()
+        }
     } else {
-        to_split.split(&*pattern).map(DynamicValue::from).collect()
+        let pattern = pattern_arg.try_as_str()?;
+
+        if let Some(c) = count {
+            to_split
+                .splitn(c.try_as_usize()? + 1, pattern.as_ref())
+                .map(DynamicValue::from)
+                .collect()
+        } else {
+            to_split.split(&*pattern).map(DynamicValue::from).collect()
+        }
     };
 
     Ok(DynamicValue::from(splitted))
@@ -346,6 +595,44 @@ fn upper(args: BoundArguments) -> FunctionResult {
     })
 }
 
+fn casefold(args: BoundArguments) -> FunctionResult {
+    let string = args.get1_str()?;
+
+    Ok(DynamicValue::from(casefold::casefold(&string)))
+}
+
+fn collapse_whitespace(args: BoundArguments) -> FunctionResult {
+    let string = args.get1_str()?;
+
+    Ok(DynamicValue::from(
+        string.split_whitespace().collect::<Vec<_>>().join(" "),
+    ))
+}
+
+fn unicode_normalize(args: BoundArguments) -> FunctionResult {
+    let string = args.get(0).unwrap().try_as_str()?;
+
+    let form = match args.get(1) {
+        None => Cow::Borrowed("NFC"),
+        Some(arg) => arg.try_as_str()?,
+    };
+
+    let normalized: String = match form.to_ascii_uppercase().as_str() {
+        "NFC" => string.nfc().collect(),
+        "NFD" => string.nfd().collect(),
+        "NFKC" => string.nfkc().collect(),
+        "NFKD" => string.nfkd().collect(),
+        _ => {
+            return Err(EvaluationError::Custom(format!(
+                "unknown normalization form \"{}\", expecting one of NFC, NFD, NFKC or NFKD",
+                form
+            )))
+        }
+    };
+
+    Ok(DynamicValue::from(normalized))
+}
+
 fn len(mut args: BoundArguments) -> FunctionResult {
     let arg = args.pop1();
 
@@ -461,6 +748,17 @@ fn fmt(args: BoundArguments) -> FunctionResult {
     Ok(DynamicValue::from(formatted))
 }
 
+fn format(args: BoundArguments) -> FunctionResult {
+    let (value, spec) = args.get2();
+
+    let spec = spec
+        .try_as_str()?
+        .parse::<FormatSpec>()
+        .map_err(EvaluationError::Custom)?;
+
+    Ok(DynamicValue::from(spec.format(value)?))
+}
+
 fn fmt_number(mut args: BoundArguments) -> FunctionResult {
     let number = args.pop1().try_as_number()?;
 
@@ -511,6 +809,9 @@ fn get_subroutine<'a>(
     key: &'a DynamicValue,
 ) -> Result<Option<DynamicValue>, EvaluationError> {
     Ok(match target {
+        // NOTE: getting anything from null yields null, so that chained
+        // indexing such as `x[a][b]` does not fail on missing keys.
+        DynamicValue::None => None,
         DynamicValue::String(value) => {
             let mut index = key.try_as_i64()?;
 
@@ -724,6 +1025,80 @@ fn regex_match(args: BoundArguments) -> FunctionResult {
     }
 }
 
+fn find_all(args: BoundArguments) -> FunctionResult {
+    let haystack = args.get(0).unwrap().try_as_str()?;
+    let arg = args.get(1).unwrap();
+
+    let pattern = match arg.try_as_regex() {
+        Ok(regex) => regex,
+        Err(_) => {
+            if args.get(2).is_some() {
+                return Err(EvaluationError::Custom(
+                    "find_all group can only be given with a regular expression".to_string(),
+                ));
+            }
+
+            let pattern = arg.try_as_str()?;
+
+            return Ok(DynamicValue::from(
+                haystack
+                    .matches(pattern.as_ref())
+                    .map(DynamicValue::from)
+                    .collect::<Vec<_>>(),
+            ));
+        }
+    };
+
+    let matches: Vec<DynamicValue> = match args.get(2) {
+        None => pattern
+            .find_iter(&haystack)
+            .map(|m| DynamicValue::from(m.as_str()))
+            .collect(),
+        Some(DynamicValue::Integer(group)) => {
+            let group = *group as usize;
+
+            pattern
+                .captures_iter(&haystack)
+                .map(|caps| DynamicValue::from(caps.get(group).map(|g| g.as_str())))
+                .collect()
+        }
+        Some(group) => {
+            let name = group.try_as_str()?;
+
+            pattern
+                .captures_iter(&haystack)
+                .map(|caps| DynamicValue::from(caps.name(&name).map(|g| g.as_str())))
+                .collect()
+        }
+    };
+
+    Ok(DynamicValue::from(matches))
+}
+
+fn captures(args: BoundArguments) -> FunctionResult {
+    let haystack = args.get(0).unwrap().try_as_str()?;
+    let pattern = args.get(1).unwrap().try_as_regex()?;
+
+    let caps = match pattern.captures(&haystack) {
+        None => return Ok(DynamicValue::None),
+        Some(caps) => caps,
+    };
+
+    let mut map = HashMap::new();
+
+    // NOTE: unnamed groups are keyed by their index
+    for (i, name) in pattern.capture_names().enumerate().skip(1) {
+        let key = match name {
+            Some(name) => name.to_string(),
+            None => i.to_string(),
+        };
+
+        map.insert(key, DynamicValue::from(caps.get(i).map(|g| g.as_str())));
+    }
+
+    Ok(DynamicValue::from(map))
+}
+
 fn replace(args: BoundArguments) -> FunctionResult {
     let (arg1, arg2, arg3) = args.get3();
 
@@ -761,6 +1136,120 @@ fn compact(mut args: BoundArguments) -> FunctionResult {
     })
 }
 
+fn sort(mut args: BoundArguments) -> FunctionResult {
+    let list = args.pop1().try_into_arc_list()?;
+    let mut sorted = Arc::try_unwrap(list).unwrap_or_else(|borrowed| borrowed.to_vec());
+    let mut error: Option<EvaluationError> = None;
+
+    sorted.sort_by(|a, b| match a.try_cmp(b) {
+        Ok(ordering) => ordering,
+        Err(err) => {
+            error.get_or_insert(err);
+            Ordering::Equal
+        }
+    });
+
+    match error {
+        Some(err) => Err(err),
+        None => Ok(DynamicValue::from(sorted)),
+    }
+}
+
+fn unique(args: BoundArguments) -> FunctionResult {
+    let list = args.get1().try_as_list()?;
+
+    let mut seen: HashSet<Cow<[u8]>> = HashSet::with_capacity(list.len());
+    let mut deduped = Vec::new();
+
+    for item in list {
+        if seen.insert(item.serialize_as_bytes()) {
+            deduped.push(item.clone());
+        }
+    }
+
+    Ok(DynamicValue::from(deduped))
+}
+
+fn zip(args: BoundArguments) -> FunctionResult {
+    let lists = args
+        .into_iter()
+        .map(|arg| arg.try_into_arc_list())
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let len = lists.iter().map(|list| list.len()).min().unwrap_or(0);
+
+    Ok(DynamicValue::from(
+        (0..len)
+            .map(|i| {
+                DynamicValue::from(lists.iter().map(|list| list[i].clone()).collect::<Vec<_>>())
+            })
+            .collect::<Vec<_>>(),
+    ))
+}
+
+fn flatten(args: BoundArguments) -> FunctionResult {
+    let list = args.get1().try_as_list()?;
+
+    let mut flattened = Vec::with_capacity(list.len());
+
+    for item in list {
+        match item {
+            DynamicValue::List(sublist) => flattened.extend(sublist.iter().cloned()),
+            _ => flattened.push(item.clone()),
+        }
+    }
+
+    Ok(DynamicValue::from(flattened))
+}
+
+fn enumerate(args: BoundArguments) -> FunctionResult {
+    let list = args.get1().try_as_list()?;
+
+    Ok(DynamicValue::from(
+        list.iter()
+            .enumerate()
+            .map(|(i, item)| DynamicValue::from(vec![DynamicValue::from(i), item.clone()]))
+            .collect::<Vec<_>>(),
+    ))
+}
+
+fn range(args: BoundArguments) -> FunctionResult {
+    let (start, end, step) = match args.len() {
+        1 => (0, args.get1().try_as_i64()?, 1),
+        2 => (
+            args.get(0).unwrap().try_as_i64()?,
+            args.get(1).unwrap().try_as_i64()?,
+            1,
+        ),
+        _ => (
+            args.get(0).unwrap().try_as_i64()?,
+            args.get(1).unwrap().try_as_i64()?,
+            args.get(2).unwrap().try_as_i64()?,
+        ),
+    };
+
+    if step == 0 {
+        return Err(EvaluationError::Custom(
+            "range step cannot be 0".to_string(),
+        ));
+    }
+
+    let mut items = Vec::new();
+    let mut current = start;
+
+    while (step > 0 && current < end) || (step < 0 && current > end) {
+        items.push(DynamicValue::from(current));
+
+        // NOTE: stepping past integer bounds means we are done
+        current = match current.checked_add(step) {
+            Some(next) => next,
+            None => break,
+        };
+    }
+
+    Ok(DynamicValue::from(items))
+}
+
 // Maps
 fn keys(args: BoundArguments) -> FunctionResult {
     let map = args.get1().try_as_map()?;
@@ -822,6 +1311,35 @@ where
     Ok(DynamicValue::from(acc))
 }
 
+fn decimal(args: BoundArguments) -> FunctionResult {
+    let value = args.get1();
+
+    let decimal = match value {
+        DynamicValue::Decimal(d) => Some(*d),
+        DynamicValue::Integer(i) => Some(Decimal::from(*i)),
+        DynamicValue::Float(f) => Decimal::from_f64(*f),
+        _ => value.try_as_str()?.parse::<Decimal>().ok(),
+    }
+    .ok_or_else(|| EvaluationError::from_cast(value, "decimal"))?;
+
+    match args.get(1) {
+        None => Ok(DynamicValue::from(decimal)),
+        Some(scale) => {
+            let scale = scale.try_as_usize()?;
+
+            decimal
+                .rescale(scale as u32)
+                .map(DynamicValue::from)
+                .ok_or_else(|| {
+                    EvaluationError::Custom(format!(
+                        "cannot represent {} with {} decimal places",
+                        decimal, scale
+                    ))
+                })
+        }
+    }
+}
+
 fn unary_arithmetic_op<F>(mut args: BoundArguments, op: F) -> FunctionResult
 where
     F: Fn(DynamicNumber) -> DynamicNumber,
@@ -992,15 +1510,6 @@ fn or(args: BoundArguments) -> FunctionResult {
     Ok(last.unwrap())
 }
 
-// TODO: rewrap those to take lists instead, since the variadic usage is mostly moot
-// fn all(args: BoundArguments) -> FunctionResult {
-//     Ok(DynamicValue::from(args.into_iter().all(|v| v.is_truthy())))
-// }
-
-// fn any(args: BoundArguments) -> FunctionResult {
-//     Ok(DynamicValue::from(args.into_iter().any(|v| v.is_truthy())))
-// }
-
 // Comparison
 fn abstract_compare<F>(mut args: BoundArguments, validate: F) -> FunctionResult
 where
@@ -1056,6 +1565,40 @@ fn mean(args: BoundArguments) -> FunctionResult {
     Ok(DynamicValue::from(welford.mean()))
 }
 
+fn sum(args: BoundArguments) -> FunctionResult {
+    let items = args.get1().try_as_list()?;
+    let mut total = DynamicNumber::Integer(0);
+
+    for item in items {
+        total += item.try_as_number()?;
+    }
+
+    Ok(DynamicValue::from(total))
+}
+
+fn median(args: BoundArguments) -> FunctionResult {
+    let items = args.get1().try_as_list()?;
+
+    let mut numbers = items
+        .iter()
+        .map(|item| item.try_as_number())
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if numbers.is_empty() {
+        return Ok(DynamicValue::None);
+    }
+
+    numbers.sort();
+
+    let mid = numbers.len() / 2;
+
+    Ok(if numbers.len() % 2 == 1 {
+        DynamicValue::from(numbers[mid])
+    } else {
+        DynamicValue::from((numbers[mid - 1].as_float() + numbers[mid].as_float()) / 2.0)
+    })
+}
+
 // IO
 fn abspath(args: BoundArguments) -> FunctionResult {
     let arg = args.get1_str()?;
@@ -1308,6 +1851,356 @@ fn bytesize(args: BoundArguments) -> FunctionResult {
     Ok(DynamicValue::from(human_readable))
 }
 
+// Hashing & encoding
+fn md5(args: BoundArguments) -> FunctionResult {
+    Ok(DynamicValue::from(format!(
+        "{:x}",
+        md5::compute(args.get1().try_as_bytes()?)
+    )))
+}
+
+fn hexdigest<D: Digest>(args: BoundArguments) -> FunctionResult {
+    let digest = D::digest(args.get1().try_as_bytes()?);
+
+    Ok(DynamicValue::from(hex::encode(digest)))
+}
+
+fn xxh3(args: BoundArguments) -> FunctionResult {
+    Ok(DynamicValue::from(format!(
+        "{:016x}",
+        xxh3_64(args.get1().try_as_bytes()?)
+    )))
+}
+
+fn crc32(args: BoundArguments) -> FunctionResult {
+    Ok(DynamicValue::from(
+        crc32fast::hash(args.get1().try_as_bytes()?) as i64,
+    ))
+}
+
+fn base64_encode(args: BoundArguments) -> FunctionResult {
+    Ok(DynamicValue::from(
+        BASE64_STANDARD.encode(args.get1().try_as_bytes()?),
+    ))
+}
+
+fn base64_decode(args: BoundArguments) -> FunctionResult {
+    let bytes = BASE64_STANDARD
+        .decode(args.get1().try_as_bytes()?)
+        .map_err(|_| EvaluationError::Custom("invalid base64".to_string()))?;
+
+    Ok(DynamicValue::from_owned_bytes(bytes))
+}
+
+fn hex_encode(args: BoundArguments) -> FunctionResult {
+    Ok(DynamicValue::from(hex::encode(args.get1().try_as_bytes()?)))
+}
+
+fn hex_decode(args: BoundArguments) -> FunctionResult {
+    let bytes = hex::decode(args.get1().try_as_bytes()?)
+        .map_err(|_| EvaluationError::Custom("invalid hexadecimal".to_string()))?;
+
+    Ok(DynamicValue::from_owned_bytes(bytes))
+}
+
+// NOTE: unreserved characters, as per RFC 3986, are kept as-is
+const URL_ENCODE_SET: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'_')
+    .remove(b'.')
+    .remove(b'~');
+
+fn urlencode(args: BoundArguments) -> FunctionResult {
+    let string = args.get1_str()?;
+
+    Ok(DynamicValue::from(
+        utf8_percent_encode(&string, URL_ENCODE_SET).to_string(),
+    ))
+}
+
+fn urldecode(args: BoundArguments) -> FunctionResult {
+    let bytes: Vec<u8> = percent_decode(args.get1().try_as_bytes()?).collect();
+
+    Ok(DynamicValue::from_owned_bytes(bytes))
+}
+
+// Html
+fn strip_tags(args: BoundArguments) -> FunctionResult {
+    let string = args.get1_str()?;
+
+    Ok(DynamicValue::from(html::strip_tags(&string)))
+}
+
+fn html_to_text(args: BoundArguments) -> FunctionResult {
+    let string = args.get1_str()?;
+
+    Ok(DynamicValue::from(html::to_text(&string)))
+}
+
+fn html_unescape(args: BoundArguments) -> FunctionResult {
+    let string = args.get1_str()?;
+
+    Ok(DynamicValue::from(html::unescape(&string)))
+}
+
+fn html_escape(args: BoundArguments) -> FunctionResult {
+    let string = args.get1_str()?;
+
+    Ok(DynamicValue::from(html::escape(&string)))
+}
+
+fn html_links(args: BoundArguments) -> FunctionResult {
+    let string = args.get(0).unwrap().try_as_str()?;
+    let links = html::links(&string);
+
+    let base_url = match args.get(1) {
+        None => None,
+        Some(arg) => {
+            let base_url = arg.try_as_str()?;
+
+            Some(url::Url::parse(&base_url).map_err(|_| {
+                EvaluationError::Custom(format!("invalid base url \"{}\"", base_url))
+            })?)
+        }
+    };
+
+    Ok(DynamicValue::from(
+        links
+            .into_iter()
+            .map(|link| match &base_url {
+                // NOTE: links that cannot be resolved are kept as-is
+                Some(base_url) => base_url
+                    .join(&link)
+                    .map(|resolved| resolved.to_string())
+                    .unwrap_or(link),
+                None => link,
+            })
+            .map(DynamicValue::from)
+            .collect::<Vec<_>>(),
+    ))
+}
+
+// Ip addresses
+fn try_parse_ip(value: &DynamicValue) -> Result<IpAddr, EvaluationError> {
+    let string = value.try_as_str()?;
+
+    string
+        .trim()
+        .parse::<IpAddr>()
+        .map_err(|_| EvaluationError::Custom(format!("invalid ip address \"{}\"", string)))
+}
+
+fn is_ip(args: BoundArguments) -> FunctionResult {
+    let string = args.get(0).unwrap().try_as_str()?;
+
+    let ip = match string.trim().parse::<IpAddr>() {
+        Err(_) => return Ok(DynamicValue::from(false)),
+        Ok(ip) => ip,
+    };
+
+    Ok(DynamicValue::from(match args.get(1) {
+        None => true,
+        Some(version) => match version.try_as_usize()? {
+            4 => ip.is_ipv4(),
+            6 => ip.is_ipv6(),
+            _ => {
+                return Err(EvaluationError::Custom(
+                    "ip version should be 4 or 6".to_string(),
+                ))
+            }
+        },
+    }))
+}
+
+fn parse_ip(args: BoundArguments) -> FunctionResult {
+    let ip = try_parse_ip(args.get1())?;
+
+    let mut map = HashMap::with_capacity(6);
+
+    map.insert(
+        "version".to_string(),
+        DynamicValue::from(if ip.is_ipv4() { 4 } else { 6 }),
+    );
+    map.insert("address".to_string(), DynamicValue::from(ip.to_string()));
+    map.insert(
+        "is_private".to_string(),
+        DynamicValue::from(ip::is_private(&ip)),
+    );
+    map.insert(
+        "is_loopback".to_string(),
+        DynamicValue::from(ip.is_loopback()),
+    );
+    map.insert(
+        "is_multicast".to_string(),
+        DynamicValue::from(ip.is_multicast()),
+    );
+    map.insert(
+        "is_unspecified".to_string(),
+        DynamicValue::from(ip.is_unspecified()),
+    );
+
+    Ok(DynamicValue::from(map))
+}
+
+fn parse_cidr_ranges(ranges: &DynamicValue) -> Result<Vec<Cidr>, EvaluationError> {
+    let parse_range = |range: &DynamicValue| -> Result<Option<Cidr>, EvaluationError> {
+        let range = range.try_as_str()?;
+
+        // NOTE: blank lines are ignored so that ranges can easily be read
+        // from a file, e.g. split(read("ranges.txt"), "\n")
+        if range.trim().is_empty() {
+            return Ok(None);
+        }
+
+        range
+            .parse::<Cidr>()
+            .map(Some)
+            .map_err(EvaluationError::Custom)
+    };
+
+    match ranges {
+        DynamicValue::List(list) => list
+            .iter()
+            .filter_map(|range| parse_range(range).transpose())
+            .collect(),
+        _ => Ok(parse_range(ranges)?.into_iter().collect()),
+    }
+}
+
+thread_local! {
+    static CIDR_RANGES_CACHE: RefCell<ArgumentCache<Vec<Cidr>>> =
+        RefCell::new(ArgumentCache::new());
+}
+
+fn in_cidr(args: BoundArguments) -> FunctionResult {
+    let (ip, ranges) = args.get2();
+    let ip = try_parse_ip(ip)?;
+
+    // NOTE: ranges are usually given as a literal or read from a file, so
+    // they are only parsed once instead of every row
+    let ranges = CIDR_RANGES_CACHE.with(|cache| {
+        cache
+            .borrow_mut()
+            .get_or_try_insert_with(ranges, parse_cidr_ranges)
+    })?;
+
+    Ok(DynamicValue::from(
+        ranges.iter().any(|cidr| cidr.contains(&ip)),
+    ))
+}
+
+// NOTE: integers are returned as strings of decimal digits, since IPv6
+// addresses do not fit in 64 bits.
+fn ip_to_int(args: BoundArguments) -> FunctionResult {
+    Ok(DynamicValue::from(match try_parse_ip(args.get1())? {
+        IpAddr::V4(v4) => u32::from(v4).to_string(),
+        IpAddr::V6(v6) => u128::from(v6).to_string(),
+    }))
+}
+
+fn anonymize_ip(args: BoundArguments) -> FunctionResult {
+    let ip = try_parse_ip(args.get(0).unwrap())?;
+    let max = ip::max_prefix_len(&ip);
+
+    let prefix_len = match args.get(1) {
+        None => {
+            if ip.is_ipv4() {
+                24
+            } else {
+                48
+            }
+        }
+        Some(arg) => match arg.try_as_usize()? {
+            p if p <= max as usize => p as u8,
+            _ => {
+                return Err(EvaluationError::Custom(format!(
+                    "prefix length should not exceed {}",
+                    max
+                )))
+            }
+        },
+    };
+
+    Ok(DynamicValue::from(ip::truncate(ip, prefix_len).to_string()))
+}
+
+// Urls
+fn try_parse_url(args: &BoundArguments) -> Result<url::Url, EvaluationError> {
+    let string = args.get1_str()?;
+
+    urls::parse(&string)
+        .ok_or_else(|| EvaluationError::Custom(format!("cannot parse \"{}\" as url", string)))
+}
+
+fn parse_url(args: BoundArguments) -> FunctionResult {
+    let url = try_parse_url(&args)?;
+
+    // NOTE: repeated parameters, e.g. "q=1&q=2", are collected into a list
+    let mut query: HashMap<String, DynamicValue> = HashMap::new();
+
+    for (name, value) in url.query_pairs() {
+        let value = DynamicValue::from(value);
+
+        match query.entry(name.into_owned()) {
+            Entry::Vacant(entry) => {
+                entry.insert(value);
+            }
+            Entry::Occupied(mut entry) => match entry.get_mut() {
+                DynamicValue::List(values) => Arc::make_mut(values).push(value),
+                first_value => {
+                    *first_value = DynamicValue::from(vec![first_value.clone(), value]);
+                }
+            },
+        }
+    }
+
+    let mut map = HashMap::new();
+
+    map.insert("scheme".to_string(), DynamicValue::from(url.scheme()));
+    map.insert("host".to_string(), DynamicValue::from(url.host_str()));
+    map.insert(
+        "port".to_string(),
+        DynamicValue::from(url.port().map(|port| port as i64)),
+    );
+    map.insert("path".to_string(), DynamicValue::from(url.path()));
+    map.insert("query".to_string(), DynamicValue::from(query));
+    map.insert("fragment".to_string(), DynamicValue::from(url.fragment()));
+
+    Ok(DynamicValue::from(map))
+}
+
+fn domain_name(args: BoundArguments) -> FunctionResult {
+    let url = try_parse_url(&args)?;
+
+    Ok(DynamicValue::from(urls::registered_domain(&url)))
+}
+
+fn normalize_url(args: BoundArguments) -> FunctionResult {
+    let url = try_parse_url(&args)?;
+
+    Ok(DynamicValue::from(urls::normalize(url)))
+}
+
+fn is_shortened_url(args: BoundArguments) -> FunctionResult {
+    let url = try_parse_url(&args)?;
+
+    Ok(DynamicValue::from(urls::is_shortened(&url)))
+}
+
+fn infer_redirection(args: BoundArguments) -> FunctionResult {
+    let url = try_parse_url(&args)?;
+
+    Ok(DynamicValue::from(
+        urls::infer_redirection(&url).unwrap_or(url).as_str(),
+    ))
+}
+
+fn lru(args: BoundArguments) -> FunctionResult {
+    let url = try_parse_url(&args)?;
+
+    Ok(DynamicValue::from(urls::lru(&url)))
+}
+
 // Dates
 fn timestamp(args: BoundArguments) -> FunctionResult {
     let seconds = args.get1().try_as_i64()?;
@@ -1496,6 +2389,172 @@ fn carry_stemmer_fn(args: BoundArguments) -> FunctionResult {
     Ok(DynamicValue::from(carry_stemmer(&string)))
 }
 
+fn levenshtein(args: BoundArguments) -> FunctionResult {
+    let (a, b) = args.get2_str()?;
+
+    Ok(DynamicValue::from(fuzzy::levenshtein(&a, &b)))
+}
+
+fn damerau_levenshtein(args: BoundArguments) -> FunctionResult {
+    let (a, b) = args.get2_str()?;
+
+    Ok(DynamicValue::from(fuzzy::damerau_levenshtein(&a, &b)))
+}
+
+fn jaro_winkler(args: BoundArguments) -> FunctionResult {
+    let (a, b) = args.get2_str()?;
+
+    Ok(DynamicValue::from(fuzzy::jaro_winkler(&a, &b)))
+}
+
+fn ngram_similarity<F>(args: BoundArguments, metric: F) -> FunctionResult
+where
+    F: Fn(&str, &str, usize) -> f64,
+{
+    let a = args.get(0).unwrap().try_as_str()?;
+    let b = args.get(1).unwrap().try_as_str()?;
+
+    let n = match args.get(2) {
+        None => 2,
+        Some(arg) => arg.try_as_usize()?,
+    };
+
+    if n == 0 {
+        return Err(EvaluationError::Custom(
+            "n-gram size should be greater than 0".to_string(),
+        ));
+    }
+
+    Ok(DynamicValue::from(metric(&a, &b, n)))
+}
+
+fn longest_common_substring(args: BoundArguments) -> FunctionResult {
+    let (a, b) = args.get2_str()?;
+
+    Ok(DynamicValue::from(fuzzy::longest_common_substring(&a, &b)))
+}
+
+fn phonetic_key<F>(args: BoundArguments, algorithm: F) -> FunctionResult
+where
+    F: Fn(&str) -> String,
+{
+    let string = args.get1().try_as_str()?;
+
+    Ok(DynamicValue::from(algorithm(&string)))
+}
+
+// Geospatial
+fn haversine(args: BoundArguments) -> FunctionResult {
+    let coords = args
+        .into_iter()
+        .map(|arg| arg.try_as_f64())
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(DynamicValue::from(geo::haversine(
+        coords[0], coords[1], coords[2], coords[3],
+    )))
+}
+
+fn geohash(args: BoundArguments) -> FunctionResult {
+    let lat = args.get(0).unwrap().try_as_f64()?;
+    let lon = args.get(1).unwrap().try_as_f64()?;
+
+    let precision = match args.get(2) {
+        None => 12,
+        Some(arg) => arg.try_as_usize()?,
+    };
+
+    if !(1..=12).contains(&precision) {
+        return Err(EvaluationError::Custom(
+            "geohash precision should be between 1 and 12".to_string(),
+        ));
+    }
+
+    if !(-90.0..=90.0).contains(&lat) || !(-180.0..=180.0).contains(&lon) {
+        return Err(EvaluationError::Custom(format!(
+            "invalid coordinates: {}, {}",
+            lat, lon
+        )));
+    }
+
+    Ok(DynamicValue::from(geo::geohash_encode(lat, lon, precision)))
+}
+
+fn geohash_decode(args: BoundArguments) -> FunctionResult {
+    let hash = args.get1_str()?;
+
+    match geo::geohash_decode(&hash) {
+        Some((lat, lon)) => Ok(DynamicValue::from(vec![
+            DynamicValue::from(lat),
+            DynamicValue::from(lon),
+        ])),
+        None => Err(EvaluationError::Custom(format!(
+            "invalid geohash \"{}\"",
+            hash
+        ))),
+    }
+}
+
+fn parse_wkt(args: BoundArguments) -> FunctionResult {
+    let string = args.get1_str()?;
+
+    geo::parse_wkt(&string)
+        .map(|geometry| geometry.to_geojson())
+        .map_err(EvaluationError::Custom)
+}
+
+fn bbox(args: BoundArguments) -> FunctionResult {
+    let geometry = Geometry::from_value(args.get1())?;
+
+    Ok(match geometry.bbox() {
+        None => DynamicValue::None,
+        Some(bbox) => DynamicValue::from(bbox.map(DynamicValue::from).to_vec()),
+    })
+}
+
+fn in_bbox(args: BoundArguments) -> FunctionResult {
+    let lat = args.get(0).unwrap().try_as_f64()?;
+    let lon = args.get(1).unwrap().try_as_f64()?;
+    let bbox = args.get(2).unwrap().try_as_list()?;
+
+    let bbox = match bbox.as_slice() {
+        [min_lon, min_lat, max_lon, max_lat] => [
+            min_lon.try_as_f64()?,
+            min_lat.try_as_f64()?,
+            max_lon.try_as_f64()?,
+            max_lat.try_as_f64()?,
+        ],
+        _ => {
+            return Err(EvaluationError::Custom(
+                "bbox should be a list of 4 numbers: [min_lon, min_lat, max_lon, max_lat]"
+                    .to_string(),
+            ))
+        }
+    };
+
+    Ok(DynamicValue::from(
+        (bbox[0]..=bbox[2]).contains(&lon) && (bbox[1]..=bbox[3]).contains(&lat),
+    ))
+}
+
+thread_local! {
+    static GEOMETRY_CACHE: RefCell<ArgumentCache<Geometry>> = RefCell::new(ArgumentCache::new());
+}
+
+fn in_polygon(args: BoundArguments) -> FunctionResult {
+    let lat = args.get(0).unwrap().try_as_f64()?;
+    let lon = args.get(1).unwrap().try_as_f64()?;
+
+    // NOTE: constant geometries are only parsed once instead of every row
+    let geometry = GEOMETRY_CACHE.with(|cache| {
+        cache
+            .borrow_mut()
+            .get_or_try_insert_with(args.get(2).unwrap(), Geometry::from_value)
+    })?;
+
+    Ok(DynamicValue::from(geometry.contains(lon, lat)?))
+}
+
 // Utils
 fn err(args: BoundArguments) -> FunctionResult {
     let arg = args.get1_str()?;
@@ -1549,3 +2608,34 @@ fn mime_ext(args: BoundArguments) -> FunctionResult {
         None => Err(EvaluationError::Custom("unknown MIME type".to_string())),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use regex::Regex;
+
+    use super::super::special_functions::SPECIAL_FUNCTION_NAMES;
+    use crate::cmd::moonblade::MOONBLADE_FUNCTIONS_HELP;
+
+    #[test]
+    fn test_function_names() {
+        for name in FUNCTION_NAMES {
+            assert!(get_function(name).is_some(), "unknown function {}", name);
+        }
+
+        assert!(FUNCTION_NAMES.windows(2).all(|w| w[0] < w[1]));
+
+        let documented = Regex::new(r"(?m)^\s+- ([a-z0-9_]+)\(").unwrap();
+
+        for caps in documented.captures_iter(MOONBLADE_FUNCTIONS_HELP) {
+            let name = &caps[1];
+
+            assert!(
+                FUNCTION_NAMES.contains(&name) || SPECIAL_FUNCTION_NAMES.contains(&name),
+                "documented function {} is missing from FUNCTION_NAMES",
+                name
+            );
+        }
+    }
+}
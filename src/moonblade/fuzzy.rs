@@ -0,0 +1,637 @@
+// NOTE: string similarity metrics and phonetic algorithms used by the
+// fuzzy matching functions. Distances work on chars rather than bytes
+// so that they remain meaningful for non-ASCII strings, while phonetic
+// keys are computed on the transliterated ASCII version of the string.
+use std::collections::HashSet;
+
+use unidecode::unidecode;
+
+pub fn levenshtein(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+
+    if a.is_empty() {
+        return b.len();
+    }
+
+    if b.is_empty() {
+        return a.len();
+    }
+
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.iter().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+
+        for (j, cb) in b.iter().enumerate() {
+            let above = row[j + 1];
+            let cost = if ca == cb { 0 } else { 1 };
+
+            row[j + 1] = (above + 1).min(row[j] + 1).min(diagonal + cost);
+            diagonal = above;
+        }
+    }
+
+    row[b.len()]
+}
+
+// NOTE: this is the "optimal string alignment" variant of the distance,
+// where a substring cannot be edited more than once.
+pub fn damerau_levenshtein(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+
+    if a.is_empty() {
+        return b.len();
+    }
+
+    if b.is_empty() {
+        return a.len();
+    }
+
+    let width = b.len() + 1;
+    let mut matrix = vec![0usize; (a.len() + 1) * width];
+
+    for i in 0..=a.len() {
+        matrix[i * width] = i;
+    }
+
+    for (j, cell) in matrix.iter_mut().take(width).enumerate() {
+        *cell = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+
+            let mut distance = (matrix[(i - 1) * width + j] + 1)
+                .min(matrix[i * width + j - 1] + 1)
+                .min(matrix[(i - 1) * width + j - 1] + cost);
+
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(matrix[(i - 2) * width + j - 2] + 1);
+            }
+
+            matrix[i * width + j] = distance;
+        }
+    }
+
+    matrix[a.len() * width + b.len()]
+}
+
+fn jaro(a: &[char], b: &[char]) -> f64 {
+    if a.is_empty() && b.is_empty() {
+        return 1.0;
+    }
+
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+
+    let window = (a.len().max(b.len()) / 2).saturating_sub(1);
+
+    let mut a_matches = vec![false; a.len()];
+    let mut b_matches = vec![false; b.len()];
+    let mut matches = 0usize;
+
+    for (i, ca) in a.iter().enumerate() {
+        let start = i.saturating_sub(window);
+        let end = (i + window + 1).min(b.len());
+
+        for j in start..end {
+            if !b_matches[j] && b[j] == *ca {
+                a_matches[i] = true;
+                b_matches[j] = true;
+                matches += 1;
+                break;
+            }
+        }
+    }
+
+    if matches == 0 {
+        return 0.0;
+    }
+
+    let mut transpositions = 0usize;
+    let mut k = 0;
+
+    for (i, ca) in a.iter().enumerate() {
+        if !a_matches[i] {
+            continue;
+        }
+
+        while !b_matches[k] {
+            k += 1;
+        }
+
+        if *ca != b[k] {
+            transpositions += 1;
+        }
+
+        k += 1;
+    }
+
+    let m = matches as f64;
+
+    (m / a.len() as f64 + m / b.len() as f64 + (m - transpositions as f64 / 2.0) / m) / 3.0
+}
+
+pub fn jaro_winkler(a: &str, b: &str) -> f64 {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+
+    let similarity = jaro(&a, &b);
+
+    let prefix = a
+        .iter()
+        .zip(b.iter())
+        .take(4)
+        .take_while(|(ca, cb)| ca == cb)
+        .count();
+
+    similarity + prefix as f64 * 0.1 * (1.0 - similarity)
+}
+
+fn char_ngrams(string: &str, n: usize) -> HashSet<Vec<char>> {
+    let chars: Vec<char> = string.chars().collect();
+
+    if chars.is_empty() {
+        return HashSet::new();
+    }
+
+    // NOTE: strings shorter than n are considered as a single gram
+    if chars.len() <= n {
+        return HashSet::from([chars]);
+    }
+
+    chars.windows(n).map(|gram| gram.to_vec()).collect()
+}
+
+pub fn jaccard(a: &str, b: &str, n: usize) -> f64 {
+    let a = char_ngrams(a, n);
+    let b = char_ngrams(b, n);
+
+    if a.is_empty() && b.is_empty() {
+        return 1.0;
+    }
+
+    let intersection = a.intersection(&b).count();
+
+    intersection as f64 / (a.len() + b.len() - intersection) as f64
+}
+
+pub fn dice(a: &str, b: &str, n: usize) -> f64 {
+    let a = char_ngrams(a, n);
+    let b = char_ngrams(b, n);
+
+    if a.is_empty() && b.is_empty() {
+        return 1.0;
+    }
+
+    let intersection = a.intersection(&b).count();
+
+    2.0 * intersection as f64 / (a.len() + b.len()) as f64
+}
+
+pub fn longest_common_substring(a: &str, b: &str) -> String {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+
+    let mut row = vec![0usize; b.len() + 1];
+    let mut best_len = 0;
+    let mut best_end = 0;
+
+    for i in 1..=a.len() {
+        let mut diagonal = 0;
+
+        for j in 1..=b.len() {
+            let above = row[j];
+
+            row[j] = if a[i - 1] == b[j - 1] {
+                diagonal + 1
+            } else {
+                0
+            };
+
+            if row[j] > best_len {
+                best_len = row[j];
+                best_end = i;
+            }
+
+            diagonal = above;
+        }
+    }
+
+    a[best_end - best_len..best_end].iter().collect()
+}
+
+fn ascii_letters(string: &str) -> Vec<u8> {
+    unidecode(string)
+        .bytes()
+        .filter(u8::is_ascii_alphabetic)
+        .map(|c| c.to_ascii_uppercase())
+        .collect()
+}
+
+fn is_vowel(c: u8) -> bool {
+    matches!(c, b'A' | b'E' | b'I' | b'O' | b'U')
+}
+
+fn soundex_code(c: u8) -> u8 {
+    match c {
+        b'B' | b'F' | b'P' | b'V' => b'1',
+        b'C' | b'G' | b'J' | b'K' | b'Q' | b'S' | b'X' | b'Z' => b'2',
+        b'D' | b'T' => b'3',
+        b'L' => b'4',
+        b'M' | b'N' => b'5',
+        b'R' => b'6',
+        _ => b'0',
+    }
+}
+
+pub fn soundex(string: &str) -> String {
+    let letters = ascii_letters(string);
+
+    let first = match letters.first() {
+        None => return String::new(),
+        Some(c) => *c,
+    };
+
+    let mut key = vec![first];
+    let mut last_code = soundex_code(first);
+
+    for c in letters[1..].iter().copied() {
+        let code = soundex_code(c);
+
+        if code != b'0' && code != last_code {
+            key.push(code);
+
+            if key.len() == 4 {
+                break;
+            }
+        }
+
+        // NOTE: H and W do not separate consonants having the same code
+        if c != b'H' && c != b'W' {
+            last_code = code;
+        }
+    }
+
+    key.resize(4, b'0');
+
+    String::from_utf8(key).unwrap()
+}
+
+pub fn metaphone(string: &str) -> String {
+    let mut word = ascii_letters(string);
+
+    if word.is_empty() {
+        return String::new();
+    }
+
+    match (word[0], word.get(1).copied()) {
+        (b'A', Some(b'E')) | (b'G' | b'K' | b'P', Some(b'N')) | (b'W', Some(b'R')) => {
+            word.remove(0);
+        }
+        (b'X', _) => {
+            word[0] = b'S';
+        }
+        (b'W', Some(b'H')) => {
+            word.remove(1);
+        }
+        _ => (),
+    };
+
+    let at = |i: usize| -> Option<u8> { word.get(i).copied() };
+    let is_front_vowel = |c: Option<u8>| matches!(c, Some(b'E' | b'I' | b'Y'));
+
+    let mut key = String::new();
+    let mut i = 0;
+
+    while i < word.len() {
+        let c = word[i];
+        let prev = if i > 0 { at(i - 1) } else { None };
+        let next = at(i + 1);
+        let next_next = at(i + 2);
+
+        // Skipping duplicate letters, except for C
+        if prev == Some(c) && c != b'C' {
+            i += 1;
+            continue;
+        }
+
+        match c {
+            b'A' | b'E' | b'I' | b'O' | b'U' => {
+                if i == 0 {
+                    key.push(c as char);
+                }
+            }
+            b'B' => {
+                if !(prev == Some(b'M') && next.is_none()) {
+                    key.push('B');
+                }
+            }
+            b'C' => {
+                if next == Some(b'I') && next_next == Some(b'A') {
+                    key.push('X');
+                } else if next == Some(b'H') {
+                    key.push(if prev == Some(b'S') { 'K' } else { 'X' });
+                    i += 1;
+                } else if is_front_vowel(next) {
+                    if prev != Some(b'S') {
+                        key.push('S');
+                    }
+                } else {
+                    key.push('K');
+                }
+            }
+            b'D' => {
+                if next == Some(b'G') && is_front_vowel(next_next) {
+                    key.push('J');
+                    i += 2;
+                } else {
+                    key.push('T');
+                }
+            }
+            b'G' => {
+                if next == Some(b'H') && !next_next.is_some_and(is_vowel) && next_next.is_some() {
+                    // Silent as in "night"
+                } else if next == Some(b'N')
+                    && (next_next.is_none()
+                        || (next_next == Some(b'E')
+                            && at(i + 3) == Some(b'D')
+                            && at(i + 4).is_none()))
+                {
+                    // Silent as in "sign" or "signed"
+                } else if is_front_vowel(next) && prev != Some(b'G') {
+                    key.push('J');
+                } else {
+                    key.push('K');
+                }
+            }
+            b'H' => {
+                let after_modifier = matches!(prev, Some(b'C' | b'G' | b'P' | b'S' | b'T'));
+                let after_vowel = prev.is_some_and(is_vowel);
+
+                if !after_modifier && (!after_vowel || next.is_some_and(is_vowel)) {
+                    key.push('H');
+                }
+            }
+            b'K' => {
+                if prev != Some(b'C') {
+                    key.push('K');
+                }
+            }
+            b'P' => {
+                key.push(if next == Some(b'H') { 'F' } else { 'P' });
+            }
+            b'Q' => key.push('K'),
+            b'S' => {
+                if next == Some(b'H') {
+                    key.push('X');
+                    i += 1;
+                } else if next == Some(b'I') && matches!(next_next, Some(b'O' | b'A')) {
+                    key.push('X');
+                } else {
+                    key.push('S');
+                }
+            }
+            b'T' => {
+                if next == Some(b'I') && matches!(next_next, Some(b'O' | b'A')) {
+                    key.push('X');
+                } else if next == Some(b'H') {
+                    key.push('0');
+                    i += 1;
+                } else if !(next == Some(b'C') && next_next == Some(b'H')) {
+                    key.push('T');
+                }
+            }
+            b'V' => key.push('F'),
+            b'W' | b'Y' => {
+                if next.is_some_and(is_vowel) {
+                    key.push(c as char);
+                }
+            }
+            b'X' => key.push_str("KS"),
+            b'Z' => key.push('S'),
+            _ => key.push(c as char),
+        }
+
+        i += 1;
+    }
+
+    key
+}
+
+fn replace_all(word: &mut Vec<u8>, pattern: &[u8], replacement: &[u8]) {
+    let mut i = 0;
+
+    while i + pattern.len() <= word.len() {
+        if &word[i..i + pattern.len()] == pattern {
+            word.splice(i..i + pattern.len(), replacement.iter().copied());
+            i += replacement.len();
+        } else {
+            i += 1;
+        }
+    }
+}
+
+// NOTE: this is the "Soundex2" algorithm, an adaptation of Soundex
+// tailored for French names.
+pub fn soundex_fr(string: &str) -> String {
+    let mut word = ascii_letters(string);
+
+    if word.is_empty() {
+        return String::new();
+    }
+
+    for (pattern, replacement) in [
+        (&b"GUI"[..], &b"KI"[..]),
+        (b"GUE", b"KE"),
+        (b"GA", b"KA"),
+        (b"GO", b"KO"),
+        (b"GU", b"K"),
+        (b"CA", b"KA"),
+        (b"CO", b"KO"),
+        (b"CU", b"KU"),
+        (b"Q", b"K"),
+        (b"CC", b"K"),
+        (b"CK", b"K"),
+    ] {
+        replace_all(&mut word, pattern, replacement);
+    }
+
+    for c in word[1..].iter_mut() {
+        if matches!(*c, b'E' | b'I' | b'O' | b'U') {
+            *c = b'A';
+        }
+    }
+
+    for (prefix, replacement) in [
+        (&b"MAC"[..], &b"MCC"[..]),
+        (b"ASA", b"AZA"),
+        (b"KN", b"NN"),
+        (b"PF", b"FF"),
+        (b"SCH", b"SSS"),
+        (b"PH", b"FF"),
+    ] {
+        if word.starts_with(prefix) {
+            word.splice(..prefix.len(), replacement.iter().copied());
+            break;
+        }
+    }
+
+    let mut filtered: Vec<u8> = Vec::with_capacity(word.len());
+
+    for (i, c) in word.iter().copied().enumerate() {
+        let prev = if i > 0 { Some(word[i - 1]) } else { None };
+
+        if c == b'H' && !matches!(prev, Some(b'C' | b'S')) {
+            continue;
+        }
+
+        if c == b'Y' && prev != Some(b'A') {
+            continue;
+        }
+
+        filtered.push(c);
+    }
+
+    let mut word = filtered;
+
+    if word.len() > 1 && matches!(word.last(), Some(b'A' | b'D' | b'T' | b'S')) {
+        word.pop();
+    }
+
+    let mut key = String::new();
+
+    for (i, c) in word.iter().copied().enumerate() {
+        if i > 0 && c == b'A' {
+            continue;
+        }
+
+        if key.as_bytes().last() == Some(&c) {
+            continue;
+        }
+
+        key.push(c as char);
+
+        if key.len() == 4 {
+            break;
+        }
+    }
+
+    key
+}
+
+// Find the candidate closest to the given name, if close enough, so that
+// typos can be reported with a suggestion.
+pub fn did_you_mean<'a, I>(name: &str, candidates: I) -> Option<&'a str>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let max_distance = (name.chars().count() / 3).max(1);
+
+    candidates
+        .into_iter()
+        .map(|candidate| (damerau_levenshtein(name, candidate), candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_levenshtein() {
+        assert_eq!(levenshtein("", ""), 0);
+        assert_eq!(levenshtein("abc", ""), 3);
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+        assert_eq!(levenshtein("book", "back"), 2);
+        assert_eq!(levenshtein("été", "ete"), 2);
+    }
+
+    #[test]
+    fn test_damerau_levenshtein() {
+        assert_eq!(damerau_levenshtein("", "abc"), 3);
+        assert_eq!(damerau_levenshtein("ca", "ac"), 1);
+        assert_eq!(levenshtein("ca", "ac"), 2);
+        assert_eq!(damerau_levenshtein("kitten", "sitting"), 3);
+        assert_eq!(damerau_levenshtein("ca", "abc"), 3);
+    }
+
+    #[test]
+    fn test_did_you_mean() {
+        let candidates = ["lower", "upper", "len", "split"];
+
+        assert_eq!(did_you_mean("lowr", candidates), Some("lower"));
+        assert_eq!(did_you_mean("spilt", candidates), Some("split"));
+        assert_eq!(did_you_mean("xyz", candidates), None);
+    }
+
+    #[test]
+    fn test_jaro_winkler() {
+        assert_eq!(jaro_winkler("", ""), 1.0);
+        assert_eq!(jaro_winkler("abc", ""), 0.0);
+        assert_eq!(jaro_winkler("same", "same"), 1.0);
+        assert!((jaro_winkler("MARTHA", "MARHTA") - 0.9611).abs() < 1e-4);
+        assert!((jaro_winkler("DIXON", "DICKSONX") - 0.8133).abs() < 1e-4);
+
+        // Odd number of half-transpositions
+        let a: Vec<char> = "abcdef".chars().collect();
+        let b: Vec<char> = "abcefd".chars().collect();
+        assert!((jaro(&a, &b) - 0.9167).abs() < 1e-4);
+        assert!((jaro_winkler("abcdef", "abcefd") - 0.9417).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_jaccard_and_dice() {
+        assert_eq!(jaccard("", "", 2), 1.0);
+        assert_eq!(jaccard("night", "nacht", 2), 1.0 / 7.0);
+        assert_eq!(dice("night", "nacht", 2), 0.25);
+        assert_eq!(jaccard("ab", "ab", 3), 1.0);
+        assert_eq!(dice("abc", "xyz", 1), 0.0);
+    }
+
+    #[test]
+    fn test_longest_common_substring() {
+        assert_eq!(longest_common_substring("", "abc"), "");
+        assert_eq!(longest_common_substring("abcdef", "zcdez"), "cde");
+        assert_eq!(longest_common_substring("élégant", "éléphant"), "élé");
+    }
+
+    #[test]
+    fn test_soundex() {
+        assert_eq!(soundex(""), "");
+        assert_eq!(soundex("Robert"), "R163");
+        assert_eq!(soundex("Rupert"), "R163");
+        assert_eq!(soundex("Tymczak"), "T522");
+        assert_eq!(soundex("Pfister"), "P236");
+        assert_eq!(soundex("Ashcraft"), "A261");
+        assert_eq!(soundex("Lee"), "L000");
+    }
+
+    #[test]
+    fn test_metaphone() {
+        assert_eq!(metaphone(""), "");
+        assert_eq!(metaphone("Knight"), "NT");
+        assert_eq!(metaphone("Smith"), "SM0");
+        assert_eq!(metaphone("Schmidt"), "SKMTT");
+        assert_eq!(metaphone("Thompson"), "0MPSN");
+        assert_eq!(metaphone("Xavier"), "SFR");
+        assert_eq!(metaphone("Whistle"), "WSTL");
+    }
+
+    #[test]
+    fn test_soundex_fr() {
+        assert_eq!(soundex_fr(""), "");
+        assert_eq!(soundex_fr("Guillaume"), soundex_fr("Guilaume"));
+        assert_eq!(soundex_fr("Philippe"), "FLP");
+        assert_eq!(soundex_fr("Mac Donald"), "MCDN");
+        assert_eq!(soundex_fr("Schmitt"), "SMT");
+        assert_eq!(soundex_fr("Dupont"), soundex_fr("Dupond"));
+    }
+}
@@ -0,0 +1,562 @@
+// NOTE: helpers used by the geospatial functions. Geometries are exchanged
+// with the rest of moonblade as GeoJSON-like maps, e.g.
+// {"type": "Polygon", "coordinates": [[[x, y], ...], ...]}, so that they can
+// be produced once as constants (by parsing WKT or reading a GeoJSON file)
+// and then be reused when evaluating each row.
+// Coordinates are always given as (longitude, latitude) pairs, as per WKT
+// and GeoJSON conventions.
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use super::error::EvaluationError;
+use super::types::DynamicValue;
+
+// Mean earth radius in kilometers
+// Ref: https://en.wikipedia.org/wiki/Earth_radius#Mean_radius
+const EARTH_RADIUS: f64 = 6371.0088;
+
+const GEOHASH_ALPHABET: &[u8; 32] = b"0123456789bcdefghjkmnpqrstuvwxyz";
+
+pub fn haversine(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
+    let (lat1, lat2) = (lat1.to_radians(), lat2.to_radians());
+    let delta_lat = lat2 - lat1;
+    let delta_lon = (lon2 - lon1).to_radians();
+
+    let a =
+        (delta_lat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (delta_lon / 2.0).sin().powi(2);
+
+    2.0 * EARTH_RADIUS * a.sqrt().asin()
+}
+
+// Ref: https://en.wikipedia.org/wiki/Geohash
+pub fn geohash_encode(lat: f64, lon: f64, precision: usize) -> String {
+    let mut lat_range = (-90.0, 90.0);
+    let mut lon_range = (-180.0, 180.0);
+
+    let mut hash = String::with_capacity(precision);
+    let mut even_bit = true;
+
+    for _ in 0..precision {
+        let mut index = 0;
+
+        for _ in 0..5 {
+            let (range, value) = if even_bit {
+                (&mut lon_range, lon)
+            } else {
+                (&mut lat_range, lat)
+            };
+
+            let middle = (range.0 + range.1) / 2.0;
+
+            index <<= 1;
+
+            if value >= middle {
+                index |= 1;
+                range.0 = middle;
+            } else {
+                range.1 = middle;
+            }
+
+            even_bit = !even_bit;
+        }
+
+        hash.push(GEOHASH_ALPHABET[index] as char);
+    }
+
+    hash
+}
+
+// Return the center of the cell described by given geohash, as a
+// (lat, lon) pair.
+pub fn geohash_decode(hash: &str) -> Option<(f64, f64)> {
+    let mut lat_range = (-90.0, 90.0);
+    let mut lon_range = (-180.0, 180.0);
+
+    let mut even_bit = true;
+
+    for byte in hash.bytes() {
+        let index = GEOHASH_ALPHABET
+            .iter()
+            .position(|c| *c == byte.to_ascii_lowercase())?;
+
+        for shift in (0..5).rev() {
+            let range = if even_bit {
+                &mut lon_range
+            } else {
+                &mut lat_range
+            };
+
+            let middle = (range.0 + range.1) / 2.0;
+
+            if (index >> shift) & 1 == 1 {
+                range.0 = middle;
+            } else {
+                range.1 = middle;
+            }
+
+            even_bit = !even_bit;
+        }
+    }
+
+    Some((
+        (lat_range.0 + lat_range.1) / 2.0,
+        (lon_range.0 + lon_range.1) / 2.0,
+    ))
+}
+
+type Coord = [f64; 2];
+type Ring = Vec<Coord>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Geometry {
+    Point(Coord),
+    MultiPoint(Vec<Coord>),
+    LineString(Vec<Coord>),
+    MultiLineString(Vec<Vec<Coord>>),
+    Polygon(Vec<Ring>),
+    MultiPolygon(Vec<Vec<Ring>>),
+}
+
+// Even-odd rule, so that holes are naturally accounted for
+// Ref: https://wrfranklin.org/Research/Short_Notes/pnpoly.html
+fn polygon_contains(rings: &[Ring], x: f64, y: f64) -> bool {
+    let mut inside = false;
+
+    for ring in rings {
+        if ring.is_empty() {
+            continue;
+        }
+
+        let mut j = ring.len() - 1;
+
+        for i in 0..ring.len() {
+            let [xi, yi] = ring[i];
+            let [xj, yj] = ring[j];
+
+            if (yi > y) != (yj > y) && x < (xj - xi) * (y - yi) / (yj - yi) + xi {
+                inside = !inside;
+            }
+
+            j = i;
+        }
+    }
+
+    inside
+}
+
+impl Geometry {
+    fn type_name(&self) -> &str {
+        match self {
+            Self::Point(_) => "Point",
+            Self::MultiPoint(_) => "MultiPoint",
+            Self::LineString(_) => "LineString",
+            Self::MultiLineString(_) => "MultiLineString",
+            Self::Polygon(_) => "Polygon",
+            Self::MultiPolygon(_) => "MultiPolygon",
+        }
+    }
+
+    fn coords(&self) -> Box<dyn Iterator<Item = &Coord> + '_> {
+        match self {
+            Self::Point(coord) => Box::new(std::iter::once(coord)),
+            Self::MultiPoint(coords) | Self::LineString(coords) => Box::new(coords.iter()),
+            Self::MultiLineString(lines) | Self::Polygon(lines) => Box::new(lines.iter().flatten()),
+            Self::MultiPolygon(polygons) => Box::new(polygons.iter().flatten().flatten()),
+        }
+    }
+
+    // Return [min_x, min_y, max_x, max_y], as per GeoJSON bbox
+    pub fn bbox(&self) -> Option<[f64; 4]> {
+        self.coords().fold(None, |bbox, [x, y]| match bbox {
+            None => Some([*x, *y, *x, *y]),
+            Some([min_x, min_y, max_x, max_y]) => {
+                Some([min_x.min(*x), min_y.min(*y), max_x.max(*x), max_y.max(*y)])
+            }
+        })
+    }
+
+    pub fn contains(&self, x: f64, y: f64) -> Result<bool, EvaluationError> {
+        match self {
+            Self::Polygon(rings) => Ok(polygon_contains(rings, x, y)),
+            Self::MultiPolygon(polygons) => {
+                Ok(polygons.iter().any(|rings| polygon_contains(rings, x, y)))
+            }
+            _ => Err(EvaluationError::Custom(format!(
+                "cannot test whether a point lies within a {}",
+                self.type_name()
+            ))),
+        }
+    }
+
+    pub fn to_geojson(&self) -> DynamicValue {
+        fn coord(c: &Coord) -> DynamicValue {
+            DynamicValue::from(vec![DynamicValue::from(c[0]), DynamicValue::from(c[1])])
+        }
+
+        fn coords(c: &[Coord]) -> DynamicValue {
+            DynamicValue::from(c.iter().map(coord).collect::<Vec<_>>())
+        }
+
+        fn rings(r: &[Ring]) -> DynamicValue {
+            DynamicValue::from(r.iter().map(|c| coords(c)).collect::<Vec<_>>())
+        }
+
+        let coordinates = match self {
+            Self::Point(c) => coord(c),
+            Self::MultiPoint(c) | Self::LineString(c) => coords(c),
+            Self::MultiLineString(r) | Self::Polygon(r) => rings(r),
+            Self::MultiPolygon(p) => {
+                DynamicValue::from(p.iter().map(|r| rings(r)).collect::<Vec<_>>())
+            }
+        };
+
+        let mut map = HashMap::with_capacity(2);
+        map.insert("type".to_string(), DynamicValue::from(self.type_name()));
+        map.insert("coordinates".to_string(), coordinates);
+
+        DynamicValue::Map(Arc::new(map))
+    }
+
+    pub fn from_geojson(value: &DynamicValue) -> Result<Self, EvaluationError> {
+        fn coord(value: &DynamicValue) -> Result<Coord, EvaluationError> {
+            match value.try_as_list()?.as_slice() {
+                [x, y, ..] => Ok([x.try_as_f64()?, y.try_as_f64()?]),
+                _ => Err(EvaluationError::Custom(
+                    "geojson position should have at least 2 coordinates".to_string(),
+                )),
+            }
+        }
+
+        fn coords(value: &DynamicValue) -> Result<Vec<Coord>, EvaluationError> {
+            value.try_as_list()?.iter().map(coord).collect()
+        }
+
+        fn rings(value: &DynamicValue) -> Result<Vec<Ring>, EvaluationError> {
+            value.try_as_list()?.iter().map(coords).collect()
+        }
+
+        let map = value.try_as_map()?;
+
+        let get = |key: &str| {
+            map.get(key).ok_or_else(|| {
+                EvaluationError::Custom(format!("geojson object has no \"{}\" key", key))
+            })
+        };
+
+        let kind = get("type")?.try_as_str()?;
+
+        if kind == "Feature" {
+            return Self::from_geojson(get("geometry")?);
+        }
+
+        let coordinates = get("coordinates")?;
+
+        Ok(match kind.as_ref() {
+            "Point" => Self::Point(coord(coordinates)?),
+            "MultiPoint" => Self::MultiPoint(coords(coordinates)?),
+            "LineString" => Self::LineString(coords(coordinates)?),
+            "MultiLineString" => Self::MultiLineString(rings(coordinates)?),
+            "Polygon" => Self::Polygon(rings(coordinates)?),
+            "MultiPolygon" => Self::MultiPolygon(
+                coordinates
+                    .try_as_list()?
+                    .iter()
+                    .map(rings)
+                    .collect::<Result<_, _>>()?,
+            ),
+            _ => {
+                return Err(EvaluationError::Custom(format!(
+                    "unsupported geojson type \"{}\"",
+                    kind
+                )))
+            }
+        })
+    }
+
+    // NOTE: accepts either a GeoJSON map, or a string containing WKT or
+    // serialized GeoJSON.
+    pub fn from_value(value: &DynamicValue) -> Result<Self, EvaluationError> {
+        if let DynamicValue::Map(_) = value {
+            return Self::from_geojson(value);
+        }
+
+        let string = value.try_as_str()?;
+
+        if string.trim_start().starts_with('{') {
+            let parsed = serde_json::from_str::<DynamicValue>(&string)
+                .map_err(|_| EvaluationError::JSONParseError)?;
+
+            return Self::from_geojson(&parsed);
+        }
+
+        parse_wkt(&string).map_err(EvaluationError::Custom)
+    }
+}
+
+// WKT parsing
+// Ref: https://en.wikipedia.org/wiki/Well-known_text_representation_of_geometry
+#[derive(Debug)]
+enum WktNode {
+    Coord(Coord),
+    List(Vec<WktNode>),
+}
+
+struct WktParser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> WktParser<'a> {
+    fn skip_whitespace(&mut self) {
+        let rest = &self.input[self.pos..];
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_whitespace();
+        self.input[self.pos..].chars().next()
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), String> {
+        if self.peek() != Some(c) {
+            return Err(format!(
+                "invalid wkt: expected \"{}\" at position {}",
+                c, self.pos
+            ));
+        }
+
+        self.pos += 1;
+
+        Ok(())
+    }
+
+    fn word(&mut self) -> &'a str {
+        self.skip_whitespace();
+
+        let rest = &self.input[self.pos..];
+        let end = rest
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(rest.len());
+
+        self.pos += end;
+
+        &rest[..end]
+    }
+
+    fn coord(&mut self) -> Result<Coord, String> {
+        self.skip_whitespace();
+
+        let rest = &self.input[self.pos..];
+        let end = rest.find([',', ')']).unwrap_or(rest.len());
+
+        self.pos += end;
+
+        let numbers = rest[..end]
+            .split_whitespace()
+            .map(|n| n.parse::<f64>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| format!("invalid wkt coordinates \"{}\"", rest[..end].trim()))?;
+
+        // NOTE: z & m values are ignored
+        match numbers.as_slice() {
+            [x, y, ..] => Ok([*x, *y]),
+            _ => Err(format!(
+                "invalid wkt coordinates \"{}\"",
+                rest[..end].trim()
+            )),
+        }
+    }
+
+    fn list(&mut self) -> Result<Vec<WktNode>, String> {
+        self.expect('(')?;
+
+        let mut nodes = Vec::new();
+
+        loop {
+            if self.peek() == Some('(') {
+                nodes.push(WktNode::List(self.list()?));
+            } else {
+                nodes.push(WktNode::Coord(self.coord()?));
+            }
+
+            if self.peek() == Some(',') {
+                self.pos += 1;
+            } else {
+                break;
+            }
+        }
+
+        self.expect(')')?;
+
+        Ok(nodes)
+    }
+}
+
+impl WktNode {
+    fn into_coord(self) -> Result<Coord, String> {
+        match self {
+            Self::Coord(coord) => Ok(coord),
+            Self::List(mut nodes) if nodes.len() == 1 => nodes.pop().unwrap().into_coord(),
+            _ => Err("invalid wkt: expected a single coordinate".to_string()),
+        }
+    }
+
+    fn into_list(self) -> Result<Vec<WktNode>, String> {
+        match self {
+            Self::List(nodes) => Ok(nodes),
+            Self::Coord(_) => Err("invalid wkt: unexpected coordinate".to_string()),
+        }
+    }
+}
+
+fn wkt_coords(nodes: Vec<WktNode>) -> Result<Vec<Coord>, String> {
+    nodes.into_iter().map(WktNode::into_coord).collect()
+}
+
+fn wkt_rings(nodes: Vec<WktNode>) -> Result<Vec<Ring>, String> {
+    nodes
+        .into_iter()
+        .map(|node| wkt_coords(node.into_list()?))
+        .collect()
+}
+
+pub fn parse_wkt(string: &str) -> Result<Geometry, String> {
+    let mut parser = WktParser {
+        input: string,
+        pos: 0,
+    };
+
+    let kind = parser.word().to_ascii_uppercase();
+
+    // Dimension qualifier
+    if matches!(parser.peek(), Some(c) if c.is_ascii_alphabetic()) {
+        let qualifier = parser.word().to_ascii_uppercase();
+
+        if !matches!(qualifier.as_str(), "Z" | "M" | "ZM") {
+            return Err(format!("invalid wkt dimension \"{}\"", qualifier));
+        }
+    }
+
+    let nodes = parser.list()?;
+
+    if parser.peek().is_some() {
+        return Err(format!(
+            "invalid wkt: unexpected trailing characters at position {}",
+            parser.pos
+        ));
+    }
+
+    Ok(match kind.as_str() {
+        "POINT" => match wkt_coords(nodes)?.as_slice() {
+            [coord] => Geometry::Point(*coord),
+            _ => return Err("invalid wkt: POINT should have a single coordinate".to_string()),
+        },
+        "MULTIPOINT" => Geometry::MultiPoint(wkt_coords(nodes)?),
+        "LINESTRING" => Geometry::LineString(wkt_coords(nodes)?),
+        "MULTILINESTRING" => Geometry::MultiLineString(wkt_rings(nodes)?),
+        "POLYGON" => Geometry::Polygon(wkt_rings(nodes)?),
+        "MULTIPOLYGON" => Geometry::MultiPolygon(
+            nodes
+                .into_iter()
+                .map(|node| wkt_rings(node.into_list()?))
+                .collect::<Result<_, _>>()?,
+        ),
+        "" => return Err("invalid wkt: missing geometry type".to_string()),
+        _ => return Err(format!("unsupported wkt geometry type \"{}\"", kind)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_haversine() {
+        let paris = (48.8566, 2.3522);
+        let london = (51.5074, -0.1278);
+
+        let distance = haversine(paris.0, paris.1, london.0, london.1);
+
+        assert!((distance - 343.56).abs() < 0.1);
+        assert_eq!(haversine(paris.0, paris.1, paris.0, paris.1), 0.0);
+    }
+
+    #[test]
+    fn test_geohash() {
+        assert_eq!(geohash_encode(57.64911, 10.40744, 11), "u4pruydqqvj");
+        assert_eq!(geohash_encode(48.8566, 2.3522, 5), "u09tv");
+
+        let (lat, lon) = geohash_decode("ezs42").unwrap();
+        assert!((lat - 42.605).abs() < 0.001);
+        assert!((lon - -5.603).abs() < 0.001);
+
+        assert_eq!(geohash_decode("ezs4a"), None);
+    }
+
+    #[test]
+    fn test_parse_wkt() {
+        assert_eq!(
+            parse_wkt("POINT (30 10)"),
+            Ok(Geometry::Point([30.0, 10.0]))
+        );
+        assert_eq!(
+            parse_wkt("point z(30 10 5)"),
+            Ok(Geometry::Point([30.0, 10.0]))
+        );
+        assert_eq!(
+            parse_wkt("MULTIPOINT ((10 40), (40 30))"),
+            parse_wkt("MULTIPOINT (10 40, 40 30)")
+        );
+        assert_eq!(
+            parse_wkt("LINESTRING (30 10, 10 30, 40 40)"),
+            Ok(Geometry::LineString(vec![
+                [30.0, 10.0],
+                [10.0, 30.0],
+                [40.0, 40.0]
+            ]))
+        );
+        assert_eq!(
+            parse_wkt("POLYGON ((0 0, 4 0, 4 4, 0 4, 0 0), (1 1, 2 1, 2 2, 1 1))"),
+            Ok(Geometry::Polygon(vec![
+                vec![[0.0, 0.0], [4.0, 0.0], [4.0, 4.0], [0.0, 4.0], [0.0, 0.0]],
+                vec![[1.0, 1.0], [2.0, 1.0], [2.0, 2.0], [1.0, 1.0]]
+            ]))
+        );
+        assert_eq!(
+            parse_wkt("MULTIPOLYGON (((0 0, 1 0, 1 1, 0 0)), ((5 5, 6 5, 6 6, 5 5)))")
+                .unwrap()
+                .bbox(),
+            Some([0.0, 0.0, 6.0, 6.0])
+        );
+        assert!(parse_wkt("POLYGON ((0 0, 1 0").is_err());
+        assert!(parse_wkt("CIRCLE (0 0)").is_err());
+        assert!(parse_wkt("POINT (a b)").is_err());
+    }
+
+    #[test]
+    fn test_contains() {
+        let polygon =
+            parse_wkt("POLYGON ((0 0, 4 0, 4 4, 0 4, 0 0), (1 1, 2 1, 2 2, 1 2, 1 1))").unwrap();
+
+        assert_eq!(polygon.contains(3.0, 3.0), Ok(true));
+        assert_eq!(polygon.contains(1.5, 1.5), Ok(false));
+        assert_eq!(polygon.contains(5.0, 1.0), Ok(false));
+        assert!(Geometry::Point([0.0, 0.0]).contains(0.0, 0.0).is_err());
+    }
+
+    #[test]
+    fn test_geojson_roundtrip() {
+        let polygon = parse_wkt("MULTIPOLYGON (((0 0, 1 0, 1 1, 0 0)))").unwrap();
+
+        assert_eq!(
+            Geometry::from_geojson(&polygon.to_geojson()),
+            Ok(polygon.clone())
+        );
+
+        let parsed = Geometry::from_value(&DynamicValue::from(
+            r#"{"type": "Feature", "geometry": {"type": "MultiPolygon", "coordinates": [[[[0, 0], [1, 0], [1, 1], [0, 0]]]]}}"#,
+        ));
+
+        assert_eq!(parsed, Ok(polygon));
+    }
+}
@@ -0,0 +1,706 @@
+// NOTE: helpers used by the html-related functions. This is not a full
+// HTML5 parser, but a lenient tokenizer that is good enough to clean up
+// scraped fragments found in cells: it knows about comments, doctypes,
+// quoted attributes and raw text elements such as <script> or <style>.
+use std::borrow::Cow;
+
+// HTML 4 named character references (plus &apos;), which cover the
+// overwhelming majority of entities found in the wild.
+static ENTITIES: [(&str, char); 253] = [
+    ("AElig", '\u{C6}'),
+    ("Aacute", '\u{C1}'),
+    ("Acirc", '\u{C2}'),
+    ("Agrave", '\u{C0}'),
+    ("Alpha", '\u{391}'),
+    ("Aring", '\u{C5}'),
+    ("Atilde", '\u{C3}'),
+    ("Auml", '\u{C4}'),
+    ("Beta", '\u{392}'),
+    ("Ccedil", '\u{C7}'),
+    ("Chi", '\u{3A7}'),
+    ("Dagger", '\u{2021}'),
+    ("Delta", '\u{394}'),
+    ("ETH", '\u{D0}'),
+    ("Eacute", '\u{C9}'),
+    ("Ecirc", '\u{CA}'),
+    ("Egrave", '\u{C8}'),
+    ("Epsilon", '\u{395}'),
+    ("Eta", '\u{397}'),
+    ("Euml", '\u{CB}'),
+    ("Gamma", '\u{393}'),
+    ("Iacute", '\u{CD}'),
+    ("Icirc", '\u{CE}'),
+    ("Igrave", '\u{CC}'),
+    ("Iota", '\u{399}'),
+    ("Iuml", '\u{CF}'),
+    ("Kappa", '\u{39A}'),
+    ("Lambda", '\u{39B}'),
+    ("Mu", '\u{39C}'),
+    ("Ntilde", '\u{D1}'),
+    ("Nu", '\u{39D}'),
+    ("OElig", '\u{152}'),
+    ("Oacute", '\u{D3}'),
+    ("Ocirc", '\u{D4}'),
+    ("Ograve", '\u{D2}'),
+    ("Omega", '\u{3A9}'),
+    ("Omicron", '\u{39F}'),
+    ("Oslash", '\u{D8}'),
+    ("Otilde", '\u{D5}'),
+    ("Ouml", '\u{D6}'),
+    ("Phi", '\u{3A6}'),
+    ("Pi", '\u{3A0}'),
+    ("Prime", '\u{2033}'),
+    ("Psi", '\u{3A8}'),
+    ("Rho", '\u{3A1}'),
+    ("Scaron", '\u{160}'),
+    ("Sigma", '\u{3A3}'),
+    ("THORN", '\u{DE}'),
+    ("Tau", '\u{3A4}'),
+    ("Theta", '\u{398}'),
+    ("Uacute", '\u{DA}'),
+    ("Ucirc", '\u{DB}'),
+    ("Ugrave", '\u{D9}'),
+    ("Upsilon", '\u{3A5}'),
+    ("Uuml", '\u{DC}'),
+    ("Xi", '\u{39E}'),
+    ("Yacute", '\u{DD}'),
+    ("Yuml", '\u{178}'),
+    ("Zeta", '\u{396}'),
+    ("aacute", '\u{E1}'),
+    ("acirc", '\u{E2}'),
+    ("acute", '\u{B4}'),
+    ("aelig", '\u{E6}'),
+    ("agrave", '\u{E0}'),
+    ("alefsym", '\u{2135}'),
+    ("alpha", '\u{3B1}'),
+    ("amp", '\u{26}'),
+    ("and", '\u{2227}'),
+    ("ang", '\u{2220}'),
+    ("apos", '\u{27}'),
+    ("aring", '\u{E5}'),
+    ("asymp", '\u{2248}'),
+    ("atilde", '\u{E3}'),
+    ("auml", '\u{E4}'),
+    ("bdquo", '\u{201E}'),
+    ("beta", '\u{3B2}'),
+    ("brvbar", '\u{A6}'),
+    ("bull", '\u{2022}'),
+    ("cap", '\u{2229}'),
+    ("ccedil", '\u{E7}'),
+    ("cedil", '\u{B8}'),
+    ("cent", '\u{A2}'),
+    ("chi", '\u{3C7}'),
+    ("circ", '\u{2C6}'),
+    ("clubs", '\u{2663}'),
+    ("cong", '\u{2245}'),
+    ("copy", '\u{A9}'),
+    ("crarr", '\u{21B5}'),
+    ("cup", '\u{222A}'),
+    ("curren", '\u{A4}'),
+    ("dArr", '\u{21D3}'),
+    ("dagger", '\u{2020}'),
+    ("darr", '\u{2193}'),
+    ("deg", '\u{B0}'),
+    ("delta", '\u{3B4}'),
+    ("diams", '\u{2666}'),
+    ("divide", '\u{F7}'),
+    ("eacute", '\u{E9}'),
+    ("ecirc", '\u{EA}'),
+    ("egrave", '\u{E8}'),
+    ("empty", '\u{2205}'),
+    ("emsp", '\u{2003}'),
+    ("ensp", '\u{2002}'),
+    ("epsilon", '\u{3B5}'),
+    ("equiv", '\u{2261}'),
+    ("eta", '\u{3B7}'),
+    ("eth", '\u{F0}'),
+    ("euml", '\u{EB}'),
+    ("euro", '\u{20AC}'),
+    ("exist", '\u{2203}'),
+    ("fnof", '\u{192}'),
+    ("forall", '\u{2200}'),
+    ("frac12", '\u{BD}'),
+    ("frac14", '\u{BC}'),
+    ("frac34", '\u{BE}'),
+    ("frasl", '\u{2044}'),
+    ("gamma", '\u{3B3}'),
+    ("ge", '\u{2265}'),
+    ("gt", '\u{3E}'),
+    ("hArr", '\u{21D4}'),
+    ("harr", '\u{2194}'),
+    ("hearts", '\u{2665}'),
+    ("hellip", '\u{2026}'),
+    ("iacute", '\u{ED}'),
+    ("icirc", '\u{EE}'),
+    ("iexcl", '\u{A1}'),
+    ("igrave", '\u{EC}'),
+    ("image", '\u{2111}'),
+    ("infin", '\u{221E}'),
+    ("int", '\u{222B}'),
+    ("iota", '\u{3B9}'),
+    ("iquest", '\u{BF}'),
+    ("isin", '\u{2208}'),
+    ("iuml", '\u{EF}'),
+    ("kappa", '\u{3BA}'),
+    ("lArr", '\u{21D0}'),
+    ("lambda", '\u{3BB}'),
+    ("lang", '\u{2329}'),
+    ("laquo", '\u{AB}'),
+    ("larr", '\u{2190}'),
+    ("lceil", '\u{2308}'),
+    ("ldquo", '\u{201C}'),
+    ("le", '\u{2264}'),
+    ("lfloor", '\u{230A}'),
+    ("lowast", '\u{2217}'),
+    ("loz", '\u{25CA}'),
+    ("lrm", '\u{200E}'),
+    ("lsaquo", '\u{2039}'),
+    ("lsquo", '\u{2018}'),
+    ("lt", '\u{3C}'),
+    ("macr", '\u{AF}'),
+    ("mdash", '\u{2014}'),
+    ("micro", '\u{B5}'),
+    ("middot", '\u{B7}'),
+    ("minus", '\u{2212}'),
+    ("mu", '\u{3BC}'),
+    ("nabla", '\u{2207}'),
+    ("nbsp", '\u{A0}'),
+    ("ndash", '\u{2013}'),
+    ("ne", '\u{2260}'),
+    ("ni", '\u{220B}'),
+    ("not", '\u{AC}'),
+    ("notin", '\u{2209}'),
+    ("nsub", '\u{2284}'),
+    ("ntilde", '\u{F1}'),
+    ("nu", '\u{3BD}'),
+    ("oacute", '\u{F3}'),
+    ("ocirc", '\u{F4}'),
+    ("oelig", '\u{153}'),
+    ("ograve", '\u{F2}'),
+    ("oline", '\u{203E}'),
+    ("omega", '\u{3C9}'),
+    ("omicron", '\u{3BF}'),
+    ("oplus", '\u{2295}'),
+    ("or", '\u{2228}'),
+    ("ordf", '\u{AA}'),
+    ("ordm", '\u{BA}'),
+    ("oslash", '\u{F8}'),
+    ("otilde", '\u{F5}'),
+    ("otimes", '\u{2297}'),
+    ("ouml", '\u{F6}'),
+    ("para", '\u{B6}'),
+    ("part", '\u{2202}'),
+    ("permil", '\u{2030}'),
+    ("perp", '\u{22A5}'),
+    ("phi", '\u{3C6}'),
+    ("pi", '\u{3C0}'),
+    ("piv", '\u{3D6}'),
+    ("plusmn", '\u{B1}'),
+    ("pound", '\u{A3}'),
+    ("prime", '\u{2032}'),
+    ("prod", '\u{220F}'),
+    ("prop", '\u{221D}'),
+    ("psi", '\u{3C8}'),
+    ("quot", '\u{22}'),
+    ("rArr", '\u{21D2}'),
+    ("radic", '\u{221A}'),
+    ("rang", '\u{232A}'),
+    ("raquo", '\u{BB}'),
+    ("rarr", '\u{2192}'),
+    ("rceil", '\u{2309}'),
+    ("rdquo", '\u{201D}'),
+    ("real", '\u{211C}'),
+    ("reg", '\u{AE}'),
+    ("rfloor", '\u{230B}'),
+    ("rho", '\u{3C1}'),
+    ("rlm", '\u{200F}'),
+    ("rsaquo", '\u{203A}'),
+    ("rsquo", '\u{2019}'),
+    ("sbquo", '\u{201A}'),
+    ("scaron", '\u{161}'),
+    ("sdot", '\u{22C5}'),
+    ("sect", '\u{A7}'),
+    ("shy", '\u{AD}'),
+    ("sigma", '\u{3C3}'),
+    ("sigmaf", '\u{3C2}'),
+    ("sim", '\u{223C}'),
+    ("spades", '\u{2660}'),
+    ("sub", '\u{2282}'),
+    ("sube", '\u{2286}'),
+    ("sum", '\u{2211}'),
+    ("sup", '\u{2283}'),
+    ("sup1", '\u{B9}'),
+    ("sup2", '\u{B2}'),
+    ("sup3", '\u{B3}'),
+    ("supe", '\u{2287}'),
+    ("szlig", '\u{DF}'),
+    ("tau", '\u{3C4}'),
+    ("there4", '\u{2234}'),
+    ("theta", '\u{3B8}'),
+    ("thetasym", '\u{3D1}'),
+    ("thinsp", '\u{2009}'),
+    ("thorn", '\u{FE}'),
+    ("tilde", '\u{2DC}'),
+    ("times", '\u{D7}'),
+    ("trade", '\u{2122}'),
+    ("uArr", '\u{21D1}'),
+    ("uacute", '\u{FA}'),
+    ("uarr", '\u{2191}'),
+    ("ucirc", '\u{FB}'),
+    ("ugrave", '\u{F9}'),
+    ("uml", '\u{A8}'),
+    ("upsih", '\u{3D2}'),
+    ("upsilon", '\u{3C5}'),
+    ("uuml", '\u{FC}'),
+    ("weierp", '\u{2118}'),
+    ("xi", '\u{3BE}'),
+    ("yacute", '\u{FD}'),
+    ("yen", '\u{A5}'),
+    ("yuml", '\u{FF}'),
+    ("zeta", '\u{3B6}'),
+    ("zwj", '\u{200D}'),
+    ("zwnj", '\u{200C}'),
+];
+
+// Elements whose content is not rendered as text
+static INVISIBLE_ELEMENTS: [&str; 6] = ["head", "noscript", "script", "style", "svg", "template"];
+
+// Elements whose content is not parsed as HTML
+static RAW_TEXT_ELEMENTS: [&str; 4] = ["script", "style", "textarea", "title"];
+
+static BLOCK_ELEMENTS: [&str; 30] = [
+    "address",
+    "article",
+    "aside",
+    "blockquote",
+    "br",
+    "dd",
+    "div",
+    "dl",
+    "dt",
+    "figcaption",
+    "figure",
+    "footer",
+    "form",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "hr",
+    "li",
+    "main",
+    "nav",
+    "ol",
+    "p",
+    "pre",
+    "section",
+    "table",
+    "tr",
+];
+
+fn decode_numeric_reference(digits: &str, radix: u32) -> char {
+    u32::from_str_radix(digits, radix)
+        .ok()
+        .and_then(char::from_u32)
+        .filter(|c| *c != '\0')
+        .unwrap_or(char::REPLACEMENT_CHARACTER)
+}
+
+pub fn unescape(string: &str) -> Cow<'_, str> {
+    if !string.contains('&') {
+        return Cow::Borrowed(string);
+    }
+
+    let mut unescaped = String::with_capacity(string.len());
+    let mut rest = string;
+
+    while let Some(i) = rest.find('&') {
+        unescaped.push_str(&rest[..i]);
+        rest = &rest[i..];
+
+        let end = rest[1..]
+            .find(|c: char| !c.is_ascii_alphanumeric() && c != '#')
+            .map(|j| j + 1)
+            .unwrap_or(rest.len());
+
+        let name = &rest[1..end];
+        let terminated = rest[end..].starts_with(';');
+
+        let decoded = if let Some(digits) = name.strip_prefix("#x").or(name.strip_prefix("#X")) {
+            (!digits.is_empty() && digits.bytes().all(|b| b.is_ascii_hexdigit()))
+                .then(|| decode_numeric_reference(digits, 16))
+        } else if let Some(digits) = name.strip_prefix('#') {
+            (!digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit()))
+                .then(|| decode_numeric_reference(digits, 10))
+        } else if terminated {
+            ENTITIES
+                .binary_search_by_key(&name, |(k, _)| k)
+                .ok()
+                .map(|i| ENTITIES[i].1)
+        } else {
+            None
+        };
+
+        match decoded {
+            Some(c) => {
+                unescaped.push(c);
+                rest = &rest[end + terminated as usize..];
+            }
+            None => {
+                unescaped.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    unescaped.push_str(rest);
+
+    Cow::Owned(unescaped)
+}
+
+pub fn escape(string: &str) -> Cow<'_, str> {
+    if !string.contains(['&', '<', '>', '"', '\'']) {
+        return Cow::Borrowed(string);
+    }
+
+    let mut escaped = String::with_capacity(string.len() + 16);
+
+    for c in string.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+
+    Cow::Owned(escaped)
+}
+
+#[derive(Debug, PartialEq)]
+enum Token<'a> {
+    Text(&'a str),
+    Tag {
+        name: String,
+        closing: bool,
+        attributes: &'a str,
+    },
+}
+
+struct Tokenizer<'a> {
+    html: &'a str,
+    pos: usize,
+    raw_text_element: Option<String>,
+}
+
+impl<'a> Tokenizer<'a> {
+    fn new(html: &'a str) -> Self {
+        Self {
+            html,
+            pos: 0,
+            raw_text_element: None,
+        }
+    }
+
+    fn skip_until(&mut self, pattern: &str) {
+        self.pos = match self.html[self.pos..].find(pattern) {
+            Some(i) => self.pos + i + pattern.len(),
+            None => self.html.len(),
+        };
+    }
+
+    // NOTE: finds the end of the tag, taking quoted attribute values into account
+    fn tag_end(&self, start: usize) -> usize {
+        let mut quote: Option<u8> = None;
+
+        for (i, byte) in self.html.as_bytes()[start..].iter().enumerate() {
+            match (quote, byte) {
+                (None, b'>') => return start + i,
+                (None, b'"' | b'\'') => quote = Some(*byte),
+                (Some(q), b) if q == *b => quote = None,
+                _ => (),
+            }
+        }
+
+        self.html.len()
+    }
+}
+
+impl<'a> Iterator for Tokenizer<'a> {
+    type Item = Token<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let html = self.html;
+
+        loop {
+            if self.pos >= html.len() {
+                return None;
+            }
+
+            let rest = &html[self.pos..];
+
+            if let Some(element) = self.raw_text_element.take() {
+                let end = rest
+                    .to_ascii_lowercase()
+                    .find(&format!("</{}", element))
+                    .unwrap_or(rest.len());
+
+                self.pos += end;
+
+                if end > 0 {
+                    return Some(Token::Text(&rest[..end]));
+                }
+
+                continue;
+            }
+
+            let bytes = rest.as_bytes();
+
+            if bytes[0] != b'<' {
+                let end = rest[1..].find('<').map(|i| i + 1).unwrap_or(rest.len());
+                self.pos += end;
+
+                return Some(Token::Text(&rest[..end]));
+            }
+
+            if rest.starts_with("<!--") {
+                self.pos += 4;
+                self.skip_until("-->");
+                continue;
+            }
+
+            if rest.starts_with("<!") || rest.starts_with("<?") {
+                self.skip_until(">");
+                continue;
+            }
+
+            let closing = bytes.get(1) == Some(&b'/');
+            let name_start = 1 + closing as usize;
+
+            if !bytes
+                .get(name_start)
+                .is_some_and(|b| b.is_ascii_alphabetic())
+            {
+                // NOTE: a lone "<" is just text
+                let end = rest[1..].find('<').map(|i| i + 1).unwrap_or(rest.len());
+                self.pos += end;
+
+                return Some(Token::Text(&rest[..end]));
+            }
+
+            let name_end = rest[name_start..]
+                .find(|c: char| !c.is_ascii_alphanumeric() && c != '-')
+                .map(|i| i + name_start)
+                .unwrap_or(rest.len());
+
+            let name = rest[name_start..name_end].to_ascii_lowercase();
+            let end = self.tag_end(self.pos + name_end) - self.pos;
+            let attributes = rest[name_end..end].trim_end_matches('/');
+
+            self.pos = (self.pos + end + 1).min(html.len());
+
+            if !closing && RAW_TEXT_ELEMENTS.contains(&name.as_str()) {
+                self.raw_text_element = Some(name.clone());
+            }
+
+            return Some(Token::Tag {
+                name,
+                closing,
+                attributes,
+            });
+        }
+    }
+}
+
+fn parse_attributes(string: &str) -> Vec<(String, &str)> {
+    let mut attributes = Vec::new();
+    let mut rest = string.trim_start();
+
+    while !rest.is_empty() {
+        let name_end = rest
+            .find(|c: char| c.is_whitespace() || c == '=')
+            .unwrap_or(rest.len());
+
+        let name = rest[..name_end].to_ascii_lowercase();
+        rest = rest[name_end..].trim_start();
+
+        let mut value = "";
+
+        if let Some(after_equal) = rest.strip_prefix('=') {
+            rest = after_equal.trim_start();
+
+            match rest.chars().next() {
+                Some(quote @ ('"' | '\'')) => {
+                    let end = rest[1..].find(quote).map(|i| i + 1).unwrap_or(rest.len());
+                    value = &rest[1..end];
+                    rest = rest.get(end + 1..).unwrap_or("");
+                }
+                _ => {
+                    let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+                    value = &rest[..end];
+                    rest = &rest[end..];
+                }
+            }
+        }
+
+        if !name.is_empty() {
+            attributes.push((name, value));
+        }
+
+        rest = rest.trim_start();
+    }
+
+    attributes
+}
+
+pub fn strip_tags(html: &str) -> String {
+    let mut stripped = String::with_capacity(html.len());
+
+    for token in Tokenizer::new(html) {
+        if let Token::Text(text) = token {
+            stripped.push_str(text);
+        }
+    }
+
+    stripped
+}
+
+// NOTE: invisible elements are dropped, entities are decoded, block elements
+// are separated by line breaks and whitespace is collapsed.
+pub fn to_text(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut invisible_depth: usize = 0;
+
+    for token in Tokenizer::new(html) {
+        match token {
+            Token::Text(string) => {
+                if invisible_depth == 0 {
+                    // NOTE: source line breaks are not significant
+                    text.extend(
+                        unescape(string)
+                            .chars()
+                            .map(|c| if c == '\n' { ' ' } else { c }),
+                    );
+                }
+            }
+            Token::Tag { name, closing, .. } => {
+                if INVISIBLE_ELEMENTS.contains(&name.as_str()) {
+                    if closing {
+                        invisible_depth = invisible_depth.saturating_sub(1);
+                    } else {
+                        invisible_depth += 1;
+                    }
+                } else if BLOCK_ELEMENTS.contains(&name.as_str()) {
+                    text.push('\n');
+                } else if name == "td" || name == "th" {
+                    text.push(' ');
+                }
+            }
+        }
+    }
+
+    text.split('\n')
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+pub fn links(html: &str) -> Vec<String> {
+    let mut links = Vec::new();
+
+    for token in Tokenizer::new(html) {
+        if let Token::Tag {
+            name,
+            closing: false,
+            attributes,
+        } = token
+        {
+            if name != "a" && name != "area" {
+                continue;
+            }
+
+            if let Some((_, href)) = parse_attributes(attributes)
+                .into_iter()
+                .find(|(attr, _)| attr == "href")
+            {
+                let href = unescape(href.trim());
+
+                if !href.is_empty() {
+                    links.push(href.into_owned());
+                }
+            }
+        }
+    }
+
+    links
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unescape() {
+        assert_eq!(unescape("no entities"), "no entities");
+        assert_eq!(unescape("caf&eacute; &amp; &lt;b&gt;"), "café & <b>");
+        assert_eq!(unescape("&#233;&#xE9;&#XE9;"), "ééé");
+        assert_eq!(unescape("&#0; &#xZZ;"), "\u{FFFD} &#xZZ;");
+        assert_eq!(unescape("AT&T &unknown; &amp"), "AT&T &unknown; &amp");
+    }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape("plain"), "plain");
+        assert_eq!(
+            escape("<a href=\"x\">Tom & Jerry's</a>"),
+            "&lt;a href=&quot;x&quot;&gt;Tom &amp; Jerry&#39;s&lt;/a&gt;"
+        );
+    }
+
+    #[test]
+    fn test_strip_tags() {
+        assert_eq!(
+            strip_tags("<p class=\"a>b\">Hello <b>world</b>!</p><!-- comment -->"),
+            "Hello world!"
+        );
+        assert_eq!(strip_tags("1 < 2 <br/>and 3 > 2"), "1 < 2 and 3 > 2");
+        assert_eq!(
+            strip_tags("<script>if (a < b) { x = '</p>'; }</script>text"),
+            "if (a < b) { x = '</p>'; }text"
+        );
+    }
+
+    #[test]
+    fn test_to_text() {
+        let html = r#"<!DOCTYPE html>
+        <html>
+            <head><title>Title</title><style>p { color: red; }</style></head>
+            <body>
+                <h1>Header</h1>
+                <p>First&nbsp;paragraph,
+                   with   <em>emphasis</em>.</p>
+                <script>alert("<p>no</p>");</script>
+                <ul><li>one</li><li>two</li></ul>
+            </body>
+        </html>"#;
+
+        assert_eq!(
+            to_text(html),
+            "Header\nFirst paragraph, with emphasis.\none\ntwo"
+        );
+    }
+
+    #[test]
+    fn test_links() {
+        let html = r#"<a href="/one">1</a> <A HREF='two?a=1&amp;b=2'>2</A>
+            <a name="anchor">no</a> <link href="style.css"> <a href=three>3</a>"#;
+
+        assert_eq!(links(html), vec!["/one", "two?a=1&b=2", "three"]);
+    }
+}
@@ -4,14 +4,19 @@ use std::fmt;
 use csv::ByteRecord;
 use regex::RegexBuilder;
 
+use super::bytecode::Bytecode;
 use super::error::{ConcretizationError, EvaluationError, SpecifiedEvaluationError};
-use super::functions::{get_function, Function};
+use super::functions::{get_function, Function, FUNCTION_NAMES};
+use super::fuzzy::did_you_mean;
 use super::parser::{parse_expression, Expr, FunctionCall};
-use super::special_functions::{get_special_function, RuntimeFunction as SpecialFunction};
+use super::special_functions::{
+    get_special_function, RuntimeFunction as SpecialFunction, SPECIAL_FUNCTION_NAMES,
+};
 use super::types::{
     BoundArguments, ColumIndexationBy, DynamicValue, EvaluationResult, FunctionArguments,
     HeadersIndex, LambdaArguments, BOUND_ARGUMENTS_CAPACITY,
 };
+use super::typing;
 
 #[derive(Debug, Clone, Default)]
 pub struct EvaluationContext {
@@ -159,9 +164,9 @@ impl ConcreteExpr {
 
 #[derive(Clone, PartialEq)]
 pub struct ConcreteFunctionCall {
-    name: String,
-    function: Function,
-    args: Vec<ConcreteExpr>,
+    pub name: String,
+    pub function: Function,
+    pub args: Vec<ConcreteExpr>,
 }
 
 impl ConcreteFunctionCall {
@@ -243,9 +248,9 @@ impl fmt::Debug for ConcreteFunctionCall {
 
 #[derive(Clone, PartialEq)]
 pub struct ConcreteSpecialFunctionCall {
-    name: String,
+    pub name: String,
     function: SpecialFunction,
-    args: Vec<ConcreteExpr>,
+    pub args: Vec<ConcreteExpr>,
 }
 
 impl ConcreteSpecialFunctionCall {
@@ -270,7 +275,7 @@ impl ConcreteSpecialFunctionCall {
         )
     }
 
-    fn run(
+    pub fn run(
         &self,
         index: Option<usize>,
         record: &ByteRecord,
@@ -360,7 +365,20 @@ fn concretize_call(
     }
 
     Ok(match get_function(function_name) {
-        None => return Err(ConcretizationError::UnknownFunction(function_name.clone())),
+        None => {
+            let suggestion = did_you_mean(
+                function_name,
+                FUNCTION_NAMES
+                    .iter()
+                    .chain(SPECIAL_FUNCTION_NAMES.iter())
+                    .copied(),
+            );
+
+            return Err(ConcretizationError::UnknownFunction(
+                function_name.clone(),
+                suggestion.map(String::from),
+            ));
+        }
         Some((function, arguments)) => {
             arguments
                 .validate_arity(actual_arity)
@@ -488,17 +506,21 @@ pub fn eval_expression(
 #[derive(Clone, Debug)]
 pub struct Program {
     pub expr: ConcreteExpr,
+    bytecode: Bytecode,
     context: EvaluationContext,
 }
 
 impl Program {
     pub fn parse(code: &str, headers: &ByteRecord) -> Result<Self, ConcretizationError> {
         let expr = match parse_expression(code) {
-            Err(_) => return Err(ConcretizationError::ParseError(code.to_string())),
+            Err(err) => return Err(ConcretizationError::ParseError(err.render(code))),
             Ok(parsed_expr) => concretize_expression(parsed_expr, headers)?,
         };
 
+        typing::check(&expr)?;
+
         Ok(Self {
+            bytecode: Bytecode::compile(&expr),
             expr,
             context: EvaluationContext::new(headers),
         })
@@ -509,7 +531,7 @@ impl Program {
         index: usize,
         record: &ByteRecord,
     ) -> Result<DynamicValue, SpecifiedEvaluationError> {
-        eval_expression(&self.expr, Some(index), record, &self.context)
+        self.bytecode.run(Some(index), record, &self.context)
     }
 
     pub fn generate_key(
@@ -573,6 +595,31 @@ mod tests {
             .map_err(RunError::Evaluation)
     }
 
+    #[test]
+    fn test_unknown_function() {
+        assert_eq!(
+            concretize_code("lowr(name)"),
+            Err(ConcretizationError::UnknownFunction(
+                "lowr".to_string(),
+                Some("lower".to_string())
+            ))
+        );
+        assert_eq!(
+            concretize_code("mapp(split(name, ','), x => x)"),
+            Err(ConcretizationError::UnknownFunction(
+                "mapp".to_string(),
+                Some("map".to_string())
+            ))
+        );
+        assert_eq!(
+            concretize_code("qwertyuiop(name)"),
+            Err(ConcretizationError::UnknownFunction(
+                "qwertyuiop".to_string(),
+                None
+            ))
+        );
+    }
+
     #[test]
     fn test_static_evaluation() {
         assert_eq!(
@@ -664,6 +711,205 @@ mod tests {
         assert_eq!(eval_code("idiv(-4.5, 2)"), Ok(DynamicValue::Integer(-3)));
     }
 
+    #[test]
+    fn test_decimal() {
+        fn d(string: &str) -> DynamicValue {
+            DynamicValue::Decimal(string.parse().unwrap())
+        }
+
+        assert_eq!(eval_code("decimal('0.1') + decimal('0.2')"), Ok(d("0.3")));
+        assert_eq!(
+            eval_code("decimal(0.1) + 0.2"),
+            Ok(DynamicValue::Float(0.1 + 0.2))
+        );
+        assert_eq!(eval_code("decimal('19.99') * 3"), Ok(d("59.97")));
+        assert_eq!(eval_code("decimal('10') / 4"), Ok(d("2.5")));
+        assert_eq!(eval_code("decimal(a) - decimal('0.5')"), Ok(d("33.5")));
+        assert_eq!(eval_code("decimal(3, 2)"), Ok(d("3.00")));
+        assert_eq!(eval_code("decimal('2.675', 2)"), Ok(d("2.68")));
+        assert_eq!(
+            eval_code("round(decimal('2.5'))"),
+            Ok(DynamicValue::Integer(3))
+        );
+        assert_eq!(
+            eval_code("floor(decimal('-2.5'))"),
+            Ok(DynamicValue::Integer(-3))
+        );
+        assert_eq!(
+            eval_code("decimal('1.50') == 1.5"),
+            Ok(DynamicValue::from(true))
+        );
+        assert_eq!(
+            eval_code("typeof(decimal(1))"),
+            Ok(DynamicValue::from("decimal"))
+        );
+        assert_eq!(
+            eval_code("format(decimal('1234.5'), ',.2')"),
+            Ok(DynamicValue::from("1,234.50"))
+        );
+        assert!(eval_code("decimal(name)").is_err());
+    }
+
+    #[test]
+    fn test_geo() {
+        assert_eq!(
+            eval_code("round(haversine(48.8566, 2.3522, 51.5074, -0.1278))"),
+            Ok(DynamicValue::Integer(344))
+        );
+        assert_eq!(
+            eval_code("geohash(57.64911, 10.40744, 5)"),
+            Ok(DynamicValue::from("u4pru"))
+        );
+        assert!(eval_code("geohash(91, 0)").is_err());
+        assert_eq!(
+            eval_code("geohash_decode('s')"),
+            Ok(DynamicValue::from(vec![
+                DynamicValue::from(22.5),
+                DynamicValue::from(22.5)
+            ]))
+        );
+        assert_eq!(
+            eval_code("bbox('LINESTRING (2 3, -1 5, 4 1)')"),
+            Ok(DynamicValue::from(vec![
+                DynamicValue::from(-1.0),
+                DynamicValue::from(1.0),
+                DynamicValue::from(4.0),
+                DynamicValue::from(5.0)
+            ]))
+        );
+        assert_eq!(
+            eval_code("in_bbox(a, b, [60, 30, 70, 40])"),
+            Ok(DynamicValue::from(true))
+        );
+        assert_eq!(
+            eval_code("in_bbox(b, a, [60, 30, 70, 40])"),
+            Ok(DynamicValue::from(false))
+        );
+        assert_eq!(
+            eval_code(
+                "in_polygon(a, b, parse_wkt('POLYGON ((60 30, 70 30, 70 40, 60 40, 60 30))'))"
+            ),
+            Ok(DynamicValue::from(true))
+        );
+        assert_eq!(
+            eval_code("in_polygon(0, 0, '{\"type\": \"Polygon\", \"coordinates\": [[[1, 1], [2, 1], [2, 2], [1, 1]]]}')"),
+            Ok(DynamicValue::from(false))
+        );
+        assert!(eval_code("in_polygon(a, b, 'POINT (1 2)')").is_err());
+    }
+
+    #[test]
+    fn test_html_and_text_cleaning() {
+        assert_eq!(
+            eval_code("strip_tags('<p>Hello <b>world</b></p>')"),
+            Ok(DynamicValue::from("Hello world"))
+        );
+        assert_eq!(
+            eval_code("html_to_text('<p>caf&eacute;</p><script>x</script><p>  a  b </p>')"),
+            Ok(DynamicValue::from("café\na b"))
+        );
+        assert_eq!(
+            eval_code("html_unescape('&lt;Tom &amp; Jerry&#33;&gt;')"),
+            Ok(DynamicValue::from("<Tom & Jerry!>"))
+        );
+        assert_eq!(
+            eval_code("html_escape('<a & b>')"),
+            Ok(DynamicValue::from("&lt;a &amp; b&gt;"))
+        );
+        assert_eq!(
+            eval_code("html_links('<a href=\"/one\">1</a><a href=\"https://b.com/\">2</a>')"),
+            Ok(DynamicValue::from(vec![
+                DynamicValue::from("/one"),
+                DynamicValue::from("https://b.com/")
+            ]))
+        );
+        assert_eq!(
+            eval_code("html_links('<a href=\"one\">1</a>', 'https://a.com/path/')"),
+            Ok(DynamicValue::from(vec![DynamicValue::from(
+                "https://a.com/path/one"
+            )]))
+        );
+        assert_eq!(
+            eval_code("collapse_whitespace('  hello \t\n world ')"),
+            Ok(DynamicValue::from("hello world"))
+        );
+        assert_eq!(
+            eval_code("unicode_normalize('e\u{0301}')"),
+            Ok(DynamicValue::from("é"))
+        );
+        assert_eq!(
+            eval_code("unicode_normalize('ﬁ', 'NFKC')"),
+            Ok(DynamicValue::from("fi"))
+        );
+        assert_eq!(
+            eval_code("len(unicode_normalize('é', 'nfd'))"),
+            Ok(DynamicValue::from(2))
+        );
+        assert!(eval_code("unicode_normalize('é', 'test')").is_err());
+        assert_eq!(
+            eval_code("casefold('Straße')"),
+            Ok(DynamicValue::from("strasse"))
+        );
+    }
+
+    #[test]
+    fn test_ip() {
+        assert_eq!(
+            eval_code("is_ip('192.168.0.1')"),
+            Ok(DynamicValue::from(true))
+        );
+        assert_eq!(eval_code("is_ip('::1', 4)"), Ok(DynamicValue::from(false)));
+        assert_eq!(eval_code("is_ip('::1', 6)"), Ok(DynamicValue::from(true)));
+        assert_eq!(
+            eval_code("is_ip('256.0.0.1')"),
+            Ok(DynamicValue::from(false))
+        );
+        assert_eq!(
+            eval_code("parse_ip('10.0.0.1')['is_private']"),
+            Ok(DynamicValue::from(true))
+        );
+        assert_eq!(
+            eval_code("parse_ip('2001:0db8:0000::0001')['address']"),
+            Ok(DynamicValue::from("2001:db8::1"))
+        );
+        assert!(eval_code("parse_ip(name)").is_err());
+        assert_eq!(
+            eval_code("in_cidr('10.1.2.3', '10.0.0.0/8')"),
+            Ok(DynamicValue::from(true))
+        );
+        assert_eq!(
+            eval_code("in_cidr('172.16.0.1', ['10.0.0.0/8', '', '192.168.0.0/16'])"),
+            Ok(DynamicValue::from(false))
+        );
+        assert_eq!(
+            eval_code("in_cidr('2001:db8::1', ['10.0.0.0/8', '2001:db8::/32'])"),
+            Ok(DynamicValue::from(true))
+        );
+        assert!(eval_code("in_cidr('10.1.2.3', '10.0.0.0/42')").is_err());
+        assert_eq!(
+            eval_code("ip_to_int('192.168.0.1')"),
+            Ok(DynamicValue::from("3232235521"))
+        );
+        assert_eq!(
+            eval_code("ip_to_int('192.168.0.1') + 1"),
+            Ok(DynamicValue::from(3232235522_i64))
+        );
+        assert_eq!(eval_code("ip_to_int('::1')"), Ok(DynamicValue::from("1")));
+        assert_eq!(
+            eval_code("anonymize_ip('192.168.12.34')"),
+            Ok(DynamicValue::from("192.168.12.0"))
+        );
+        assert_eq!(
+            eval_code("anonymize_ip('192.168.12.34', 16)"),
+            Ok(DynamicValue::from("192.168.0.0"))
+        );
+        assert_eq!(
+            eval_code("anonymize_ip('2001:db8:85a3::8a2e:370:7334')"),
+            Ok(DynamicValue::from("2001:db8:85a3::"))
+        );
+        assert!(eval_code("anonymize_ip('1.2.3.4', 33)").is_err());
+    }
+
     #[test]
     fn test_lower() {
         assert_eq!(eval_code("lower(surname)"), Ok(b("smith")));
@@ -746,6 +992,125 @@ mod tests {
             eval_code("get({'one': {'two': [1, 2, 3]}}, ['one', 'two', 1])"),
             Ok(DynamicValue::from(2))
         );
+
+        // Optional chaining
+        assert_eq!(eval_code("get(null, 'one')"), Ok(DynamicValue::None));
+        assert_eq!(eval_code("get(null, 'one', 4)"), Ok(DynamicValue::from(4)));
+        assert_eq!(
+            eval_code("{one: {two: 2}}['three']['two']"),
+            Ok(DynamicValue::None)
+        );
+        assert_eq!(
+            eval_code("get({one: [{two: 2}]}, ['one', 3, 'two'], 'none')"),
+            Ok(DynamicValue::from("none"))
+        );
+        assert!(eval_code("get(45, 'one')").is_err());
+    }
+
+    #[test]
+    fn test_try() {
+        assert_eq!(eval_code("try(name + 1, 0)"), Ok(DynamicValue::from(0)));
+        assert_eq!(eval_code("try(a + 1, 0)"), Ok(DynamicValue::from(35)));
+        assert_eq!(eval_code("try(name + 1)"), Ok(DynamicValue::None));
+        assert_eq!(
+            eval_code("try(err('static'), 'caught')"),
+            Ok(DynamicValue::from("caught"))
+        );
+        assert_eq!(
+            eval_code("try(1 + err('nested'), 2)"),
+            Ok(DynamicValue::from(2))
+        );
+        assert!(eval_code("try(name + 1, err('fallback'))").is_err());
+    }
+
+    #[test]
+    fn test_fstrings() {
+        assert_eq!(
+            eval_code("f'{name} {surname}'"),
+            Ok(DynamicValue::from("john SMITH"))
+        );
+        assert_eq!(
+            eval_code("f\"{a} + {b} = {a + b}\""),
+            Ok(DynamicValue::from("34 + 62 = 96"))
+        );
+        assert_eq!(
+            eval_code("f'{a / 3:.2} | {name:*^8} | {b:05}'"),
+            Ok(DynamicValue::from("11.33 | **john** | 00062"))
+        );
+        assert_eq!(
+            eval_code("f'{{{upper(name)}}}'"),
+            Ok(DynamicValue::from("{JOHN}"))
+        );
+        assert_eq!(
+            eval_code("format(1234.5, ',.2f')"),
+            Ok(DynamicValue::from("1,234.50"))
+        );
+        assert!(eval_code("f'{name:.2}'").is_err());
+    }
+
+    #[test]
+    fn test_case() {
+        assert_eq!(
+            eval_code("case(name, 'john', 1, 'mary', 2)"),
+            Ok(DynamicValue::from(1))
+        );
+        assert_eq!(
+            eval_code("case(surname, 'john', 1, 'mary', 2)"),
+            Ok(DynamicValue::None)
+        );
+        assert_eq!(
+            eval_code("case(surname, 'john', 1, 'mary', 2, 3)"),
+            Ok(DynamicValue::from(3))
+        );
+        assert_eq!(
+            eval_code("case(surname, /^sm/i, 'regex', 'other')"),
+            Ok(DynamicValue::from("regex"))
+        );
+        assert_eq!(
+            eval_code("case(name, ['mary', 'john'], 'list', 'other')"),
+            Ok(DynamicValue::from("list"))
+        );
+        assert_eq!(
+            eval_code("case(a, {lt: 18}, 'minor', {ge: 18, lt: 65}, 'adult', 'senior')"),
+            Ok(DynamicValue::from("adult"))
+        );
+        assert_eq!(
+            eval_code("case(b, 34, 'no', 62.0, 'yes')"),
+            Ok(DynamicValue::from("yes"))
+        );
+        assert_eq!(
+            eval_code("case(b, x => x > 60, 'lambda', 'other')"),
+            Ok(DynamicValue::from("lambda"))
+        );
+        assert_eq!(
+            eval_code("case('', null, 'empty', 'other')"),
+            Ok(DynamicValue::from("empty"))
+        );
+
+        // Short-circuiting
+        assert_eq!(
+            eval_code("case(name, 'john', 1, name + 1, 2)"),
+            Ok(DynamicValue::from(1))
+        );
+        assert_eq!(
+            eval_code("case(name, 'john', 1, 'mary', name + 1)"),
+            Ok(DynamicValue::from(1))
+        );
+        assert!(eval_code("case(a, {between: 18}, 'adult')").is_err());
+    }
+
+    #[test]
+    fn test_nullish_operator() {
+        assert_eq!(eval_code("null ?? 'test'"), Ok(DynamicValue::from("test")));
+        assert_eq!(eval_code("'' ?? 'test'"), Ok(DynamicValue::from("test")));
+        assert_eq!(eval_code("false ?? 'test'"), Ok(DynamicValue::from(false)));
+        assert_eq!(eval_code("0 ?? 'test'"), Ok(DynamicValue::from(0)));
+        assert_eq!(eval_code("name ?? 'test'"), Ok(b("john")));
+        assert_eq!(eval_code("missing? ?? surname"), Ok(b("SMITH")));
+        assert_eq!(eval_code("null ?? null ?? 3"), Ok(DynamicValue::from(3)));
+
+        // Short-circuiting
+        assert_eq!(eval_code("name ?? surname + 1"), Ok(b("john")));
     }
 
     #[test]
@@ -919,6 +1284,36 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_cols_and_headers() {
+        fn list(items: &[&str]) -> DynamicValue {
+            DynamicValue::from(items.iter().map(|item| b(item)).collect::<Vec<_>>())
+        }
+
+        assert_eq!(eval_code("cols('a')"), Ok(list(&["34", "62"])));
+        assert_eq!(eval_code("cols(1, 'a')"), Ok(list(&["SMITH", "34"])));
+        assert_eq!(
+            eval_code("cols()"),
+            Ok(list(&["john", "SMITH", "34", "62"]))
+        );
+        assert_eq!(eval_code("cols('*name')"), Ok(list(&["john", "SMITH"])));
+        assert_eq!(eval_code("cols('sur*')"), Ok(list(&["SMITH"])));
+        assert_eq!(
+            eval_code("cols('x*')"),
+            Err(RunError::Prepare(ConcretizationError::ColumnNotFound(
+                ColumIndexationBy::Name("x*".to_string())
+            )))
+        );
+        assert_eq!(
+            eval_code("headers()"),
+            Ok(list(&["name", "surname", "a", "b"]))
+        );
+        assert_eq!(
+            eval_code("headers('*name')"),
+            Ok(list(&["name", "surname"]))
+        );
+    }
+
     #[test]
     fn test_fmt() {
         assert_eq!(
@@ -960,6 +1355,190 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_string_similarity() {
+        assert_eq!(
+            eval_code("levenshtein(name, 'jon')"),
+            Ok(DynamicValue::from(1))
+        );
+        assert_eq!(
+            eval_code("damerau_levenshtein('ca', 'ac')"),
+            Ok(DynamicValue::from(1))
+        );
+        assert_eq!(
+            eval_code("jaro_winkler(name, name)"),
+            Ok(DynamicValue::from(1.0))
+        );
+        assert_eq!(
+            eval_code("dice('night', 'nacht')"),
+            Ok(DynamicValue::from(0.25))
+        );
+        assert_eq!(
+            eval_code("jaccard('abc', 'abd', 1)"),
+            Ok(DynamicValue::from(0.5))
+        );
+        assert_eq!(
+            eval_code("longest_common_substring('abcdef', 'zcdez')"),
+            Ok(DynamicValue::from("cde"))
+        );
+    }
+
+    #[test]
+    fn test_phonetic_keys() {
+        assert_eq!(
+            eval_code("soundex(surname)"),
+            Ok(DynamicValue::from("S530"))
+        );
+        assert_eq!(
+            eval_code("metaphone(surname)"),
+            Ok(DynamicValue::from("SM0"))
+        );
+        assert_eq!(
+            eval_code("soundex_fr('Dupont') eq soundex_fr('Dupond')"),
+            Ok(DynamicValue::from(true))
+        );
+    }
+
+    #[test]
+    fn test_urls() {
+        assert_eq!(
+            eval_code("get(parse_url('https://lemonde.fr:8000/article?id=4'), ['query', 'id'])"),
+            Ok(DynamicValue::from("4"))
+        );
+        assert_eq!(
+            eval_code("get(parse_url('https://lemonde.fr/?q=1&id=4&q=2&q=3'), ['query', 'q'])"),
+            Ok(DynamicValue::from(vec![
+                DynamicValue::from("1"),
+                DynamicValue::from("2"),
+                DynamicValue::from("3")
+            ]))
+        );
+        assert_eq!(
+            eval_code("domain_name('http://news.bbc.co.uk')"),
+            Ok(DynamicValue::from("bbc.co.uk"))
+        );
+        assert_eq!(
+            eval_code("normalize_url('https://www.lemonde.fr/?utm_medium=social')"),
+            Ok(DynamicValue::from("https://lemonde.fr"))
+        );
+        assert_eq!(
+            eval_code("is_shortened_url('https://bit.ly/test')"),
+            Ok(DynamicValue::from(true))
+        );
+        assert_eq!(
+            eval_code("lru('https://lemonde.fr/article')"),
+            Ok(DynamicValue::from("s:https|h:fr|h:lemonde|p:article|"))
+        );
+    }
+
+    #[test]
+    fn test_hashing() {
+        assert_eq!(
+            eval_code("sha1('test')"),
+            Ok(DynamicValue::from(
+                "a94a8fe5ccb19ba61c4c0873d391e987982fbbd3"
+            ))
+        );
+        assert_eq!(
+            eval_code("sha256('test')"),
+            Ok(DynamicValue::from(
+                "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08"
+            ))
+        );
+        assert_eq!(
+            eval_code("crc32('test')"),
+            Ok(DynamicValue::from(3632233996_i64))
+        );
+        assert_eq!(eval_code("len(xxh3('test'))"), Ok(DynamicValue::from(16)));
+    }
+
+    #[test]
+    fn test_encoding() {
+        assert_eq!(
+            eval_code("base64_encode('test')"),
+            Ok(DynamicValue::from("dGVzdA=="))
+        );
+        assert_eq!(eval_code("base64_decode('dGVzdA==')"), Ok(b("test")));
+        assert_eq!(
+            eval_code("hex_encode('test')"),
+            Ok(DynamicValue::from("74657374"))
+        );
+        assert_eq!(eval_code("hex_decode('74657374')"), Ok(b("test")));
+        assert_eq!(
+            eval_code("urlencode('été & co.')"),
+            Ok(DynamicValue::from("%C3%A9t%C3%A9%20%26%20co."))
+        );
+        assert_eq!(
+            eval_code("urldecode('%C3%A9t%C3%A9%20%26%20co.')"),
+            Ok(b("été & co."))
+        );
+    }
+
+    #[test]
+    fn test_regexes() {
+        assert_eq!(
+            eval_code("find_all('#one #two three', /#\\w+/)"),
+            Ok(DynamicValue::from(vec![
+                DynamicValue::from("#one"),
+                DynamicValue::from("#two")
+            ]))
+        );
+        assert_eq!(
+            eval_code("find_all('a=1, b=2', /(\\w)=(?<value>\\d)/, 'value')"),
+            Ok(DynamicValue::from(vec![
+                DynamicValue::from("1"),
+                DynamicValue::from("2")
+            ]))
+        );
+        assert_eq!(
+            eval_code("find_all('a=1, b=2', /(\\w)=\\d/, 1)"),
+            Ok(DynamicValue::from(vec![
+                DynamicValue::from("a"),
+                DynamicValue::from("b")
+            ]))
+        );
+        assert_eq!(
+            eval_code("find_all('a.b.c', '.')"),
+            Ok(DynamicValue::from(vec![
+                DynamicValue::from("."),
+                DynamicValue::from(".")
+            ]))
+        );
+        assert_eq!(
+            eval_code("find_all('abc', 'd')"),
+            Ok(DynamicValue::from(Vec::<DynamicValue>::new()))
+        );
+        assert!(eval_code("find_all('a.b.c', '.', 1)").is_err());
+        assert_eq!(
+            eval_code("captures('2024-07', /(?<year>\\d{4})-(\\d{2})/) | get(_, 'year')"),
+            Ok(DynamicValue::from("2024"))
+        );
+        assert_eq!(
+            eval_code("captures('2024-07', /(?<year>\\d{4})-(\\d{2})/) | get(_, '2')"),
+            Ok(DynamicValue::from("07"))
+        );
+        assert_eq!(
+            eval_code("captures('test', /\\d+/)"),
+            Ok(DynamicValue::None)
+        );
+        assert_eq!(
+            eval_code("split('a1b22c', /\\d+/)"),
+            Ok(DynamicValue::from(vec![
+                DynamicValue::from("a"),
+                DynamicValue::from("b"),
+                DynamicValue::from("c")
+            ]))
+        );
+        assert_eq!(
+            eval_code("replace('john smith', /(?<first>\\w+) (\\w+)/, '$2 ${first}')"),
+            Ok(DynamicValue::from("smith john"))
+        );
+        assert_eq!(
+            eval_code("count_matches('a1b22c', /\\d/)"),
+            Ok(DynamicValue::from(3))
+        );
+    }
+
     #[test]
     fn test_pow() {
         assert_eq!(eval_code("pow(2, 4)"), Ok(DynamicValue::from(16)));
@@ -1078,6 +1657,128 @@ mod tests {
         assert_eq!(eval_code("{hello: name} | get(_, 'hello')"), Ok(b("john")));
     }
 
+    #[test]
+    fn test_list_helpers() {
+        assert_eq!(
+            eval_code("sort([3, '10', 1])"),
+            Ok(DynamicValue::from(vec![
+                DynamicValue::from(1),
+                DynamicValue::from(3),
+                DynamicValue::from("10")
+            ]))
+        );
+        assert_eq!(
+            eval_code("unique([1, 2, 1, 3, 2])"),
+            Ok(DynamicValue::from(vec![
+                DynamicValue::from(1),
+                DynamicValue::from(2),
+                DynamicValue::from(3)
+            ]))
+        );
+        assert_eq!(
+            eval_code("zip([1, 2, 3], ['a', 'b'])"),
+            Ok(DynamicValue::from(vec![
+                DynamicValue::from(vec![DynamicValue::from(1), DynamicValue::from("a")]),
+                DynamicValue::from(vec![DynamicValue::from(2), DynamicValue::from("b")])
+            ]))
+        );
+        assert_eq!(
+            eval_code("flatten([[1, 2], 3, [4]])"),
+            Ok(DynamicValue::from(vec![
+                DynamicValue::from(1),
+                DynamicValue::from(2),
+                DynamicValue::from(3),
+                DynamicValue::from(4)
+            ]))
+        );
+        assert_eq!(
+            eval_code("enumerate(['a']) | first"),
+            Ok(DynamicValue::from(vec![
+                DynamicValue::from(0),
+                DynamicValue::from("a")
+            ]))
+        );
+        assert_eq!(eval_code("range(4) | sum"), Ok(DynamicValue::from(6)));
+        assert_eq!(
+            eval_code("range(1, 10, 3) | len"),
+            Ok(DynamicValue::from(3))
+        );
+        assert_eq!(
+            eval_code("range(5, 0, -2) | sum"),
+            Ok(DynamicValue::from(9))
+        );
+        assert_eq!(
+            eval_code("range(9223372036854775800, 9223372036854775807, 10)"),
+            Ok(DynamicValue::from(vec![DynamicValue::from(
+                9223372036854775800_i64
+            )]))
+        );
+        assert_eq!(
+            eval_code("range(-9223372036854775800, -9223372036854775807, -10)"),
+            Ok(DynamicValue::from(vec![DynamicValue::from(
+                -9223372036854775800_i64
+            )]))
+        );
+        assert_eq!(eval_code("sum([a, b, 0.5])"), Ok(DynamicValue::from(96.5)));
+        assert_eq!(eval_code("median([3, 1, 2])"), Ok(DynamicValue::from(2)));
+        assert_eq!(
+            eval_code("median([4, 1, 2, 3])"),
+            Ok(DynamicValue::from(2.5))
+        );
+        assert_eq!(eval_code("median([])"), Ok(DynamicValue::None));
+    }
+
+    #[test]
+    fn test_higher_order_functions() {
+        assert_eq!(
+            eval_code("map([1, 2], x => x + 1)"),
+            Ok(DynamicValue::from(vec![
+                DynamicValue::from(2),
+                DynamicValue::from(3)
+            ]))
+        );
+        assert_eq!(
+            eval_code("reduce([1, 2, 3], (acc, x) => acc * x + a)"),
+            Ok(DynamicValue::from(142))
+        );
+        assert_eq!(
+            eval_code("fold([1, 2, 3], (acc, x) => acc + x, 10)"),
+            Ok(DynamicValue::from(16))
+        );
+        assert!(eval_code("reduce([], (acc, x) => acc + x)").is_err());
+        assert_eq!(
+            eval_code("find([1, 5, 8], x => x > 4)"),
+            Ok(DynamicValue::from(5))
+        );
+        assert_eq!(
+            eval_code("find([1, 5, 8], x => x > 10)"),
+            Ok(DynamicValue::None)
+        );
+        assert_eq!(
+            eval_code("any([1, 5, 8], x => x > 6)"),
+            Ok(DynamicValue::from(true))
+        );
+        assert_eq!(
+            eval_code("all([1, 5, 8], x => x > 6)"),
+            Ok(DynamicValue::from(false))
+        );
+        assert_eq!(
+            eval_code("sort_by(['aaa', 'b', 'cc'], s => len(s))"),
+            Ok(DynamicValue::from(vec![
+                DynamicValue::from("b"),
+                DynamicValue::from("cc"),
+                DynamicValue::from("aaa")
+            ]))
+        );
+        assert_eq!(
+            eval_code("group_by([1, 2, 3, 4], x => x % 2) | get(_, '0')"),
+            Ok(DynamicValue::from(vec![
+                DynamicValue::from(2),
+                DynamicValue::from(4)
+            ]))
+        );
+    }
+
     #[test]
     fn test_json() {
         assert_eq!(
@@ -0,0 +1,143 @@
+// NOTE: helpers used by the ip-related functions. Addresses are handled as
+// integers (u32 for IPv4, u128 for IPv6) so that CIDR masking is just a
+// matter of bitwise operations.
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::str::FromStr;
+
+// Zero all bits of the address after the given prefix length
+pub fn truncate(ip: IpAddr, prefix_len: u8) -> IpAddr {
+    match ip {
+        IpAddr::V4(v4) => {
+            let mask = u32::MAX.checked_shl(32 - prefix_len as u32).unwrap_or(0);
+            IpAddr::V4(Ipv4Addr::from(u32::from(v4) & mask))
+        }
+        IpAddr::V6(v6) => {
+            let mask = u128::MAX.checked_shl(128 - prefix_len as u32).unwrap_or(0);
+            IpAddr::V6(Ipv6Addr::from(u128::from(v6) & mask))
+        }
+    }
+}
+
+pub fn max_prefix_len(ip: &IpAddr) -> u8 {
+    match ip {
+        IpAddr::V4(_) => 32,
+        IpAddr::V6(_) => 128,
+    }
+}
+
+// NOTE: a bit like Ipv4Addr::is_private, but also covering IPv6 unique
+// local addresses, since Ipv6Addr::is_unique_local is not stable yet.
+pub fn is_private(ip: &IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => v4.is_private(),
+        IpAddr::V6(v6) => (v6.segments()[0] & 0xfe00) == 0xfc00,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cidr {
+    network: IpAddr,
+    prefix_len: u8,
+}
+
+impl Cidr {
+    pub fn contains(&self, ip: &IpAddr) -> bool {
+        // NOTE: IPv4-mapped IPv6 addresses are matched against IPv4 ranges
+        let ip = match (ip, self.network) {
+            (IpAddr::V6(v6), IpAddr::V4(_)) => match v6.to_ipv4_mapped() {
+                Some(v4) => IpAddr::V4(v4),
+                None => return false,
+            },
+            _ => *ip,
+        };
+
+        if ip.is_ipv4() != self.network.is_ipv4() {
+            return false;
+        }
+
+        truncate(ip, self.prefix_len) == self.network
+    }
+}
+
+impl FromStr for Cidr {
+    type Err = String;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        let string = string.trim();
+
+        let (address, prefix_len) = match string.split_once('/') {
+            Some((address, prefix_len)) => (address, Some(prefix_len)),
+            None => (string, None),
+        };
+
+        let network = address
+            .parse::<IpAddr>()
+            .map_err(|_| format!("invalid cidr range \"{}\"", string))?;
+
+        let max = max_prefix_len(&network);
+
+        let prefix_len = match prefix_len {
+            None => max,
+            Some(p) => match p.parse::<u8>() {
+                Ok(p) if p <= max => p,
+                _ => return Err(format!("invalid cidr prefix length in \"{}\"", string)),
+            },
+        };
+
+        // NOTE: host bits are zeroed, as "192.168.1.12/24" is routinely used
+        // to mean "192.168.1.0/24"
+        Ok(Self {
+            network: truncate(network, prefix_len),
+            prefix_len,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ip(string: &str) -> IpAddr {
+        string.parse().unwrap()
+    }
+
+    fn cidr(string: &str) -> Cidr {
+        string.parse().unwrap()
+    }
+
+    #[test]
+    fn test_truncate() {
+        assert_eq!(truncate(ip("192.168.12.34"), 24), ip("192.168.12.0"));
+        assert_eq!(truncate(ip("192.168.12.34"), 0), ip("0.0.0.0"));
+        assert_eq!(truncate(ip("192.168.12.34"), 32), ip("192.168.12.34"));
+        assert_eq!(
+            truncate(ip("2001:db8:85a3::8a2e:370:7334"), 48),
+            ip("2001:db8:85a3::")
+        );
+    }
+
+    #[test]
+    fn test_cidr() {
+        assert!(cidr("10.0.0.0/8").contains(&ip("10.12.0.1")));
+        assert!(!cidr("10.0.0.0/8").contains(&ip("11.0.0.1")));
+        assert!(cidr("192.168.1.12/24").contains(&ip("192.168.1.200")));
+        assert!(cidr("1.2.3.4").contains(&ip("1.2.3.4")));
+        assert!(!cidr("1.2.3.4").contains(&ip("1.2.3.5")));
+        assert!(cidr("0.0.0.0/0").contains(&ip("8.8.8.8")));
+        assert!(cidr("10.0.0.0/8").contains(&ip("::ffff:10.1.2.3")));
+        assert!(!cidr("10.0.0.0/8").contains(&ip("2001:db8::1")));
+        assert!(cidr("2001:db8::/32").contains(&ip("2001:db8:1::1")));
+        assert!(!cidr("2001:db8::/32").contains(&ip("10.0.0.1")));
+        assert!("10.0.0.0/33".parse::<Cidr>().is_err());
+        assert!("10.0.0/8".parse::<Cidr>().is_err());
+        assert!("2001:db8::/129".parse::<Cidr>().is_err());
+    }
+
+    #[test]
+    fn test_is_private() {
+        assert!(is_private(&ip("192.168.0.1")));
+        assert!(is_private(&ip("fd12:3456::1")));
+        assert!(!is_private(&ip("8.8.8.8")));
+        assert!(!is_private(&ip("2001:db8::1")));
+    }
+}
@@ -1,15 +1,25 @@
 pub mod agg;
+mod bytecode;
+mod casefold;
 mod choose;
+mod decimal;
 mod error;
+mod formatting;
 mod functions;
+mod fuzzy;
+mod geo;
+mod html;
 mod interpreter;
+mod ip;
 mod parser;
 mod select;
 mod special_functions;
 mod types;
+mod typing;
+mod urls;
 mod utils;
 
-pub use self::agg::{AggregationProgram, GroupAggregationProgram, Stats};
+pub use self::agg::{AggregationProgram, GroupAggregationProgram, GroupState, Stats};
 pub use self::choose::ChooseProgram;
 pub use self::error::{ConcretizationError, EvaluationError, SpecifiedEvaluationError};
 pub use self::interpreter::Program;
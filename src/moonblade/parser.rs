@@ -2,12 +2,14 @@
 // fassions pas *tous* tuer.
 use lazy_static::lazy_static;
 use pest::{
+    error::InputLocation,
     iterators::{Pair, Pairs},
     pratt_parser::{Assoc, Op, PrattParser},
     Parser,
 };
 use pest_derive::Parser;
 
+use super::formatting::FormatSpec;
 use super::functions::get_function;
 use super::types::DynamicValue;
 use super::utils::downgrade_float;
@@ -39,6 +41,7 @@ impl Rule {
             Self::pow => "pow",
             Self::rem => "mod",
             Self::concat => "concat",
+            Self::nullish => "ifnull",
             // NOTE: and & or operators need to be resolved using if statements
             // Self::and => "and",
             // Self::or => "or",
@@ -61,7 +64,8 @@ lazy_static! {
     // open_indexing here to pretend it's an infix operator.
     static ref PRATT_PARSER: PrattParser<Rule> = PrattParser::new()
         .op(Op::infix(Rule::pipe, Assoc::Left))
-        .op(Op::infix(Rule::or, Assoc::Left))
+        .op(Op::infix(Rule::or, Assoc::Left) |
+            Op::infix(Rule::nullish, Assoc::Left))
         .op(Op::infix(Rule::and, Assoc::Left))
         .op(Op::infix(Rule::gen_eq, Assoc::Left) |
             Op::infix(Rule::gen_ne, Assoc::Left) |
@@ -97,6 +101,23 @@ fn parse_int(pair: Pair<Rule>) -> Result<i64, &str> {
         .or(Err("could not parse int"))
 }
 
+fn build_escape(pair: Pair<Rule>) -> char {
+    let inner = pair.into_inner().next().unwrap();
+
+    match inner.as_rule() {
+        Rule::predefined => match inner.as_str() {
+            "n" => '\n',
+            "r" => '\r',
+            "t" => '\t',
+            "\\" => '\\',
+            "\"" => '"',
+            "'" => '\'',
+            _ => unreachable!(),
+        },
+        _ => unreachable!(),
+    }
+}
+
 fn build_string(pair: Pair<Rule>) -> String {
     let mut string = String::new();
 
@@ -108,24 +129,7 @@ fn build_string(pair: Pair<Rule>) -> String {
                 string.push_str(inner.as_str());
             }
             Rule::regex_flag => break,
-            Rule::escape => {
-                let inner = inner.into_inner().next().unwrap();
-
-                match inner.as_rule() {
-                    Rule::predefined => {
-                        string.push(match inner.as_str() {
-                            "n" => '\n',
-                            "r" => '\r',
-                            "t" => '\t',
-                            "\\" => '\\',
-                            "\"" => '"',
-                            "'" => '\'',
-                            _ => unreachable!(),
-                        });
-                    }
-                    _ => unreachable!(),
-                }
-            }
+            Rule::escape => string.push(build_escape(inner)),
             Rule::escape_regex => {
                 string.push_str(match inner.as_str() {
                     r"\n" => "\n",
@@ -143,6 +147,56 @@ fn build_string(pair: Pair<Rule>) -> String {
     string
 }
 
+// NOTE: f-strings are parsed into a concat call so they can be constant-folded
+// like any other expression, e.g. f"{name} ({year})" => concat("", name, " (", year, ")")
+fn build_fstring(pair: Pair<Rule>) -> Result<Expr, String> {
+    let mut parts = Vec::new();
+    let mut literal = String::new();
+
+    for inner in pair.into_inner() {
+        match inner.as_rule() {
+            Rule::raw_double_quoted_fstring | Rule::raw_single_quoted_fstring => {
+                literal.push_str(inner.as_str());
+            }
+            Rule::escape => literal.push(build_escape(inner)),
+            Rule::fstring_brace => literal.push_str(&inner.as_str()[..1]),
+            Rule::fstring_placeholder => {
+                let mut placeholder = inner.into_inner();
+                let mut expr = pratt_parse(Pairs::single(placeholder.next().unwrap()))?;
+
+                if let Some(format) = placeholder.next() {
+                    let spec = format.into_inner().next().unwrap().as_str();
+
+                    spec.parse::<FormatSpec>()?;
+
+                    expr = Expr::Func(FunctionCall::new(
+                        "format",
+                        vec![expr, Expr::Str(spec.to_string())],
+                    ));
+                }
+
+                // NOTE: concat must always start with a string, lest it acts on lists
+                if parts.is_empty() || !literal.is_empty() {
+                    parts.push(Expr::Str(std::mem::take(&mut literal)));
+                }
+
+                parts.push(expr);
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    if parts.is_empty() {
+        return Ok(Expr::Str(literal));
+    }
+
+    if !literal.is_empty() {
+        parts.push(Expr::Str(literal));
+    }
+
+    Ok(Expr::Func(FunctionCall::new("concat", parts)))
+}
+
 fn build_function_argument(pair: Pair<Rule>) -> (Option<String>, Pair<Rule>) {
     match pair.as_rule() {
         Rule::func_arg => {
@@ -219,6 +273,7 @@ fn pratt_parse(pairs: Pairs<Rule>) -> Result<Expr, String> {
                 }
                 Rule::string => Expr::Str(build_string(primary)),
                 Rule::binary_string => Expr::BStr(build_string(primary).into_bytes()),
+                Rule::fstring => build_fstring(primary)?,
                 Rule::regex => {
                     let case_insensitive =
                         primary.clone().into_inner().any(|t| match t.as_rule() {
@@ -521,6 +576,56 @@ impl From<String> for ParseError {
     }
 }
 
+impl ParseError {
+    // Render a human-readable error, highlighting the offending part of the
+    // code with carets when its location is known.
+    pub fn render(&self, code: &str) -> String {
+        let error = match self {
+            Self::Custom(msg) => {
+                return format!("could not parse expression: {}\n    {}", msg, code)
+            }
+            Self::Pest(error) => error,
+        };
+
+        let (start, end) = match error.location {
+            // NOTE: pest reports the position right after the last token it
+            // could parse, so we skip whitespace to reach the offending one
+            InputLocation::Pos(pos) => {
+                let pos = code.len() - code[pos..].trim_start().len();
+                (pos, pos)
+            }
+            InputLocation::Span(span) => span,
+        };
+
+        let line_start = code[..start].rfind('\n').map(|i| i + 1).unwrap_or(0);
+        let line_end = code[start..]
+            .find('\n')
+            .map(|i| i + start)
+            .unwrap_or(code.len());
+
+        let line = &code[line_start..line_end];
+        let line_number = code[..line_start].matches('\n').count() + 1;
+        let column = code[line_start..start].chars().count();
+        let width = code[start..end.min(line_end)].chars().count().max(1);
+
+        let reason = if start >= code.trim_end().len() {
+            "unexpected end of expression"
+        } else {
+            "unexpected token"
+        };
+
+        format!(
+            "could not parse expression, {} at line {}, column {}:\n    {}\n    {}{}",
+            reason,
+            line_number,
+            column + 1,
+            line.replace('\t', " "),
+            " ".repeat(column),
+            "^".repeat(width)
+        )
+    }
+}
+
 pub fn parse_expression(input: &str) -> Result<Expr, ParseError> {
     let mut pairs = MoonbladePestParser::parse(Rule::full_expr, input)?;
 
@@ -529,6 +634,60 @@ pub fn parse_expression(input: &str) -> Result<Expr, ParseError> {
     Ok(pratt_parse(Pairs::single(first_pair))?)
 }
 
+fn build_star_slice_item(pair: Pair<Rule>) -> Result<DynamicValue, ParseError> {
+    debug_assert!(matches!(pair.as_rule(), Rule::star_slice_item));
+
+    Ok(
+        match pratt_parse(Pairs::single(pair.into_inner().next().unwrap()))? {
+            Expr::Int(i) => DynamicValue::Integer(i),
+            Expr::Str(s) | Expr::Identifier(s, _) => DynamicValue::from(s),
+            _ => unreachable!(),
+        },
+    )
+}
+
+fn build_star_slice(pair: Pair<Rule>) -> Result<Expr, ParseError> {
+    debug_assert!(matches!(pair.as_rule(), Rule::star_slice));
+
+    let slice = match pair.into_inner().next() {
+        None => Slice::Full,
+        Some(slice) => {
+            let rule = slice.as_rule();
+            let mut inner = slice.into_inner();
+            let first = build_star_slice_item(inner.next().unwrap())?;
+
+            match rule {
+                Rule::full_star_slice => {
+                    Slice::Closed(first, build_star_slice_item(inner.next().unwrap())?)
+                }
+                Rule::start_star_slice => Slice::From(first),
+                Rule::end_star_slice => Slice::To(first),
+                _ => unreachable!(),
+            }
+        }
+    };
+
+    Ok(Expr::StarSlice(slice))
+}
+
+// NOTE: star slices are resolved as calls to `cols`, which is able to
+// find the relevant columns when given the headers.
+pub fn star_slice_to_cols_call(slice: &Slice<DynamicValue>) -> Expr {
+    let to_expr = |value: &DynamicValue| match value {
+        DynamicValue::Integer(i) => Expr::Int(*i),
+        _ => Expr::Str(value.try_as_str().unwrap().into_owned()),
+    };
+
+    let args = match slice {
+        Slice::Full => vec![],
+        Slice::From(start) => vec![to_expr(start)],
+        Slice::To(end) => vec![Expr::Int(0), to_expr(end)],
+        Slice::Closed(start, end) => vec![to_expr(start), to_expr(end)],
+    };
+
+    Expr::Func(FunctionCall::new("cols", args))
+}
+
 pub fn parse_named_expressions(input: &str) -> Result<Vec<(Expr, String)>, ParseError> {
     let pairs = MoonbladePestParser::parse(Rule::named_exprs, input)?;
 
@@ -536,51 +695,9 @@ pub fn parse_named_expressions(input: &str) -> Result<Vec<(Expr, String)>, Parse
         .filter(|p| !matches!(p.as_rule(), Rule::EOI))
         .map(|p| {
             let (name, p) = if p.as_rule() == Rule::star_slice {
-                let mut inner = p.into_inner();
                 let dummy_name = "".to_string();
 
-                if inner.len() == 0 {
-                    return Ok((Expr::StarSlice(Slice::Full), dummy_name));
-                } else if inner.len() == 1 {
-                    let slice = inner.next().unwrap();
-                    let start = slice.as_rule() == Rule::start_star_slice;
-
-                    let expr = pratt_parse(Pairs::single(slice.into_inner().next().unwrap()))?;
-                    let value = match expr {
-                        Expr::Int(i) => DynamicValue::Integer(i),
-                        Expr::Str(s) => DynamicValue::from(s),
-                        _ => unreachable!(),
-                    };
-
-                    if start {
-                        return Ok((Expr::StarSlice(Slice::From(value)), dummy_name));
-                    }
-
-                    return Ok((Expr::StarSlice(Slice::To(value)), dummy_name));
-                } else {
-                    let start = inner.next().unwrap();
-                    let end = inner.next().unwrap();
-
-                    let start_expr =
-                        pratt_parse(Pairs::single(start.into_inner().next().unwrap()))?;
-                    let end_expr = pratt_parse(Pairs::single(end.into_inner().next().unwrap()))?;
-
-                    let start_value = match start_expr {
-                        Expr::Int(i) => DynamicValue::Integer(i),
-                        Expr::Str(s) => DynamicValue::from(s),
-                        _ => unreachable!(),
-                    };
-                    let end_value = match end_expr {
-                        Expr::Int(i) => DynamicValue::Integer(i),
-                        Expr::Str(s) => DynamicValue::from(s),
-                        _ => unreachable!(),
-                    };
-
-                    return Ok((
-                        Expr::StarSlice(Slice::Closed(start_value, end_value)),
-                        dummy_name,
-                    ));
-                }
+                return Ok((build_star_slice(p)?, dummy_name));
             } else {
                 match p.as_rule() {
                     Rule::expr => (p.as_span().as_str().to_string(), p),
@@ -620,53 +737,107 @@ pub struct Aggregation {
     pub agg_name: String,
     pub args: Vec<Expr>,
     pub func_name: String,
+    pub filter: Option<Expr>,
+    // NOTE: byte range of the first argument in the aggregation name, when
+    // the name was not given explicitly. This is useful to generate names
+    // when expanding the aggregation over multiple columns.
+    pub first_arg_span: Option<(usize, usize)>,
 }
 
 pub type Aggregations = Vec<Aggregation>;
 
+fn build_aggregation(
+    agg_name: String,
+    named: bool,
+    pair: Pair<Rule>,
+) -> Result<Aggregation, ParseError> {
+    debug_assert!(matches!(pair.as_rule(), Rule::agg_func));
+
+    let offset = pair.as_span().start();
+    let mut inner = pair.into_inner();
+
+    let func = inner.next().unwrap();
+
+    let filter = inner
+        .next()
+        .map(|agg_filter| {
+            debug_assert!(matches!(agg_filter.as_rule(), Rule::agg_filter));
+
+            pratt_parse(agg_filter.into_inner())
+        })
+        .transpose()?;
+
+    let first_arg_span = if named {
+        None
+    } else {
+        func.clone().into_inner().nth(1).map(|arg| {
+            let span = arg.as_span();
+            (span.start() - offset, span.end() - offset)
+        })
+    };
+
+    match func.as_rule() {
+        Rule::star_func => {
+            let mut func_inner = func.into_inner();
+
+            let func_name = func_inner.next().unwrap().as_str().to_string();
+            let mut args = vec![build_star_slice(func_inner.next().unwrap())?];
+
+            for func_arg in func_inner {
+                let expr = func_arg.into_inner().last().unwrap();
+                args.push(pratt_parse(Pairs::single(expr))?);
+            }
+
+            Ok(Aggregation {
+                agg_name,
+                args,
+                func_name,
+                filter,
+                first_arg_span,
+            })
+        }
+        Rule::func => match pratt_parse(Pairs::single(func))? {
+            Expr::Func(call) => Ok(Aggregation {
+                agg_name,
+                args: call.args.into_iter().map(|(_, arg)| arg).collect(),
+                func_name: call.name,
+                filter,
+                first_arg_span,
+            }),
+            _ => unreachable!(),
+        },
+        _ => unreachable!(),
+    }
+}
+
 pub fn parse_aggregations(input: &str) -> Result<Aggregations, ParseError> {
     let pairs = MoonbladePestParser::parse(Rule::named_aggs, input)?;
 
     pairs
         .filter(|p| !matches!(p.as_rule(), Rule::EOI))
-        .map(|p| {
-            let (agg_name, p) = match p.as_rule() {
-                Rule::func => (p.as_span().as_str().to_string(), p),
-                Rule::named_func => {
-                    let mut inner = p.into_inner();
-
-                    debug_assert!(inner.len() == 2);
-
-                    let func = inner.next().unwrap();
+        .map(|p| match p.as_rule() {
+            Rule::agg_func => build_aggregation(p.as_span().as_str().to_string(), false, p),
+            Rule::named_func => {
+                let mut inner = p.into_inner();
 
-                    let expr_name = inner.next().unwrap();
-                    debug_assert!(matches!(expr_name.as_rule(), Rule::expr_name));
+                debug_assert!(inner.len() == 2);
 
-                    let expr_name_inner = expr_name.into_inner().next().unwrap();
+                let agg_func = inner.next().unwrap();
 
-                    let name = match expr_name_inner.as_rule() {
-                        Rule::ident => expr_name_inner.as_str().to_string(),
-                        Rule::string => build_string(expr_name_inner),
-                        _ => unreachable!(),
-                    };
+                let expr_name = inner.next().unwrap();
+                debug_assert!(matches!(expr_name.as_rule(), Rule::expr_name));
 
-                    debug_assert!(matches!(func.as_rule(), Rule::func));
+                let expr_name_inner = expr_name.into_inner().next().unwrap();
 
-                    (name, func)
-                }
-                _ => unreachable!(),
-            };
-
-            let expr = pratt_parse(Pairs::single(p))?;
+                let name = match expr_name_inner.as_rule() {
+                    Rule::ident => expr_name_inner.as_str().to_string(),
+                    Rule::string => build_string(expr_name_inner),
+                    _ => unreachable!(),
+                };
 
-            match expr {
-                Expr::Func(call) => Ok(Aggregation {
-                    agg_name,
-                    args: call.args.into_iter().map(|(_, arg)| arg).collect(),
-                    func_name: call.name,
-                }),
-                _ => unreachable!(),
+                build_aggregation(name, true, agg_func)
             }
+            _ => unreachable!(),
         })
         .collect()
 }
@@ -872,6 +1043,52 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_fstrings() {
+        assert_eq!(parse_expression("f'hello'"), Ok(s("hello")));
+        assert_eq!(parse_expression("f\"{{}}\""), Ok(s("{}")));
+        assert_eq!(
+            parse_expression("f\"{name} ({ year + 1 })\""),
+            Ok(func(
+                "concat",
+                vec![
+                    s(""),
+                    id("name"),
+                    s(" ("),
+                    func("add", vec![id("year"), Int(1)]),
+                    s(")")
+                ]
+            ))
+        );
+        assert_eq!(
+            parse_expression("f'n={count:>5.2}'"),
+            Ok(func(
+                "concat",
+                vec![s("n="), func("format", vec![id("count"), s(">5.2")])]
+            ))
+        );
+        assert!(parse_expression("f'{count:!!}'").is_err());
+    }
+
+    #[test]
+    fn test_nullish_operator() {
+        assert_eq!(
+            parse_expression("name ?? 'john'"),
+            Ok(func("ifnull", vec![id("name"), s("john")]))
+        );
+        assert_eq!(
+            parse_expression("name??surname"),
+            Ok(func("ifnull", vec![id("name"), id("surname")]))
+        );
+        assert_eq!(
+            parse_expression("name? ?? 1 + 2"),
+            Ok(func(
+                "ifnull",
+                vec![unsure_id("name"), func("add", vec![Int(1), Int(2)])]
+            ))
+        );
+    }
+
     #[test]
     fn test_expr_recursivity() {
         assert_eq!(
@@ -1010,7 +1227,9 @@ mod tests {
                 args: vec![func(
                     "add",
                     vec![func("add", vec![id("A"), id("B")]), Int(1)]
-                ),]
+                ),],
+                filter: None,
+                first_arg_span: Some((6, 19))
             }])
         );
 
@@ -1019,7 +1238,9 @@ mod tests {
             Ok(vec![Aggregation {
                 agg_name: "join(name, '|')".to_string(),
                 func_name: "join".to_string(),
-                args: vec![id("name"), s("|")]
+                args: vec![id("name"), s("|")],
+                filter: None,
+                first_arg_span: Some((5, 9))
             }])
         );
 
@@ -1029,12 +1250,62 @@ mod tests {
                 Aggregation {
                     agg_name: "c".to_string(),
                     func_name: "count".to_string(),
-                    args: vec![id("a")]
+                    args: vec![id("a")],
+                    filter: None,
+                    first_arg_span: None
                 },
                 Aggregation {
                     agg_name: "Sum".to_string(),
                     func_name: "sum".to_string(),
-                    args: vec![id("b")]
+                    args: vec![id("b")],
+                    filter: None,
+                    first_arg_span: None
+                }
+            ])
+        );
+
+        assert_eq!(
+            parse_aggregations("count() where a > 2, mean(b) where c as m"),
+            Ok(vec![
+                Aggregation {
+                    agg_name: "count() where a > 2".to_string(),
+                    func_name: "count".to_string(),
+                    args: vec![],
+                    filter: Some(func(">", vec![id("a"), Int(2)])),
+                    first_arg_span: None
+                },
+                Aggregation {
+                    agg_name: "m".to_string(),
+                    func_name: "mean".to_string(),
+                    args: vec![id("b")],
+                    filter: Some(id("c")),
+                    first_arg_span: None
+                }
+            ])
+        );
+    }
+
+    #[test]
+    fn test_star_aggregations() {
+        assert_eq!(
+            parse_aggregations("sum(*[a:\"b\"]), mean(*, 2) as m"),
+            Ok(vec![
+                Aggregation {
+                    agg_name: "sum(*[a:\"b\"])".to_string(),
+                    func_name: "sum".to_string(),
+                    args: vec![StarSlice(super::Slice::Closed(
+                        DynamicValue::from("a"),
+                        DynamicValue::from("b")
+                    ))],
+                    filter: None,
+                    first_arg_span: Some((4, 12))
+                },
+                Aggregation {
+                    agg_name: "m".to_string(),
+                    func_name: "mean".to_string(),
+                    args: vec![StarSlice(super::Slice::Full), Int(2)],
+                    filter: None,
+                    first_arg_span: None
                 }
             ])
         );
@@ -1050,4 +1321,22 @@ mod tests {
             ])
         );
     }
+
+    #[test]
+    fn test_parse_error_rendering() {
+        let render = |code: &str| parse_expression(code).unwrap_err().render(code);
+
+        assert_eq!(
+            render("trim(a +)"),
+            "could not parse expression, unexpected token at line 1, column 9:\n    trim(a +)\n            ^"
+        );
+        assert_eq!(
+            render("upper(name"),
+            "could not parse expression, unexpected end of expression at line 1, column 11:\n    upper(name\n              ^"
+        );
+        assert_eq!(
+            render("trim(\n  a +)"),
+            "could not parse expression, unexpected token at line 2, column 3:\n      a +)\n      ^"
+        );
+    }
 }
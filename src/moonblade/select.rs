@@ -1,27 +1,59 @@
 use csv::ByteRecord;
 
+use super::bytecode::Bytecode;
 use super::error::{ConcretizationError, SpecifiedEvaluationError};
-use super::interpreter::{concretize_expression, eval_expression, ConcreteExpr, EvaluationContext};
-use super::parser::parse_named_expressions;
+use super::interpreter::{concretize_expression, ConcreteExpr, EvaluationContext};
+use super::parser::{parse_named_expressions, star_slice_to_cols_call, Expr};
+use super::typing;
 
 #[derive(Clone)]
 pub struct SelectionProgram {
-    exprs: Vec<(ConcreteExpr, String)>,
+    exprs: Vec<(Bytecode, String)>,
     context: EvaluationContext,
 }
 
 impl SelectionProgram {
     pub fn parse(code: &str, headers: &ByteRecord) -> Result<Self, ConcretizationError> {
-        let exprs = match parse_named_expressions(code) {
-            Err(_) => return Err(ConcretizationError::ParseError(code.to_string())),
-            Ok(parsed_exprs) => parsed_exprs
-                .into_iter()
-                .map(|e| concretize_expression(e.0.clone(), headers).map(|c| (c, e.1)))
-                .collect::<Result<Vec<_>, _>>(),
-        }?;
+        let parsed_exprs = match parse_named_expressions(code) {
+            Err(err) => return Err(ConcretizationError::ParseError(err.render(code))),
+            Ok(parsed_exprs) => parsed_exprs,
+        };
+
+        let mut exprs = Vec::with_capacity(parsed_exprs.len());
+
+        for (expr, name) in parsed_exprs {
+            match expr {
+                // NOTE: star slices are expanded into one expression per column
+                Expr::StarSlice(slice) => {
+                    match concretize_expression(star_slice_to_cols_call(&slice), headers)? {
+                        ConcreteExpr::List(items) => {
+                            for item in items {
+                                let name = match item {
+                                    ConcreteExpr::Column(i) => {
+                                        String::from_utf8_lossy(&headers[i]).into_owned()
+                                    }
+                                    _ => unreachable!(),
+                                };
+
+                                exprs.push((item, name));
+                            }
+                        }
+                        _ => unreachable!(),
+                    }
+                }
+                _ => exprs.push((concretize_expression(expr, headers)?, name)),
+            }
+        }
+
+        for (expr, _) in exprs.iter() {
+            typing::check(expr)?;
+        }
 
         Ok(Self {
-            exprs,
+            exprs: exprs
+                .iter()
+                .map(|(expr, name)| (Bytecode::compile(expr), name.clone()))
+                .collect(),
             context: EvaluationContext::new(headers),
         })
     }
@@ -38,7 +70,7 @@ impl SelectionProgram {
         let mut output_record = csv::ByteRecord::new();
 
         for (expr, _) in self.exprs.iter() {
-            let value = eval_expression(expr, Some(index), record, &self.context)?;
+            let value = expr.run(Some(index), record, &self.context)?;
             output_record.push_field(&value.serialize_as_bytes());
         }
 
@@ -1,13 +1,15 @@
 // NOTE: the runtime function take a &[ConcreteExpr] instead of BoundArguments
 // because they notoriously might want not to bind arguments in the first
 // place (e.g. "if"/"unless").
+use std::cmp::Ordering;
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use csv::ByteRecord;
 
 use super::error::{ConcretizationError, EvaluationError, SpecifiedEvaluationError};
-use super::interpreter::{ConcreteExpr, EvaluationContext};
-use super::parser::FunctionCall;
+use super::interpreter::{concretize_expression, ConcreteExpr, EvaluationContext};
+use super::parser::{Expr, FunctionCall};
 use super::types::{
     Arity, ColumIndexationBy, DynamicValue, EvaluationResult, FunctionArguments, LambdaArguments,
 };
@@ -22,6 +24,11 @@ pub type RuntimeFunction = fn(
     Option<&LambdaArguments>,
 ) -> EvaluationResult;
 
+pub static SPECIAL_FUNCTION_NAMES: &[&str] = &[
+    "all", "any", "case", "col", "cols", "filter", "find", "fold", "group_by", "headers", "if",
+    "ifnull", "index", "map", "reduce", "sort_by", "try", "unless",
+];
+
 pub fn get_special_function(
     name: &str,
 ) -> Option<(
@@ -31,6 +38,9 @@ pub fn get_special_function(
 )> {
     macro_rules! higher_order_fn {
         ($name:expr, $variant:ident) => {
+            higher_order_fn!($name, $variant, FunctionArguments::binary())
+        };
+        ($name:expr, $variant:ident, $arguments:expr) => {
             (
                 None,
                 Some(
@@ -50,7 +60,7 @@ pub fn get_special_function(
                         )
                     },
                 ),
-                FunctionArguments::binary(),
+                $arguments,
             )
         };
     }
@@ -94,6 +104,20 @@ pub fn get_special_function(
             Some(runtime_unless),
             FunctionArguments::with_range(2..=3),
         ),
+        // NOTE: case is basically a chain of if calls and must short-circuit
+        // the same way.
+        "case" => (None, Some(runtime_case), FunctionArguments::variadic(3)),
+        // NOTE: ifnull backs the `??` operator and must only evaluate its
+        // fallback when needed, contrary to coalesce.
+        "ifnull" => (None, Some(runtime_ifnull), FunctionArguments::binary()),
+
+        // NOTE: try needs a comptime version because errors raised by static
+        // evaluation of its first argument must be caught too.
+        "try" => (
+            Some(comptime_try),
+            Some(runtime_try),
+            FunctionArguments::with_range(1..=2),
+        ),
 
         // NOTE: lambda evaluation need to be a special function because, like
         // if and unless, they cannot work in DFS fashion unless you
@@ -101,6 +125,13 @@ pub fn get_special_function(
         // NOTE: higher-order functions work fine with static evaluation
         "map" => higher_order_fn!("map", Map),
         "filter" => higher_order_fn!("filter", Filter),
+        "find" => higher_order_fn!("find", Find),
+        "any" => higher_order_fn!("any", Any),
+        "all" => higher_order_fn!("all", All),
+        "sort_by" => higher_order_fn!("sort_by", SortBy),
+        "group_by" => higher_order_fn!("group_by", GroupBy),
+        "reduce" => higher_order_fn!("reduce", Reduce),
+        "fold" => higher_order_fn!("fold", Fold, FunctionArguments::nary(3)),
 
         _ => return None,
     })
@@ -118,6 +149,30 @@ fn comptime_col(call: &FunctionCall, headers: &ByteRecord) -> ComptimeFunctionRe
     Ok(None)
 }
 
+fn find_columns_matching_pattern(pattern: &str, headers: &ByteRecord) -> Option<Vec<usize>> {
+    if pattern.len() < 2 || headers.iter().any(|h| h == pattern.as_bytes()) {
+        return None;
+    }
+
+    let columns = if let Some(prefix) = pattern.strip_suffix('*') {
+        headers
+            .iter()
+            .enumerate()
+            .filter_map(|(i, h)| h.starts_with(prefix.as_bytes()).then_some(i))
+            .collect()
+    } else if let Some(suffix) = pattern.strip_prefix('*') {
+        headers
+            .iter()
+            .enumerate()
+            .filter_map(|(i, h)| h.ends_with(suffix.as_bytes()).then_some(i))
+            .collect()
+    } else {
+        return None;
+    };
+
+    Some(columns)
+}
+
 fn comptime_cols_headers<F>(
     call: &FunctionCall,
     headers: &ByteRecord,
@@ -126,6 +181,28 @@ fn comptime_cols_headers<F>(
 where
     F: Fn(usize) -> ConcreteExpr,
 {
+    if call.args.is_empty() {
+        return Ok(Some(ConcreteExpr::List(
+            (0..headers.len()).map(map).collect(),
+        )));
+    }
+
+    // NOTE: a single name starting or ending with `*` is used as a prefix
+    // or suffix pattern, unless it matches an existing column exactly.
+    if let (1, Expr::Str(pattern)) = (call.args.len(), &call.args[0].1) {
+        if let Some(columns) = find_columns_matching_pattern(pattern, headers) {
+            let range: Vec<_> = columns.into_iter().map(map).collect();
+
+            if range.is_empty() {
+                return Err(ConcretizationError::ColumnNotFound(
+                    ColumIndexationBy::Name(pattern.clone()),
+                ));
+            }
+
+            return Ok(Some(ConcreteExpr::List(range)));
+        }
+    }
+
     match ColumIndexationBy::from_argument(&call.args[0].1) {
         None => Err(ConcretizationError::NotStaticallyAnalyzable),
         Some(first_column_indexation) => match first_column_indexation.find_column_index(headers) {
@@ -213,6 +290,153 @@ fn runtime_unless(
     }
 }
 
+fn case_pattern_matches(
+    value: &DynamicValue,
+    pattern: &DynamicValue,
+) -> Result<bool, EvaluationError> {
+    Ok(match pattern {
+        DynamicValue::Regex(regex) => regex.is_match(&value.try_as_str()?),
+        DynamicValue::List(alternatives) => {
+            for alternative in alternatives.iter() {
+                if case_pattern_matches(value, alternative)? {
+                    return Ok(true);
+                }
+            }
+
+            false
+        }
+        DynamicValue::Map(bounds) => {
+            let number = match value.try_as_number() {
+                Ok(number) => number,
+                Err(_) => return Ok(false),
+            };
+
+            for (op, bound) in bounds.iter() {
+                let bound = bound.try_as_number()?;
+
+                let in_range = match op.as_str() {
+                    "gt" => number > bound,
+                    "ge" => number >= bound,
+                    "lt" => number < bound,
+                    "le" => number <= bound,
+                    _ => {
+                        return Err(EvaluationError::Custom(format!(
+                            "unknown range bound \"{}\", expecting one of \"gt\", \"ge\", \"lt\" or \"le\"",
+                            op
+                        )))
+                    }
+                };
+
+                if !in_range {
+                    return Ok(false);
+                }
+            }
+
+            true
+        }
+        DynamicValue::Integer(_) | DynamicValue::Float(_) => match value.try_as_number() {
+            Ok(number) => number == pattern.try_as_number()?,
+            Err(_) => false,
+        },
+        DynamicValue::None => value.is_nullish(),
+        _ => value.try_as_str()? == pattern.try_as_str()?,
+    })
+}
+
+fn runtime_case(
+    index: Option<usize>,
+    record: &ByteRecord,
+    context: &EvaluationContext,
+    args: &[ConcreteExpr],
+    lambda_variables: Option<&LambdaArguments>,
+) -> EvaluationResult {
+    let value = args[0].evaluate(index, record, context, lambda_variables)?;
+
+    let mut branches = args[1..].chunks_exact(2);
+
+    for branch in branches.by_ref() {
+        let (pattern, result) = (&branch[0], &branch[1]);
+
+        let matches = match pattern.try_as_lambda() {
+            Ok((names, lambda)) => {
+                Arity::Strict(1)
+                    .validate(names.len())
+                    .map_err(|invalid_arity| {
+                        EvaluationError::InvalidArity(invalid_arity).specify("case")
+                    })?;
+
+                let mut variables = match lambda_variables {
+                    None => LambdaArguments::new(),
+                    Some(v) => v.clone(),
+                };
+
+                let arg_index = variables.register(&names[0]);
+                variables.set(arg_index, value.clone());
+
+                lambda
+                    .evaluate(index, record, context, Some(&variables))?
+                    .is_truthy()
+            }
+            Err(_) => {
+                let pattern = pattern.evaluate(index, record, context, lambda_variables)?;
+
+                case_pattern_matches(&value, &pattern).map_err(|err| err.specify("case"))?
+            }
+        };
+
+        if matches {
+            return result.evaluate(index, record, context, lambda_variables);
+        }
+    }
+
+    match branches.remainder().first() {
+        None => Ok(DynamicValue::None),
+        Some(default) => default.evaluate(index, record, context, lambda_variables),
+    }
+}
+
+fn runtime_ifnull(
+    index: Option<usize>,
+    record: &ByteRecord,
+    context: &EvaluationContext,
+    args: &[ConcreteExpr],
+    lambda_variables: Option<&LambdaArguments>,
+) -> EvaluationResult {
+    let value = args[0].evaluate(index, record, context, lambda_variables)?;
+
+    if value.is_nullish() {
+        args[1].evaluate(index, record, context, lambda_variables)
+    } else {
+        Ok(value)
+    }
+}
+
+fn comptime_try(call: &FunctionCall, headers: &ByteRecord) -> ComptimeFunctionResult {
+    match concretize_expression(call.args[0].1.clone(), headers) {
+        Err(ConcretizationError::StaticEvaluationError(_)) => match call.args.get(1) {
+            None => Ok(Some(ConcreteExpr::Value(DynamicValue::None))),
+            Some((_, fallback)) => concretize_expression(fallback.clone(), headers).map(Some),
+        },
+        _ => Ok(None),
+    }
+}
+
+fn runtime_try(
+    index: Option<usize>,
+    record: &ByteRecord,
+    context: &EvaluationContext,
+    args: &[ConcreteExpr],
+    lambda_variables: Option<&LambdaArguments>,
+) -> EvaluationResult {
+    match args[0].evaluate(index, record, context, lambda_variables) {
+        Ok(value) => Ok(value),
+        Err(_) => match args.get(1) {
+            None => Ok(DynamicValue::None),
+            Some(fallback) => fallback.evaluate(index, record, context, lambda_variables),
+        },
+    }
+}
+
 fn runtime_index(
     index: Option<usize>,
     _record: &ByteRecord,
@@ -261,6 +485,22 @@ fn runtime_col(
 enum HigherOrderOperation {
     Filter,
     Map,
+    Find,
+    Any,
+    All,
+    SortBy,
+    GroupBy,
+    Reduce,
+    Fold,
+}
+
+impl HigherOrderOperation {
+    fn lambda_arity(&self) -> usize {
+        match self {
+            Self::Reduce | Self::Fold => 2,
+            _ => 1,
+        }
+    }
 }
 
 fn runtime_higher_order(
@@ -286,17 +526,47 @@ fn runtime_higher_order(
         .map_err(|err| err.anonymous())?;
 
     // Validating arity
-    Arity::Strict(1)
+    Arity::Strict(op.lambda_arity())
         .validate(names.len())
         .map_err(|invalid_arity| EvaluationError::InvalidArity(invalid_arity).anonymous())?;
 
-    let arg_name = names.first().unwrap();
-
     let mut variables = match lambda_variables {
         None => LambdaArguments::new(),
         Some(v) => v.clone(),
     };
 
+    if let HigherOrderOperation::Reduce | HigherOrderOperation::Fold = op {
+        let acc_arg_index = variables.register(&names[0]);
+        let item_arg_index = variables.register(&names[1]);
+
+        let mut items = list.iter();
+
+        let mut acc = match op {
+            HigherOrderOperation::Fold => {
+                args[2].evaluate(index, record, context, lambda_variables)?
+            }
+            _ => match items.next() {
+                Some(first) => first.clone(),
+                None => {
+                    return Err(
+                        EvaluationError::Custom("cannot reduce an empty list".to_string())
+                            .specify(name),
+                    )
+                }
+            },
+        };
+
+        for item in items {
+            variables.set(acc_arg_index, acc);
+            variables.set(item_arg_index, item.clone());
+
+            acc = lambda.evaluate(index, record, context, Some(&variables))?;
+        }
+
+        return Ok(acc);
+    }
+
+    let arg_name = names.first().unwrap();
     let item_arg_index = variables.register(arg_name);
 
     match op {
@@ -339,5 +609,88 @@ fn runtime_higher_order(
 
             Ok(DynamicValue::from(new_list))
         }
+        HigherOrderOperation::Find => {
+            for item in list.iter() {
+                variables.set(item_arg_index, item.clone());
+
+                let result = lambda.evaluate(index, record, context, Some(&variables))?;
+
+                if result.is_truthy() {
+                    return Ok(item.clone());
+                }
+            }
+
+            Ok(DynamicValue::None)
+        }
+        HigherOrderOperation::Any | HigherOrderOperation::All => {
+            // NOTE: any short-circuits on the first truthy result, all on the
+            // first falsey one.
+            let target = matches!(op, HigherOrderOperation::Any);
+
+            for item in list.iter() {
+                variables.set(item_arg_index, item.clone());
+
+                let result = lambda.evaluate(index, record, context, Some(&variables))?;
+
+                if result.is_truthy() == target {
+                    return Ok(DynamicValue::from(target));
+                }
+            }
+
+            Ok(DynamicValue::from(!target))
+        }
+        HigherOrderOperation::SortBy => {
+            let mut keyed_list = Vec::with_capacity(list.len());
+
+            for item in list.iter() {
+                variables.set(item_arg_index, item.clone());
+
+                let key = lambda.evaluate(index, record, context, Some(&variables))?;
+                keyed_list.push((key, item.clone()));
+            }
+
+            let mut error: Option<EvaluationError> = None;
+
+            keyed_list.sort_by(|(a, _), (b, _)| match a.try_cmp(b) {
+                Ok(ordering) => ordering,
+                Err(err) => {
+                    error.get_or_insert(err);
+                    Ordering::Equal
+                }
+            });
+
+            if let Some(err) = error {
+                return Err(err.specify(name));
+            }
+
+            Ok(DynamicValue::from(
+                keyed_list
+                    .into_iter()
+                    .map(|(_, item)| item)
+                    .collect::<Vec<_>>(),
+            ))
+        }
+        HigherOrderOperation::GroupBy => {
+            let mut groups: HashMap<String, Vec<DynamicValue>> = HashMap::new();
+
+            for item in list.iter() {
+                variables.set(item_arg_index, item.clone());
+
+                let key = lambda.evaluate(index, record, context, Some(&variables))?;
+
+                groups
+                    .entry(String::from_utf8_lossy(&key.serialize_as_bytes()).into_owned())
+                    .or_default()
+                    .push(item.clone());
+            }
+
+            Ok(DynamicValue::from(
+                groups
+                    .into_iter()
+                    .map(|(key, items)| (key, DynamicValue::from(items)))
+                    .collect::<HashMap<_, _>>(),
+            ))
+        }
+        HigherOrderOperation::Reduce | HigherOrderOperation::Fold => unreachable!(),
     }
 }
@@ -18,6 +18,7 @@ use serde::{
     Deserialize, Serialize, Serializer,
 };
 
+use super::decimal::Decimal;
 use super::error::{ConcretizationError, EvaluationError, InvalidArity, SpecifiedEvaluationError};
 use super::parser::Expr;
 use super::utils::downgrade_float;
@@ -393,6 +394,18 @@ impl Arity {
 pub enum DynamicNumber {
     Float(f64),
     Integer(i64),
+    Decimal(Decimal),
+}
+
+// NOTE: arithmetic involving a decimal and an integer remains exact, while
+// arithmetic involving a float always degrades to floats.
+fn decimal_operands(lhs: DynamicNumber, rhs: DynamicNumber) -> Option<(Decimal, Decimal)> {
+    match (lhs, rhs) {
+        (DynamicNumber::Decimal(a), DynamicNumber::Decimal(b)) => Some((a, b)),
+        (DynamicNumber::Decimal(a), DynamicNumber::Integer(b)) => Some((a, Decimal::from(b))),
+        (DynamicNumber::Integer(a), DynamicNumber::Decimal(b)) => Some((Decimal::from(a), b)),
+        _ => None,
+    }
 }
 
 impl DynamicNumber {
@@ -400,6 +413,10 @@ impl DynamicNumber {
         match self {
             Self::Float(n) => Self::Float(n.abs()),
             Self::Integer(n) => Self::Integer(n.abs()),
+            Self::Decimal(n) => match n.checked_abs() {
+                Some(n) => Self::Decimal(n),
+                None => Self::Float(n.to_f64().abs()),
+            },
         }
     }
 
@@ -407,6 +424,7 @@ impl DynamicNumber {
         match self {
             Self::Float(f) => f,
             Self::Integer(i) => i as f64,
+            Self::Decimal(d) => d.to_f64(),
         }
     }
 
@@ -414,13 +432,14 @@ impl DynamicNumber {
         match self {
             Self::Float(f) => f as i64,
             Self::Integer(i) => i,
+            Self::Decimal(d) => d.trunc(),
         }
     }
 
     pub fn is_nan(&self) -> bool {
         match self {
             Self::Float(f) => f.is_nan(),
-            Self::Integer(_) => false,
+            _ => false,
         }
     }
 
@@ -429,19 +448,25 @@ impl DynamicNumber {
     }
 
     pub fn idiv(self, rhs: Self) -> Self {
-        Self::Integer(match self {
-            Self::Integer(a) => match rhs {
-                Self::Integer(b) => return Self::Integer(a / b),
-                Self::Float(b) => (a as f64).div_euclid(b) as i64,
-            },
-            Self::Float(a) => match rhs {
-                Self::Integer(b) => a.div_euclid(b as f64) as i64,
-                Self::Float(b) => a.div_euclid(b) as i64,
-            },
+        if let Some((a, b)) = decimal_operands(self, rhs) {
+            if let Some(quotient) = a.checked_div(b) {
+                return Self::Integer(quotient.floor());
+            }
+        }
+
+        Self::Integer(match (self, rhs) {
+            (Self::Integer(a), Self::Integer(b)) => return Self::Integer(a / b),
+            _ => self.as_float().div_euclid(rhs.as_float()) as i64,
         })
     }
 
     pub fn pow(self, rhs: Self) -> Self {
+        if let (Self::Decimal(n), Self::Integer(e)) = (self, rhs) {
+            if let Some(result) = u32::try_from(e).ok().and_then(|e| n.checked_pow(e)) {
+                return Self::Decimal(result);
+            }
+        }
+
         match rhs {
             Self::Integer(e) => match self {
                 Self::Integer(n) => {
@@ -451,6 +476,7 @@ impl DynamicNumber {
                         DynamicNumber::Float((n as f64).powf(e as f64))
                     }
                 }
+                Self::Decimal(n) => DynamicNumber::Float(n.to_f64().powf(e as f64)),
                 Self::Float(n) => {
                     if e >= i32::MIN as i64 && e <= i32::MAX as i64 {
                         DynamicNumber::Float(n.powi(e as i32))
@@ -462,7 +488,9 @@ impl DynamicNumber {
             Self::Float(e) => match self {
                 DynamicNumber::Integer(n) => DynamicNumber::Float((n as f64).powf(e)),
                 DynamicNumber::Float(n) => DynamicNumber::Float(n.powf(e)),
+                DynamicNumber::Decimal(n) => DynamicNumber::Float(n.to_f64().powf(e)),
             },
+            Self::Decimal(e) => DynamicNumber::Float(self.as_float().powf(e.to_f64())),
         }
     }
 
@@ -473,33 +501,36 @@ impl DynamicNumber {
         match self {
             Self::Integer(a) => Self::Float(callback(a as f64)),
             Self::Float(a) => Self::Float(callback(a)),
+            Self::Decimal(a) => Self::Float(callback(a.to_f64())),
         }
     }
 
-    pub fn map_float_to_int<F>(self, callback: F) -> Self
+    pub fn map_float_to_int<F, G>(self, callback: F, decimal_callback: G) -> Self
     where
         F: Fn(f64) -> f64,
+        G: Fn(Decimal) -> i64,
     {
         match self {
             Self::Integer(_) => self,
             Self::Float(n) => Self::Integer(callback(n) as i64),
+            Self::Decimal(n) => Self::Integer(decimal_callback(n)),
         }
     }
 
     pub fn floor(self) -> Self {
-        self.map_float_to_int(|n| n.floor())
+        self.map_float_to_int(|n| n.floor(), Decimal::floor)
     }
 
     pub fn ceil(self) -> Self {
-        self.map_float_to_int(|n| n.ceil())
+        self.map_float_to_int(|n| n.ceil(), Decimal::ceil)
     }
 
     pub fn trunc(self) -> Self {
-        self.map_float_to_int(|n| n.trunc())
+        self.map_float_to_int(|n| n.trunc(), Decimal::trunc)
     }
 
     pub fn round(self) -> Self {
-        self.map_float_to_int(|n| n.round())
+        self.map_float_to_int(|n| n.round(), Decimal::round)
     }
 
     pub fn ln(self) -> Self {
@@ -515,8 +546,48 @@ impl DynamicNumber {
     }
 }
 
+// NOTE: compact formats, such as the one used to spill aggregation states to
+// disk, are not self-describing. Numbers are therefore serialized with an
+// explicit tag in this case, so that they can be restored exactly.
+#[derive(Deserialize)]
+enum CompactDynamicNumber {
+    Float(f64),
+    Integer(i64),
+    Decimal(Decimal),
+}
+
+impl Serialize for DynamicNumber {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            return match self {
+                Self::Float(n) => n.serialize(serializer),
+                Self::Integer(n) => n.serialize(serializer),
+                Self::Decimal(n) => n.to_string().serialize(serializer),
+            };
+        }
+
+        match self {
+            Self::Float(n) => serializer.serialize_newtype_variant("DynamicNumber", 0, "Float", n),
+            Self::Integer(n) => {
+                serializer.serialize_newtype_variant("DynamicNumber", 1, "Integer", n)
+            }
+            Self::Decimal(n) => {
+                serializer.serialize_newtype_variant("DynamicNumber", 2, "Decimal", n)
+            }
+        }
+    }
+}
+
 impl<'de> Deserialize<'de> for DynamicNumber {
     fn deserialize<D: Deserializer<'de>>(d: D) -> Result<DynamicNumber, D::Error> {
+        if !d.is_human_readable() {
+            return Ok(match CompactDynamicNumber::deserialize(d)? {
+                CompactDynamicNumber::Float(n) => Self::Float(n),
+                CompactDynamicNumber::Integer(n) => Self::Integer(n),
+                CompactDynamicNumber::Decimal(n) => Self::Decimal(n),
+            });
+        }
+
         let raw = String::deserialize(d)?;
 
         raw.parse::<DynamicNumber>()
@@ -529,21 +600,20 @@ impl fmt::Display for DynamicNumber {
         match self {
             Self::Integer(n) => n.fmt(f),
             Self::Float(n) => n.fmt(f),
+            Self::Decimal(n) => n.fmt(f),
         }
     }
 }
 
 impl PartialEq for DynamicNumber {
     fn eq(&self, other: &Self) -> bool {
-        match self {
-            Self::Float(self_value) => match other {
-                Self::Float(other_value) => self_value == other_value,
-                Self::Integer(other_value) => *self_value == (*other_value as f64),
-            },
-            Self::Integer(self_value) => match other {
-                Self::Float(other_value) => (*self_value as f64) == *other_value,
-                Self::Integer(other_value) => self_value == other_value,
-            },
+        if let Some((a, b)) = decimal_operands(*self, *other) {
+            return a == b;
+        }
+
+        match (self, other) {
+            (Self::Integer(self_value), Self::Integer(other_value)) => self_value == other_value,
+            _ => self.as_float() == other.as_float(),
         }
     }
 }
@@ -559,39 +629,44 @@ impl PartialOrd for DynamicNumber {
 impl Ord for DynamicNumber {
     // TODO: NaN is gonna bite us in the buttocks at one point I'm sure..
     fn cmp(&self, other: &Self) -> Ordering {
-        (match self {
-            Self::Float(self_value) => match other {
-                Self::Float(other_value) => self_value.partial_cmp(other_value),
-                Self::Integer(other_value) => self_value.partial_cmp(&(*other_value as f64)),
-            },
-            Self::Integer(self_value) => match other {
-                Self::Float(other_value) => (*self_value as f64).partial_cmp(other_value),
-                Self::Integer(other_value) => Some(self_value.cmp(other_value)),
-            },
+        if let Some((a, b)) = decimal_operands(*self, *other) {
+            return a.cmp(&b);
+        }
+
+        (match (self, other) {
+            (Self::Integer(self_value), Self::Integer(other_value)) => {
+                Some(self_value.cmp(other_value))
+            }
+            _ => self.as_float().partial_cmp(&other.as_float()),
         })
         .unwrap()
     }
 }
 
-fn apply_op<F1, F2>(
+// NOTE: decimal arithmetic overflowing degrades to floats
+fn apply_op<F1, F2, F3>(
     lhs: DynamicNumber,
     rhs: DynamicNumber,
     op_int: F1,
     op_float: F2,
+    op_decimal: F3,
 ) -> DynamicNumber
 where
     F1: FnOnce(i64, i64) -> i64,
     F2: FnOnce(f64, f64) -> f64,
+    F3: FnOnce(Decimal, Decimal) -> Option<Decimal>,
 {
-    match lhs {
-        DynamicNumber::Integer(a) => match rhs {
-            DynamicNumber::Integer(b) => DynamicNumber::Integer(op_int(a, b)),
-            DynamicNumber::Float(b) => DynamicNumber::Float(op_float(a as f64, b)),
-        },
-        DynamicNumber::Float(a) => match rhs {
-            DynamicNumber::Integer(b) => DynamicNumber::Float(op_float(a, b as f64)),
-            DynamicNumber::Float(b) => DynamicNumber::Float(op_float(a, b)),
-        },
+    if let Some((a, b)) = decimal_operands(lhs, rhs) {
+        if let Some(result) = op_decimal(a, b) {
+            return DynamicNumber::Decimal(result);
+        }
+    }
+
+    match (lhs, rhs) {
+        (DynamicNumber::Integer(a), DynamicNumber::Integer(b)) => {
+            DynamicNumber::Integer(op_int(a, b))
+        }
+        _ => DynamicNumber::Float(op_float(lhs.as_float(), rhs.as_float())),
     }
 }
 
@@ -602,6 +677,10 @@ impl Neg for DynamicNumber {
         match self {
             Self::Float(v) => DynamicNumber::Float(-v),
             Self::Integer(v) => DynamicNumber::Integer(-v),
+            Self::Decimal(v) => match v.checked_neg() {
+                Some(v) => DynamicNumber::Decimal(v),
+                None => DynamicNumber::Float(-v.to_f64()),
+            },
         }
     }
 }
@@ -610,7 +689,13 @@ impl Rem for DynamicNumber {
     type Output = Self;
 
     fn rem(self, rhs: Self) -> Self::Output {
-        apply_op(self, rhs, Rem::<i64>::rem, Rem::<f64>::rem)
+        apply_op(
+            self,
+            rhs,
+            Rem::<i64>::rem,
+            Rem::<f64>::rem,
+            Decimal::checked_rem,
+        )
     }
 }
 
@@ -618,21 +703,23 @@ impl Add for DynamicNumber {
     type Output = Self;
 
     fn add(self, rhs: Self) -> Self::Output {
-        apply_op(self, rhs, Add::<i64>::add, Add::<f64>::add)
+        apply_op(
+            self,
+            rhs,
+            Add::<i64>::add,
+            Add::<f64>::add,
+            Decimal::checked_add,
+        )
     }
 }
 
 impl AddAssign for DynamicNumber {
     fn add_assign(&mut self, rhs: Self) {
-        match self {
-            DynamicNumber::Float(a) => match rhs {
-                DynamicNumber::Float(b) => *a += b,
-                DynamicNumber::Integer(b) => *a += b as f64,
-            },
-            DynamicNumber::Integer(a) => match rhs {
-                DynamicNumber::Float(b) => *self = DynamicNumber::Float((*a as f64) + b),
-                DynamicNumber::Integer(b) => *a += b,
-            },
+        match (&mut *self, rhs) {
+            (DynamicNumber::Float(a), DynamicNumber::Float(b)) => *a += b,
+            (DynamicNumber::Float(a), DynamicNumber::Integer(b)) => *a += b as f64,
+            (DynamicNumber::Integer(a), DynamicNumber::Integer(b)) => *a += b,
+            _ => *self = *self + rhs,
         };
     }
 }
@@ -641,7 +728,13 @@ impl Sub for DynamicNumber {
     type Output = Self;
 
     fn sub(self, rhs: Self) -> Self::Output {
-        apply_op(self, rhs, Sub::<i64>::sub, Sub::<f64>::sub)
+        apply_op(
+            self,
+            rhs,
+            Sub::<i64>::sub,
+            Sub::<f64>::sub,
+            Decimal::checked_sub,
+        )
     }
 }
 
@@ -649,7 +742,13 @@ impl Mul for DynamicNumber {
     type Output = Self;
 
     fn mul(self, rhs: Self) -> Self::Output {
-        apply_op(self, rhs, Mul::<i64>::mul, Mul::<f64>::mul)
+        apply_op(
+            self,
+            rhs,
+            Mul::<i64>::mul,
+            Mul::<f64>::mul,
+            Decimal::checked_mul,
+        )
     }
 }
 
@@ -657,16 +756,13 @@ impl Div for DynamicNumber {
     type Output = Self;
 
     fn div(self, rhs: Self) -> Self::Output {
-        DynamicNumber::Float(match self {
-            DynamicNumber::Integer(a) => match rhs {
-                DynamicNumber::Integer(b) => a as f64 / b as f64,
-                DynamicNumber::Float(b) => a as f64 / b,
-            },
-            DynamicNumber::Float(a) => match rhs {
-                DynamicNumber::Integer(b) => a / b as f64,
-                DynamicNumber::Float(b) => a / b,
-            },
-        })
+        if let Some((a, b)) = decimal_operands(self, rhs) {
+            if let Some(quotient) = a.checked_div(b) {
+                return DynamicNumber::Decimal(quotient);
+            }
+        }
+
+        DynamicNumber::Float(self.as_float() / rhs.as_float())
     }
 }
 
@@ -702,10 +798,7 @@ impl TryFrom<&[u8]> for DynamicNumber {
 
 impl numfmt::Numeric for DynamicNumber {
     fn to_f64(&self) -> f64 {
-        match self {
-            Self::Float(n) => *n,
-            Self::Integer(n) => *n as f64,
-        }
+        self.as_float()
     }
 }
 
@@ -720,6 +813,7 @@ pub enum DynamicValue {
     Bytes(Arc<BString>),
     Float(f64),
     Integer(i64),
+    Decimal(Decimal),
     Boolean(bool),
     Regex(Arc<Regex>),
     DateTime(Box<Zoned>),
@@ -747,14 +841,59 @@ impl Default for DynamicValue {
     }
 }
 
+// NOTE: same as CompactDynamicNumber, variants must remain in the same order
+// as the ones serialized by DynamicValue.
+#[derive(Deserialize)]
+enum CompactDynamicValue {
+    List(Vec<DynamicValue>),
+    Map(HashMap<String, DynamicValue>),
+    String(String),
+    Bytes(Vec<u8>),
+    Float(f64),
+    Integer(i64),
+    Decimal(Decimal),
+    Boolean(bool),
+    Regex(String),
+    DateTime(Zoned),
+    None,
+}
+
 impl Serialize for DynamicValue {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
+        if !serializer.is_human_readable() {
+            let name = "DynamicValue";
+
+            return match self {
+                Self::List(v) => serializer.serialize_newtype_variant(name, 0, "List", &**v),
+                Self::Map(v) => serializer.serialize_newtype_variant(name, 1, "Map", &**v),
+                Self::String(v) => {
+                    serializer.serialize_newtype_variant(name, 2, "String", v.as_str())
+                }
+                Self::Bytes(v) => {
+                    serializer.serialize_newtype_variant(name, 3, "Bytes", v.as_slice())
+                }
+                Self::Float(v) => serializer.serialize_newtype_variant(name, 4, "Float", v),
+                Self::Integer(v) => serializer.serialize_newtype_variant(name, 5, "Integer", v),
+                Self::Decimal(v) => serializer.serialize_newtype_variant(name, 6, "Decimal", v),
+                Self::Boolean(v) => serializer.serialize_newtype_variant(name, 7, "Boolean", v),
+                Self::Regex(v) => {
+                    serializer.serialize_newtype_variant(name, 8, "Regex", v.as_str())
+                }
+                Self::DateTime(v) => {
+                    serializer.serialize_newtype_variant(name, 9, "DateTime", &**v)
+                }
+                Self::None => serializer.serialize_unit_variant(name, 10, "None"),
+            };
+        }
+
         match self {
             Self::Float(v) => v.serialize(serializer),
             Self::Integer(v) => v.serialize(serializer),
+            // NOTE: decimals are serialized as strings to remain exact
+            Self::Decimal(v) => v.to_string().serialize(serializer),
             Self::Boolean(v) => v.serialize(serializer),
             Self::String(v) => v.serialize(serializer),
             Self::Bytes(v) => v.serialize(serializer),
@@ -776,6 +915,24 @@ impl<'de> Deserialize<'de> for DynamicValue {
     where
         D: Deserializer<'de>,
     {
+        if !deserializer.is_human_readable() {
+            return Ok(match CompactDynamicValue::deserialize(deserializer)? {
+                CompactDynamicValue::List(v) => Self::from(v),
+                CompactDynamicValue::Map(v) => Self::Map(Arc::new(v)),
+                CompactDynamicValue::String(v) => Self::from(v),
+                CompactDynamicValue::Bytes(v) => Self::Bytes(Arc::new(BString::from(v))),
+                CompactDynamicValue::Float(v) => Self::Float(v),
+                CompactDynamicValue::Integer(v) => Self::Integer(v),
+                CompactDynamicValue::Decimal(v) => Self::Decimal(v),
+                CompactDynamicValue::Boolean(v) => Self::Boolean(v),
+                CompactDynamicValue::Regex(v) => {
+                    Self::Regex(Arc::new(Regex::new(&v).map_err(D::Error::custom)?))
+                }
+                CompactDynamicValue::DateTime(v) => Self::DateTime(Box::new(v)),
+                CompactDynamicValue::None => Self::None,
+            });
+        }
+
         struct ValueVisitor;
 
         impl<'de> Visitor<'de> for ValueVisitor {
@@ -890,6 +1047,7 @@ impl DynamicValue {
             Self::Bytes(_) => "bytes",
             Self::Float(_) => "float",
             Self::Integer(_) => "integer",
+            Self::Decimal(_) => "decimal",
             Self::Boolean(_) => "boolean",
             Self::DateTime(_) => "datetime",
             Self::Regex(_) => "regex",
@@ -935,6 +1093,7 @@ impl DynamicValue {
             Self::Bytes(value) => Cow::Borrowed(value),
             Self::Float(value) => Cow::Owned(value.to_string().into_bytes()),
             Self::Integer(value) => Cow::Owned(value.to_string().into_bytes()),
+            Self::Decimal(value) => Cow::Owned(value.to_string().into_bytes()),
             Self::Boolean(value) => Cow::Borrowed(if *value { b"true" } else { b"false" }),
             Self::DateTime(value) => Cow::Owned(
                 value
@@ -987,6 +1146,7 @@ impl DynamicValue {
             ),
             Self::Float(value) => Cow::Owned(value.to_string()),
             Self::Integer(value) => Cow::Owned(value.to_string()),
+            Self::Decimal(value) => Cow::Owned(value.to_string()),
             Self::DateTime(value) => Cow::Owned(value.to_string()),
             Self::Boolean(value) => Cow::Borrowed(if *value { "true" } else { "false" }),
             Self::Regex(pattern) => Cow::Borrowed(pattern.as_str()),
@@ -1043,6 +1203,7 @@ impl DynamicValue {
             },
             Self::Integer(value) => DynamicNumber::Integer(*value),
             Self::Float(value) => DynamicNumber::Float(*value),
+            Self::Decimal(value) => DynamicNumber::Decimal(*value),
             Self::Boolean(value) => DynamicNumber::Integer(*value as i64),
             _ => return Err(EvaluationError::from_cast(self, "number")),
         })
@@ -1075,6 +1236,9 @@ impl DynamicValue {
                     return Err(EvaluationError::from_cast(self, "unsigned_number"));
                 }
             }
+            Self::Decimal(value) if value.normalize().scale() == 0 && value.trunc() >= 0 => {
+                value.trunc() as usize
+            }
             Self::Boolean(value) => (*value) as usize,
             _ => return Err(EvaluationError::from_cast(self, "unsigned_number")),
         })
@@ -1095,6 +1259,7 @@ impl DynamicValue {
                 None => return Err(EvaluationError::from_cast(self, "integer")),
             },
             Self::Integer(value) => *value,
+            Self::Decimal(value) if value.normalize().scale() == 0 => value.trunc(),
             Self::Boolean(value) => (*value) as i64,
             _ => return Err(EvaluationError::from_cast(self, "integer")),
         })
@@ -1112,6 +1277,7 @@ impl DynamicValue {
             },
             Self::Float(value) => *value,
             Self::Integer(value) => *value as f64,
+            Self::Decimal(value) => value.to_f64(),
             Self::Boolean(value) => *value as usize as f64,
             _ => return Err(EvaluationError::from_cast(self, "float")),
         })
@@ -1125,6 +1291,7 @@ impl DynamicValue {
             Self::Bytes(value) => !value.is_empty(),
             Self::Float(value) => value == &0.0,
             Self::Integer(value) => value != &0,
+            Self::Decimal(value) => !value.is_zero(),
             Self::Boolean(value) => *value,
             Self::Regex(pattern) => !pattern.as_str().is_empty(),
             Self::DateTime(_) => true,
@@ -1148,6 +1315,34 @@ impl DynamicValue {
     pub fn flat_iter(&self) -> DynamicValueFlatIter {
         DynamicValueFlatIter::new(self)
     }
+
+    // NOTE: values that can be understood as numbers are compared numerically,
+    // datetimes are compared chronologically, lists are compared item by item
+    // and everything else is compared lexicographically as strings. None is
+    // always considered lower than anything else.
+    pub fn try_cmp(&self, other: &Self) -> Result<Ordering, EvaluationError> {
+        Ok(match (self, other) {
+            (Self::None, Self::None) => Ordering::Equal,
+            (Self::None, _) => Ordering::Less,
+            (_, Self::None) => Ordering::Greater,
+            (Self::DateTime(a), Self::DateTime(b)) => a.cmp(b),
+            (Self::List(a), Self::List(b)) => {
+                for (x, y) in a.iter().zip(b.iter()) {
+                    let ordering = x.try_cmp(y)?;
+
+                    if ordering.is_ne() {
+                        return Ok(ordering);
+                    }
+                }
+
+                a.len().cmp(&b.len())
+            }
+            _ => match (self.try_as_number(), other.try_as_number()) {
+                (Ok(a), Ok(b)) => a.cmp(&b),
+                _ => self.try_as_str()?.cmp(&other.try_as_str()?),
+            },
+        })
+    }
 }
 
 pub struct DynamicValueFlatIter<'a> {
@@ -1279,10 +1474,17 @@ impl From<DynamicNumber> for DynamicValue {
         match value {
             DynamicNumber::Integer(value) => DynamicValue::Integer(value),
             DynamicNumber::Float(value) => DynamicValue::Float(value),
+            DynamicNumber::Decimal(value) => DynamicValue::Decimal(value),
         }
     }
 }
 
+impl From<Decimal> for DynamicValue {
+    fn from(value: Decimal) -> Self {
+        DynamicValue::Decimal(value)
+    }
+}
+
 impl From<Zoned> for DynamicValue {
     fn from(value: Zoned) -> Self {
         DynamicValue::DateTime(Box::new(value))
@@ -1310,6 +1512,7 @@ impl PartialEq for DynamicValue {
             (Self::Bytes(a), Self::Bytes(b)) => a == b,
             (Self::Float(a), Self::Float(b)) => a == b,
             (Self::Integer(a), Self::Integer(b)) => a == b,
+            (Self::Decimal(a), Self::Decimal(b)) => a == b,
             (Self::List(a), Self::List(b)) => a == b,
             (Self::DateTime(a), Self::DateTime(b)) => a == b,
             (Self::None, Self::None) => true,
@@ -1566,4 +1769,64 @@ mod tests {
             DynamicNumber::Integer(4)
         );
     }
+
+    #[test]
+    fn test_dynamic_number_decimal_overflow() {
+        let min = DynamicNumber::Decimal(Decimal::new(i64::MIN, 2));
+
+        assert_eq!(min.abs(), DynamicNumber::Float(92233720368547758.08));
+        assert_eq!(-min, DynamicNumber::Float(92233720368547758.08));
+    }
+
+    #[test]
+    fn test_dynamic_value_decimal_serialization() {
+        let value = DynamicValue::Decimal("0.1".parse().unwrap());
+        let list = DynamicValue::from(vec![value.clone()]);
+
+        assert_eq!(serde_json::to_string(&value).unwrap(), "\"0.1\"");
+        assert_eq!(serde_json::to_string(&list).unwrap(), "[\"0.1\"]");
+    }
+
+    #[test]
+    fn test_compact_serialization() {
+        fn roundtrip<T: Serialize + for<'de> Deserialize<'de>>(value: &T) -> T {
+            bincode::deserialize(&bincode::serialize(value).unwrap()).unwrap()
+        }
+
+        let numbers = vec![
+            DynamicNumber::Float(1.0),
+            DynamicNumber::Integer(1),
+            DynamicNumber::Decimal("-12.50".parse().unwrap()),
+        ];
+
+        for number in numbers {
+            let restored = roundtrip(&number);
+
+            assert_eq!(restored, number);
+            assert_eq!(restored.to_string(), number.to_string());
+        }
+
+        let values = vec![
+            DynamicValue::None,
+            DynamicValue::Boolean(true),
+            DynamicValue::Float(1.0),
+            DynamicValue::Integer(1),
+            DynamicValue::Decimal("0.10".parse().unwrap()),
+            DynamicValue::from("test"),
+            DynamicValue::Bytes(Arc::new(BString::from("test"))),
+            DynamicValue::from(vec![DynamicValue::Integer(1), DynamicValue::from("two")]),
+            DynamicValue::from(
+                "2024-01-01T12:00:00+01:00[Europe/Paris]"
+                    .parse::<Zoned>()
+                    .unwrap(),
+            ),
+        ];
+
+        for value in values {
+            let restored = roundtrip(&value);
+
+            assert_eq!(restored.type_of(), value.type_of());
+            assert_eq!(restored.serialize_as_bytes(), value.serialize_as_bytes());
+        }
+    }
 }
@@ -0,0 +1,324 @@
+// NOTE: a best-effort static analysis pass over concrete expressions, whose
+// goal is to report obvious type errors, e.g. `add(a, [1, 2])`, before
+// reading any data, instead of failing on the first row. The kind of values
+// that cannot be known statically, such as column cells, is assumed to be
+// anything, so that only certain mismatches are ever reported.
+use super::error::{ConcretizationError, TypeMismatch};
+use super::interpreter::ConcreteExpr;
+use super::types::DynamicValue;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Kind {
+    Any,
+    Null,
+    Boolean,
+    Number,
+    String,
+    List,
+    Map,
+    Regex,
+    DateTime,
+    Lambda,
+}
+
+impl Kind {
+    fn of(value: &DynamicValue) -> Self {
+        match value {
+            DynamicValue::None => Self::Null,
+            DynamicValue::Boolean(_) => Self::Boolean,
+            DynamicValue::Integer(_) | DynamicValue::Float(_) | DynamicValue::Decimal(_) => {
+                Self::Number
+            }
+            DynamicValue::String(_) | DynamicValue::Bytes(_) => Self::String,
+            DynamicValue::List(_) => Self::List,
+            DynamicValue::Map(_) => Self::Map,
+            DynamicValue::Regex(_) => Self::Regex,
+            DynamicValue::DateTime(_) => Self::DateTime,
+        }
+    }
+
+    fn as_str(&self) -> &str {
+        match self {
+            Self::Any => "any",
+            Self::Null => "null",
+            Self::Boolean => "boolean",
+            Self::Number => "number",
+            Self::String => "string",
+            Self::List => "list",
+            Self::Map => "map",
+            Self::Regex => "regex",
+            Self::DateTime => "datetime",
+            Self::Lambda => "lambda",
+        }
+    }
+
+    fn union(self, other: Self) -> Self {
+        match (self, other) {
+            (a, b) if a == b => a,
+            (Self::Null, kind) | (kind, Self::Null) => kind,
+            _ => Self::Any,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Expected {
+    Anything,
+    Number,
+    String,
+    List,
+    Map,
+}
+
+use Expected::{Anything as A, List as L, Map as M, Number as N, String as S};
+
+impl Expected {
+    fn as_str(&self) -> &str {
+        match self {
+            Self::Anything => "any",
+            Self::Number => "number",
+            Self::String => "string",
+            Self::List => "list",
+            Self::Map => "map",
+        }
+    }
+
+    // NOTE: strings may contain numbers, and null values are often used
+    // as placeholders, so we cannot say anything about them.
+    fn accepts(&self, kind: Kind) -> bool {
+        if matches!(kind, Kind::Any | Kind::Null) {
+            return true;
+        }
+
+        match self {
+            Self::Anything => true,
+            Self::Number => matches!(kind, Kind::Number | Kind::Boolean | Kind::String),
+            Self::String => !matches!(kind, Kind::List | Kind::Map | Kind::Lambda),
+            Self::List => kind == Kind::List,
+            Self::Map => kind == Kind::Map,
+        }
+    }
+
+    // Literal values can be checked precisely
+    fn accepts_value(&self, value: &DynamicValue) -> bool {
+        match (self, value) {
+            (_, DynamicValue::None) => true,
+            (Self::Number, _) => value.try_as_number().is_ok(),
+            _ => self.accepts(Kind::of(value)),
+        }
+    }
+}
+
+// Return the expected kind of arguments (the last one being repeated for
+// variadic functions) and the kind of the returned value, for the functions
+// whose signature is known.
+fn get_signature(name: &str) -> Option<(&'static [Expected], Kind)> {
+    Some(match name {
+        "add" | "sub" | "mul" | "div" | "idiv" | "pow" | "mod" | "neg" | "abs" | "ceil"
+        | "floor" | "round" | "trunc" | "sqrt" | "log" | "haversine" => (&[N], Kind::Number),
+        "==" | "!=" | "<" | "<=" | ">" | ">=" | "eq" | "ne" | "lt" | "le" | "gt" | "ge" | "not"
+        | "contains" | "isfile" | "is_ip" | "in_cidr" | "in_bbox" | "in_polygon"
+        | "is_shortened_url" => (&[A], Kind::Boolean),
+        "startswith" | "endswith" => (&[S], Kind::Boolean),
+        "len"
+        | "count"
+        | "count_matches"
+        | "levenshtein"
+        | "damerau_levenshtein"
+        | "jaro_winkler"
+        | "jaccard"
+        | "dice"
+        | "timestamp"
+        | "timestamp_ms" => (&[A], Kind::Number),
+        "lower"
+        | "upper"
+        | "trim"
+        | "ltrim"
+        | "rtrim"
+        | "unidecode"
+        | "casefold"
+        | "collapse_whitespace"
+        | "unicode_normalize"
+        | "html_to_text"
+        | "html_unescape"
+        | "html_escape"
+        | "strip_tags"
+        | "escape_regex"
+        | "fingerprint"
+        | "urlencode"
+        | "urldecode"
+        | "md5"
+        | "sha1"
+        | "sha256" => (&[S], Kind::String),
+        "replace" => (&[S, A, S], Kind::String),
+        "fmt" | "format" | "typeof" | "numfmt" | "uuid" | "strftime" | "geohash"
+        | "anonymize_ip" => (&[A], Kind::String),
+        // NOTE: concat works on strings as well as lists
+        "concat" => (&[A], Kind::Any),
+        "join" => (&[L, S], Kind::String),
+        "split" | "find_all" | "html_links" => (&[S, A], Kind::List),
+        "compact" | "flatten" | "sort" | "unique" | "enumerate" | "zip" => (&[L], Kind::List),
+        "keys" | "values" => (&[M], Kind::List),
+        "range" | "geohash_decode" => (&[A], Kind::List),
+        "parse_url" | "parse_ip" => (&[S], Kind::Map),
+        "datetime" => (&[A], Kind::DateTime),
+        "decimal" => (&[A], Kind::Number),
+        _ => return None,
+    })
+}
+
+fn check_arguments(name: &str, args: &[ConcreteExpr]) -> Result<Kind, ConcretizationError> {
+    let kinds = args.iter().map(check).collect::<Result<Vec<_>, _>>()?;
+
+    let (expected_args, returned) = match get_signature(name) {
+        None => return Ok(Kind::Any),
+        Some(signature) => signature,
+    };
+
+    for (i, (arg, kind)) in args.iter().zip(kinds).enumerate() {
+        let expected = expected_args[i.min(expected_args.len() - 1)];
+
+        let ok = match arg {
+            ConcreteExpr::Value(value) => expected.accepts_value(value),
+            _ => expected.accepts(kind),
+        };
+
+        if !ok {
+            let got = match arg {
+                ConcreteExpr::Value(value) => Kind::of(value),
+                _ => kind,
+            };
+
+            return Err(ConcretizationError::TypeMismatch(
+                name.to_string(),
+                TypeMismatch::new(i, expected.as_str(), got.as_str()),
+            ));
+        }
+    }
+
+    Ok(returned)
+}
+
+// Infer the kind of value an expression will produce, while checking that
+// the functions it calls are given arguments of compatible kinds.
+pub fn check(expr: &ConcreteExpr) -> Result<Kind, ConcretizationError> {
+    Ok(match expr {
+        ConcreteExpr::Value(value) => Kind::of(value),
+        ConcreteExpr::Column(_) | ConcreteExpr::LambdaBinding(_) => Kind::Any,
+        ConcreteExpr::Lambda(_, body) => {
+            check(body)?;
+            Kind::Lambda
+        }
+        ConcreteExpr::List(items) => {
+            for item in items {
+                check(item)?;
+            }
+
+            Kind::List
+        }
+        ConcreteExpr::Map(pairs) => {
+            for (_, value) in pairs {
+                check(value)?;
+            }
+
+            Kind::Map
+        }
+        ConcreteExpr::Call(call) => check_arguments(&call.name, &call.args)?,
+        ConcreteExpr::SpecialCall(call) => {
+            let kinds = call.args.iter().map(check).collect::<Result<Vec<_>, _>>()?;
+
+            match call.name.as_str() {
+                "if" | "unless" => kinds[1..]
+                    .iter()
+                    .copied()
+                    .reduce(Kind::union)
+                    .map(|kind| {
+                        // NOTE: missing else branch yields null
+                        if kinds.len() == 2 {
+                            kind.union(Kind::Null)
+                        } else {
+                            kind
+                        }
+                    })
+                    .unwrap_or(Kind::Any),
+                "map" | "filter" | "sort_by" => Kind::List,
+                "any" | "all" => Kind::Boolean,
+                "group_by" => Kind::Map,
+                _ => Kind::Any,
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use csv::ByteRecord;
+
+    use crate::moonblade::interpreter::concretize_expression;
+    use crate::moonblade::parser::parse_expression;
+
+    fn check_code(code: &str) -> Result<Kind, ConcretizationError> {
+        let headers = ByteRecord::from(vec!["a", "b"]);
+        let expr = concretize_expression(parse_expression(code).unwrap(), &headers)?;
+
+        check(&expr)
+    }
+
+    fn mismatch(name: &str, position: usize, expected: &str, got: &str) -> ConcretizationError {
+        ConcretizationError::TypeMismatch(
+            name.to_string(),
+            TypeMismatch::new(position, expected, got),
+        )
+    }
+
+    #[test]
+    fn test_inference() {
+        assert_eq!(check_code("a + 1"), Ok(Kind::Number));
+        assert_eq!(check_code("upper(a)"), Ok(Kind::String));
+        assert_eq!(check_code("split(a, ',')"), Ok(Kind::List));
+        assert_eq!(check_code("a == b"), Ok(Kind::Boolean));
+        assert_eq!(check_code("if(a, 1, 2 + b)"), Ok(Kind::Number));
+        assert_eq!(check_code("if(a, 1, upper(b))"), Ok(Kind::Any));
+        assert_eq!(check_code("get(a, 1)"), Ok(Kind::Any));
+        assert_eq!(check_code("map(split(a, ','), x => x + 1)"), Ok(Kind::List));
+    }
+
+    #[test]
+    fn test_mismatches() {
+        assert_eq!(
+            check_code("a + [b]"),
+            Err(mismatch("add", 1, "number", "list"))
+        );
+        assert_eq!(
+            check_code("a * 'test'"),
+            Err(mismatch("mul", 1, "number", "string"))
+        );
+        assert_eq!(
+            check_code("split(a, ',') + 1"),
+            Err(mismatch("add", 0, "number", "list"))
+        );
+        assert_eq!(
+            check_code("join(upper(a), '|')"),
+            Err(mismatch("join", 0, "list", "string"))
+        );
+        assert_eq!(
+            check_code("keys(split(a, ','))"),
+            Err(mismatch("keys", 0, "map", "list"))
+        );
+        assert_eq!(
+            check_code("map(split(a, ','), x => x + {b: 1})"),
+            Err(mismatch("add", 1, "number", "map"))
+        );
+        assert_eq!(
+            check_code("lower(split(a, ','))"),
+            Err(mismatch("lower", 0, "string", "list"))
+        );
+        assert!(check_code("a + '3'").is_ok());
+        assert!(check_code("a + b? + null").is_ok());
+        assert!(check_code("join(split(a, ','), '|')").is_ok());
+        assert!(check_code("join(concat(split(a, ','), b), '|')").is_ok());
+        assert!(check_code("len(a) * true").is_ok());
+    }
+}
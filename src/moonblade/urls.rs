@@ -0,0 +1,448 @@
+// NOTE: helpers used by the url-related functions. Most of the heavy lifting
+// regarding parsing is done by the `url` crate, while registered domains
+// are computed using the public suffix list (https://publicsuffix.org/).
+// The list is vendored in data/public_suffix_list.dat and can be updated by
+// downloading https://publicsuffix.org/list/public_suffix_list.dat again.
+use std::collections::HashSet;
+
+use lazy_static::lazy_static;
+use url::{form_urlencoded, Host, Url};
+
+lazy_static! {
+    static ref PUBLIC_SUFFIX_LIST: PublicSuffixList =
+        PublicSuffixList::parse(include_str!("data/public_suffix_list.dat"));
+}
+
+// Ref: https://github.com/publicsuffix/list/wiki/Format
+#[derive(Debug, Default)]
+struct PublicSuffixList {
+    rules: HashSet<String>,
+    // NOTE: wildcard rules, e.g. "*.ck", are stored without their "*." prefix
+    wildcards: HashSet<String>,
+    // NOTE: exception rules, e.g. "!www.ck", are stored without their "!" prefix
+    exceptions: HashSet<String>,
+}
+
+impl PublicSuffixList {
+    fn parse(string: &str) -> Self {
+        let mut list = Self::default();
+
+        for line in string.lines() {
+            let rule = line.split_whitespace().next().unwrap_or("");
+
+            if rule.is_empty() || rule.starts_with("//") {
+                continue;
+            }
+
+            let (set, rule) = if let Some(rule) = rule.strip_prefix("*.") {
+                (&mut list.wildcards, rule)
+            } else if let Some(rule) = rule.strip_prefix('!') {
+                (&mut list.exceptions, rule)
+            } else {
+                (&mut list.rules, rule)
+            };
+
+            // NOTE: hosts given by the url crate are punycode-encoded, while
+            // some rules are written in unicode
+            let rule = if rule.is_ascii() {
+                rule.to_string()
+            } else {
+                match Host::parse(rule) {
+                    Ok(Host::Domain(domain)) => domain,
+                    _ => continue,
+                }
+            };
+
+            set.insert(rule);
+        }
+
+        list
+    }
+
+    // NOTE: returns the number of labels of the public suffix of given host,
+    // whose labels start at given byte offsets, using the longest matching
+    // rule, exceptions taking precedence. Unlisted suffixes are considered to
+    // be made of a single label, as per the implicit "*" rule.
+    fn suffix_len(&self, host: &str, starts: &[usize]) -> usize {
+        let labels_count = starts.len();
+
+        for (i, start) in starts.iter().enumerate() {
+            let candidate = &host[*start..];
+
+            if self.exceptions.contains(candidate) {
+                return labels_count - i - 1;
+            }
+
+            if self.rules.contains(candidate) {
+                return labels_count - i;
+            }
+
+            if let Some(parent) = starts.get(i + 1) {
+                if self.wildcards.contains(&host[*parent..]) {
+                    return labels_count - i;
+                }
+            }
+        }
+
+        1
+    }
+
+    fn registered_domain<'a>(&self, host: &'a str) -> Option<&'a str> {
+        let starts = std::iter::once(0)
+            .chain(host.match_indices('.').map(|(i, _)| i + 1))
+            .collect::<Vec<_>>();
+
+        let suffix_len = self.suffix_len(host, &starts);
+
+        if starts.len() <= suffix_len {
+            return None;
+        }
+
+        Some(&host[starts[starts.len() - suffix_len - 1]..])
+    }
+}
+
+static TRACKING_QUERY_PARAMS: [&str; 12] = [
+    "_ga", "_hsenc", "dclid", "fbclid", "gclid", "igshid", "mc_cid", "mc_eid", "mkt_tok",
+    "msclkid", "xtor", "yclid",
+];
+
+static SHORTENER_DOMAINS: [&str; 24] = [
+    "amzn.to",
+    "bit.do",
+    "bit.ly",
+    "bitly.com",
+    "buff.ly",
+    "cutt.ly",
+    "dlvr.it",
+    "fb.me",
+    "goo.gl",
+    "ift.tt",
+    "is.gd",
+    "j.mp",
+    "lnkd.in",
+    "ow.ly",
+    "rebrand.ly",
+    "shorturl.at",
+    "snip.ly",
+    "t.co",
+    "t.ly",
+    "tiny.cc",
+    "tinyurl.com",
+    "trib.al",
+    "wp.me",
+    "youtu.be",
+];
+
+// NOTE: returns whether the given string starts with something looking like
+// "host:port", e.g. "localhost:8080/path", which the url crate would otherwise
+// happily parse as an url whose scheme is "localhost".
+fn starts_with_host_and_port(string: &str) -> bool {
+    let Some((_, rest)) = string.split_once(':') else {
+        return false;
+    };
+
+    let port_len = rest.bytes().take_while(u8::is_ascii_digit).count();
+
+    port_len > 0
+        && matches!(
+            rest.as_bytes().get(port_len),
+            None | Some(b'/' | b'?' | b'#')
+        )
+}
+
+// NOTE: urls found in the wild often lack their scheme, e.g. "lemonde.fr/path"
+// or "localhost:8080/path", in which case we assume http.
+pub fn parse(string: &str) -> Option<Url> {
+    let string = string.trim();
+
+    match Url::parse(string) {
+        Ok(url) if starts_with_host_and_port(string) => {
+            Url::parse(&format!("http://{}", string)).ok().or(Some(url))
+        }
+        Ok(url) => Some(url),
+        Err(url::ParseError::RelativeUrlWithoutBase) => {
+            Url::parse(&format!("http://{}", string)).ok()
+        }
+        Err(_) => None,
+    }
+}
+
+fn strip_www(host: &str) -> &str {
+    host.strip_prefix("www.").unwrap_or(host)
+}
+
+fn is_tracking_query_param(name: &str) -> bool {
+    name.starts_with("utm_") || TRACKING_QUERY_PARAMS.contains(&name)
+}
+
+pub fn registered_domain(url: &Url) -> Option<String> {
+    match url.host()? {
+        Host::Domain(host) => PUBLIC_SUFFIX_LIST.registered_domain(host).map(String::from),
+        _ => None,
+    }
+}
+
+pub fn normalize(mut url: Url) -> String {
+    url.set_fragment(None);
+
+    // NOTE: we filter the raw query segments rather than re-encoding decoded
+    // pairs, so that untouched parameters are kept exactly as they were.
+    if let Some(query) = url.query() {
+        let segments: Vec<&str> = query.split('&').collect();
+
+        let kept: Vec<&str> = segments
+            .iter()
+            .copied()
+            .filter(|segment| {
+                !form_urlencoded::parse(segment.as_bytes())
+                    .next()
+                    .is_some_and(|(name, _)| is_tracking_query_param(&name))
+            })
+            .collect();
+
+        if kept.len() != segments.len() {
+            if kept.is_empty() {
+                url.set_query(None);
+            } else {
+                let filtered = kept.join("&");
+                url.set_query(Some(&filtered));
+            }
+        }
+    }
+
+    if let Some(Host::Domain(host)) = url.host() {
+        let stripped = strip_www(host).to_string();
+
+        if stripped.len() != host.len() {
+            // NOTE: this cannot fail since we are removing a label from a valid host
+            url.set_host(Some(&stripped)).unwrap();
+        }
+    }
+
+    let path = url.path();
+
+    if path.len() > 1 && path.ends_with('/') {
+        let trimmed = path.trim_end_matches('/').to_string();
+        url.set_path(&trimmed);
+    }
+
+    let mut normalized = String::from(url.as_str());
+
+    if url.path() == "/" && url.query().is_none() {
+        normalized.pop();
+    }
+
+    normalized
+}
+
+pub fn is_shortened(url: &Url) -> bool {
+    match url.host_str() {
+        Some(host) => SHORTENER_DOMAINS.contains(&strip_www(host)),
+        None => false,
+    }
+}
+
+fn query_param(url: &Url, name: &str) -> Option<String> {
+    url.query_pairs()
+        .find(|(k, _)| k == name)
+        .map(|(_, v)| v.into_owned())
+}
+
+// NOTE: this only resolves redirections that can be inferred from the url
+// itself, without needing to perform any network request.
+pub fn infer_redirection(url: &Url) -> Option<Url> {
+    let host = strip_www(url.host_str()?);
+    let path = url.path();
+
+    let target = match host {
+        "youtu.be" => {
+            let id = path.trim_start_matches('/');
+
+            if id.is_empty() || id.contains('/') {
+                return None;
+            }
+
+            format!("https://www.youtube.com/watch?v={}", id)
+        }
+        "l.facebook.com" | "lm.facebook.com" | "l.instagram.com"
+            if path == "/l.php" || path == "/" =>
+        {
+            query_param(url, "u")?
+        }
+        "t.umblr.com" if path == "/redirect" => query_param(url, "z")?,
+        "out.reddit.com" => query_param(url, "url")?,
+        "youtube.com" if path == "/redirect" => query_param(url, "q")?,
+        _ if path == "/url" && host.split('.').any(|label| label == "google") => {
+            query_param(url, "q").or_else(|| query_param(url, "url"))?
+        }
+        _ => return None,
+    };
+
+    Url::parse(&target).ok()
+}
+
+// NOTE: LRUs are a reversed and hierarchical representation of urls, useful
+// to group urls by prefix, e.g. "s:http|h:com|h:example|p:path|".
+pub fn lru(url: &Url) -> String {
+    let mut stems = vec![format!("s:{}", url.scheme())];
+
+    if let Some(port) = url.port() {
+        stems.push(format!("t:{}", port));
+    }
+
+    match url.host() {
+        Some(Host::Domain(host)) => {
+            stems.extend(host.rsplit('.').map(|label| format!("h:{}", label)));
+        }
+        Some(host) => {
+            stems.push(format!("h:{}", host));
+        }
+        None => (),
+    }
+
+    if let Some(segments) = url.path_segments() {
+        stems.extend(
+            segments
+                .filter(|segment| !segment.is_empty())
+                .map(|segment| format!("p:{}", segment)),
+        );
+    }
+
+    if let Some(query) = url.query() {
+        stems.push(format!("q:{}", query));
+    }
+
+    if let Some(fragment) = url.fragment() {
+        stems.push(format!("f:{}", fragment));
+    }
+
+    let mut key = stems.join("|");
+    key.push('|');
+
+    key
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn url(string: &str) -> Url {
+        parse(string).unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            url("lemonde.fr/article").as_str(),
+            "http://lemonde.fr/article"
+        );
+        assert_eq!(url("localhost:8080/x").as_str(), "http://localhost:8080/x");
+        assert_eq!(url("lemonde.fr:80/a").as_str(), "http://lemonde.fr/a");
+        assert_eq!(url("example.com:8000").as_str(), "http://example.com:8000/");
+        assert_eq!(url("mailto:john@example.com").scheme(), "mailto");
+        assert_eq!(url("https://lemonde.fr:8080").port(), Some(8080));
+        assert_eq!(parse("http://"), None);
+    }
+
+    #[test]
+    fn test_registered_domain() {
+        assert_eq!(
+            registered_domain(&url("https://www.lemonde.fr/article")),
+            Some("lemonde.fr".to_string())
+        );
+        assert_eq!(
+            registered_domain(&url("http://news.bbc.co.uk")),
+            Some("bbc.co.uk".to_string())
+        );
+        assert_eq!(
+            registered_domain(&url("https://someone.blogspot.com/post")),
+            Some("someone.blogspot.com".to_string())
+        );
+        assert_eq!(registered_domain(&url("http://127.0.0.1:8000")), None);
+        assert_eq!(registered_domain(&url("http://localhost:8000")), None);
+        assert_eq!(registered_domain(&url("http://co.uk")), None);
+        assert_eq!(
+            registered_domain(&url("http://a.b.example.unknowntld")),
+            Some("example.unknowntld".to_string())
+        );
+        assert_eq!(
+            registered_domain(&url("http://www.city.kawasaki.jp")),
+            Some("city.kawasaki.jp".to_string())
+        );
+        assert_eq!(
+            registered_domain(&url("http://a.b.test.kawasaki.jp")),
+            Some("b.test.kawasaki.jp".to_string())
+        );
+        assert_eq!(
+            registered_domain(&url("http://www.xn--55qx5d.cn")),
+            Some("www.xn--55qx5d.cn".to_string())
+        );
+        assert_eq!(
+            registered_domain(&url("http://www.公司.cn")),
+            Some("www.xn--55qx5d.cn".to_string())
+        );
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(
+            normalize(url(
+                "https://WWW.LeMonde.fr/article/?utm_source=twitter&id=4#top"
+            )),
+            "https://lemonde.fr/article?id=4"
+        );
+        assert_eq!(normalize(url("http://example.com/")), "http://example.com");
+        assert_eq!(
+            normalize(url("http://example.com/path/?fbclid=test")),
+            "http://example.com/path"
+        );
+        assert_eq!(
+            normalize(url("http://example.com/?q=hello%20world&utm_medium=social")),
+            "http://example.com/?q=hello%20world"
+        );
+        assert_eq!(
+            normalize(url("http://example.com/?b=%7E&a=1")),
+            "http://example.com/?b=%7E&a=1"
+        );
+    }
+
+    #[test]
+    fn test_is_shortened() {
+        assert!(is_shortened(&url("https://bit.ly/3xyz")));
+        assert!(is_shortened(&url("t.co/abc")));
+        assert!(!is_shortened(&url("https://lemonde.fr")));
+    }
+
+    #[test]
+    fn test_infer_redirection() {
+        assert_eq!(
+            infer_redirection(&url("https://youtu.be/dQw4w9WgXcQ")).map(String::from),
+            Some("https://www.youtube.com/watch?v=dQw4w9WgXcQ".to_string())
+        );
+        assert_eq!(
+            infer_redirection(&url(
+                "https://l.facebook.com/l.php?u=https%3A%2F%2Flemonde.fr%2Farticle&h=AT0"
+            ))
+            .map(String::from),
+            Some("https://lemonde.fr/article".to_string())
+        );
+        assert_eq!(
+            infer_redirection(&url("https://www.google.fr/url?q=http://example.com/&sa=U"))
+                .map(String::from),
+            Some("http://example.com/".to_string())
+        );
+        assert_eq!(infer_redirection(&url("https://lemonde.fr")), None);
+    }
+
+    #[test]
+    fn test_lru() {
+        assert_eq!(
+            lru(&url(
+                "http://www.lemonde.fr:8000/article/1234/index.html?field=value#2"
+            )),
+            "s:http|t:8000|h:fr|h:lemonde|h:www|p:article|p:1234|p:index.html|q:field=value|f:2|"
+        );
+        assert_eq!(lru(&url("https://lemonde.fr")), "s:https|h:fr|h:lemonde|");
+    }
+}
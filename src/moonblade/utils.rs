@@ -1,3 +1,7 @@
+use std::sync::Arc;
+
+use super::types::DynamicValue;
+
 const LARGE_EPSILON: f64 = f64::EPSILON * 2.0;
 
 // NOTE: this is not equivalent to casting since it will return None
@@ -12,10 +16,78 @@ pub fn downgrade_float(f: f64) -> Option<i64> {
     }
 }
 
+const ARGUMENT_CACHE_CAPACITY: usize = 8;
+
+fn arc_address(value: &DynamicValue) -> Option<*const ()> {
+    Some(match value {
+        DynamicValue::List(list) => Arc::as_ptr(list) as *const (),
+        DynamicValue::Map(map) => Arc::as_ptr(map) as *const (),
+        DynamicValue::String(string) => Arc::as_ptr(string) as *const (),
+        DynamicValue::Bytes(bytes) => Arc::as_ptr(bytes) as *const (),
+        _ => return None,
+    })
+}
+
+// NOTE: some functions must parse costly arguments, e.g. a list of CIDR ranges
+// or a polygon, that are most of the time literals or statically evaluated,
+// and therefore shared across rows through the same Arc. So we can cache what
+// was parsed from them using the Arc address as key. Cached values are kept
+// alive by the cache so that their address cannot be reused meanwhile.
+pub struct ArgumentCache<T> {
+    entries: Vec<(DynamicValue, Arc<T>)>,
+}
+
+impl<T> ArgumentCache<T> {
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::with_capacity(ARGUMENT_CACHE_CAPACITY),
+        }
+    }
+
+    pub fn get_or_try_insert_with<F, E>(
+        &mut self,
+        value: &DynamicValue,
+        parse: F,
+    ) -> Result<Arc<T>, E>
+    where
+        F: FnOnce(&DynamicValue) -> Result<T, E>,
+    {
+        let address = match arc_address(value) {
+            None => return parse(value).map(Arc::new),
+            Some(address) => address,
+        };
+
+        // NOTE: least recently used entries come first
+        if let Some(i) = self
+            .entries
+            .iter()
+            .position(|(cached, _)| arc_address(cached) == Some(address))
+        {
+            let entry = self.entries.remove(i);
+            let parsed = entry.1.clone();
+            self.entries.push(entry);
+
+            return Ok(parsed);
+        }
+
+        let parsed = Arc::new(parse(value)?);
+
+        if self.entries.len() == ARGUMENT_CACHE_CAPACITY {
+            self.entries.remove(0);
+        }
+
+        self.entries.push((value.clone(), parsed.clone()));
+
+        Ok(parsed)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    use std::cell::Cell;
+
     #[test]
     fn test_downgrade_float() {
         assert_eq!(downgrade_float(0.0), Some(0i64));
@@ -27,4 +99,36 @@ mod tests {
         assert_eq!(downgrade_float(1.0 + f64::EPSILON), Some(1i64));
         assert_eq!(downgrade_float(1.0 - f64::EPSILON), Some(1i64));
     }
+
+    #[test]
+    fn test_argument_cache() {
+        let mut cache: ArgumentCache<usize> = ArgumentCache::new();
+        let calls = Cell::new(0);
+
+        let parse = |value: &DynamicValue| -> Result<usize, ()> {
+            calls.set(calls.get() + 1);
+            Ok(value.try_as_str().unwrap().len())
+        };
+
+        let literal = DynamicValue::from("test");
+
+        assert_eq!(*cache.get_or_try_insert_with(&literal, parse).unwrap(), 4);
+        assert_eq!(
+            *cache
+                .get_or_try_insert_with(&literal.clone(), parse)
+                .unwrap(),
+            4
+        );
+
+        // NOTE: an equal value with a different address is parsed again
+        let other = DynamicValue::from("test");
+        assert_eq!(*cache.get_or_try_insert_with(&other, parse).unwrap(), 4);
+
+        // NOTE: values that are not backed by an Arc are never cached
+        let number = DynamicValue::from(3);
+        assert_eq!(*cache.get_or_try_insert_with(&number, parse).unwrap(), 1);
+        assert_eq!(*cache.get_or_try_insert_with(&number, parse).unwrap(), 1);
+
+        assert_eq!(calls.get(), 4);
+    }
 }
@@ -623,6 +623,10 @@ impl Selection {
     pub fn subtract(&mut self, other: &Self) {
         self.0.retain(|i| !other.contains(*i));
     }
+
+    pub fn remove(&mut self, index: usize) {
+        self.0.retain(|i| *i != index);
+    }
 }
 
 impl ops::Deref for Selection {
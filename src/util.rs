@@ -772,6 +772,26 @@ impl<'a> ImmutableRecordHelpers<'a> for csv::StringRecord {
     }
 }
 
+// NOTE: empty weights are returned as `None` so that the related rows can be
+// ignored by the caller.
+pub fn parse_weight(cell: &[u8]) -> Result<Option<f64>, String> {
+    if cell.is_empty() {
+        return Ok(None);
+    }
+
+    let weight = std::str::from_utf8(cell)
+        .ok()
+        .and_then(|string| string.trim().parse::<f64>().ok())
+        .filter(|weight| weight.is_finite())
+        .ok_or_else(|| format!("could not parse weight {:?}", String::from_utf8_lossy(cell)))?;
+
+    if weight < 0.0 {
+        return Err(format!("weights cannot be negative, got {}", weight));
+    }
+
+    Ok(Some(weight))
+}
+
 pub fn str_to_csv_byte_record(target: &str) -> csv::ByteRecord {
     let cursor = io::Cursor::new(target);
     let reader = csv::ReaderBuilder::new()
@@ -857,6 +877,14 @@ pub fn bytes_cursor_from_read<R: io::Read>(source: &mut R) -> io::Result<io::Cur
 }
 
 // A custom implementation de/serializing ext-sort chunks as CSV
+pub struct TempFileGuard(pub String);
+
+impl Drop for TempFileGuard {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
 pub struct DeepSizedByteRecord(pub csv::ByteRecord);
 
 impl DeepSizedByteRecord {
@@ -552,3 +552,228 @@ fn agg_correlation() {
     let expected = vec![svec!["c", "r"], svec!["3.8", "0.442939783914149"]];
     assert_eq!(got, expected);
 }
+
+#[test]
+fn agg_moments() {
+    let wrk = Workdir::new("agg_moments");
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["n", "c"],
+            svec!["2", "a"],
+            svec!["8", "b"],
+            svec!["0", "a"],
+            svec!["4", "a"],
+            svec!["1", "c"],
+            svec!["9", "b"],
+            svec!["9", "a"],
+            svec!["3", "b"],
+        ],
+    );
+
+    let mut cmd = wrk.command("agg");
+    cmd.arg("skewness(n) as skew, kurtosis(n) as kurt, cv(n) as cv")
+        .arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["skew", "kurt", "cv"],
+        svec![
+            "0.22345275293294484",
+            "-1.5744680851063828",
+            "0.7617394000445604"
+        ],
+    ];
+    assert_eq!(got, expected);
+
+    test_single_agg_function(&wrk, "mad(n) as mad", "mad", "3");
+    test_single_agg_function(&wrk, "iqr(n) as iqr", "iqr", "6.5");
+    test_single_agg_function(
+        &wrk,
+        "entropy(c) as entropy",
+        "entropy",
+        "1.4056390622295665",
+    );
+    test_single_agg_function(&wrk, "gini(c) as gini", "gini", "0.59375");
+    test_single_agg_function(&wrk, "geometric_mean(n) as mean", "mean", "");
+}
+
+#[test]
+fn agg_power_means() {
+    let wrk = Workdir::new("agg_power_means");
+    wrk.create(
+        "data.csv",
+        vec![svec!["n"], svec!["1"], svec!["4"], svec!["16"]],
+    );
+
+    test_single_agg_function(&wrk, "geometric_mean(n) as mean", "mean", "4");
+    test_single_agg_function(
+        &wrk,
+        "harmonic_mean(n) as mean",
+        "mean",
+        "2.2857142857142856",
+    );
+}
+
+#[test]
+fn agg_weighted() {
+    let wrk = Workdir::new("agg_weighted");
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["n", "w"],
+            svec!["1", "2"],
+            svec!["2", "1"],
+            svec!["4", "1"],
+            svec!["", "3"],
+            svec!["5", ""],
+        ],
+    );
+
+    test_single_agg_function(&wrk, "weighted_count(n, w) as count", "count", "4");
+    test_single_agg_function(&wrk, "weighted_sum(n, w) as sum", "sum", "8");
+    test_single_agg_function(&wrk, "weighted_mean(n, w) as mean", "mean", "2");
+    test_single_agg_function(&wrk, "weighted_median(n, w) as median", "median", "1.5");
+    test_single_agg_function(&wrk, "weighted_quantile(n, w, 0.75) as q", "q", "3");
+
+    let mut cmd = wrk.command("agg");
+    cmd.arg("weighted_mean(n, w) as mean, sum(n) as sum")
+        .arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![svec!["mean", "sum"], svec!["2", "12"]];
+    assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("agg");
+    cmd.arg("weighted_mean(n)").arg("data.csv");
+    wrk.assert_err(&mut cmd);
+
+    let mut cmd = wrk.command("agg");
+    cmd.arg("weighted_mean(n, -1)").arg("data.csv");
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn agg_linreg() {
+    let wrk = Workdir::new("agg_linreg");
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["x", "y", "z"],
+            svec!["1", "3", "0"],
+            svec!["2", "4", "1"],
+            svec!["3", "3", "5"],
+            svec!["4", "7", "2"],
+            svec!["5", "8", "3"],
+        ],
+    );
+
+    test_single_agg_function(&wrk, "linreg_slope(y, x) as slope", "slope", "1.3");
+    test_single_agg_function(
+        &wrk,
+        "linreg_intercept(y, x) as i",
+        "i",
+        "1.0999999999999996",
+    );
+    test_single_agg_function(&wrk, "linreg_count(y, x) as n", "n", "5");
+    test_single_agg_function(&wrk, "linreg_multi(y, x) as coefs", "coefs", "1.1|1.3");
+
+    let mut cmd = wrk.command("agg");
+    cmd.arg("linreg_multi(1 + 2 * x - z, [x, z]) as coefs, linreg_multi_r2(1 + 2 * x - z, [x, z]) as r2")
+        .arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    assert_eq!(got[0], svec!["coefs", "r2"]);
+
+    let coefficients = got[1][0]
+        .split('|')
+        .map(|c| c.parse::<f64>().unwrap())
+        .collect::<Vec<_>>();
+
+    for (coefficient, expected) in coefficients.iter().zip([1.0, 2.0, -1.0]) {
+        assert!((coefficient - expected).abs() < 1e-9);
+    }
+
+    assert!((got[1][1].parse::<f64>().unwrap() - 1.0).abs() < 1e-9);
+
+    let mut cmd = wrk.command("agg");
+    cmd.arg("linreg_slope(y)").arg("data.csv");
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn agg_where() {
+    let wrk = Workdir::new("agg_where");
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["n", "g"],
+            svec!["1", "a"],
+            svec!["2", "a"],
+            svec!["4", "b"],
+            svec!["5", "b"],
+            svec!["8", "a"],
+        ],
+    );
+
+    let mut cmd = wrk.command("agg");
+    cmd.arg("count() as total, count() where n > 2 as big, values(n) where g eq 'b' as b, sum(n)")
+        .arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["total", "big", "b", "sum(n)"],
+        svec!["5", "3", "4|5", "20"],
+    ];
+    assert_eq!(got, expected);
+
+    test_single_agg_function(
+        &wrk,
+        "count() where g eq 'a'",
+        "count() where g eq 'a'",
+        "3",
+    );
+    test_single_agg_function(
+        &wrk,
+        "mean(n) where g eq 'a' as m",
+        "m",
+        "3.6666666666666665",
+    );
+    test_single_agg_function(&wrk, "ratio(n > 4) where g eq 'b' as r", "r", "0.5");
+}
+
+#[test]
+fn agg_star_expansion() {
+    let wrk = Workdir::new("agg_star_expansion");
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["name", "count_a", "count_b", "num_x", "other_num"],
+            svec!["john", "1", "2", "3", "4"],
+            svec!["mary", "5", "6", "7", "8"],
+        ],
+    );
+
+    let mut cmd = wrk.command("agg");
+    cmd.arg(
+        "sum(*[count_a:count_b]), max(cols(\"*_num\")) as m, mean(*[-1:]) where name eq 'john'",
+    )
+    .arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec![
+            "sum(count_a)",
+            "sum(count_b)",
+            "m_other_num",
+            "mean(other_num) where name eq 'john'"
+        ],
+        svec!["6", "8", "8", "4"],
+    ];
+    assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("agg");
+    cmd.arg("sum(cols(\"unknown_*\"))").arg("data.csv");
+
+    wrk.assert_err(&mut cmd);
+}
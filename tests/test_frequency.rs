@@ -295,3 +295,68 @@ fn frequency_all() {
     ];
     assert_eq!(got, expected);
 }
+
+#[test]
+fn frequency_weight() {
+    let wrk = Workdir::new("frequency_weight");
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["color", "weight"],
+            svec!["red", "1.5"],
+            svec!["blue", "2"],
+            svec!["red", "0.5"],
+            svec!["", "1"],
+            svec!["green", ""],
+        ],
+    );
+
+    let mut cmd = wrk.command("frequency");
+    cmd.args(["-w", "weight"]).arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["field", "value", "count"],
+        svec!["color", "blue", "2"],
+        svec!["color", "red", "2"],
+        svec!["color", "<empty>", "1"],
+    ];
+    assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("frequency");
+    cmd.args(["-w", "weight"]).args(["-l", "1"]).arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["field", "value", "count"],
+        svec!["color", "blue", "2"],
+        svec!["color", "<rest>", "3"],
+    ];
+    assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("frequency");
+    cmd.args(["-w", "weight"])
+        .args(["-t", "1.5"])
+        .arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["field", "value", "count"],
+        svec!["color", "blue", "2"],
+        svec!["color", "red", "2"],
+        svec!["color", "<rest>", "1"],
+    ];
+    assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("frequency");
+    cmd.args(["-w", "weight"])
+        .args(["-t", "2.5"])
+        .arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["field", "value", "count"],
+        svec!["color", "<rest>", "5"],
+    ];
+    assert_eq!(got, expected);
+}
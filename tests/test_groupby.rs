@@ -316,3 +316,282 @@ fn groupby_complex_keep() {
     ];
     assert_eq!(got, expected);
 }
+
+#[test]
+fn groupby_where() {
+    let wrk = Workdir::new("groupby_where");
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["id", "value"],
+            svec!["x", "1"],
+            svec!["y", "2"],
+            svec!["x", "3"],
+            svec!["y", "4"],
+            svec!["x", "5"],
+        ],
+    );
+
+    let mut cmd = wrk.command("groupby");
+    cmd.arg("id")
+        .arg("count() as count, count() where value > 2 as big, sum(value) where value < 5 as sum")
+        .arg("data.csv");
+
+    let mut got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    got[1..].sort();
+    let expected = vec![
+        svec!["id", "count", "big", "sum"],
+        svec!["x", "3", "2", "4"],
+        svec!["y", "2", "1", "6"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn groupby_rollup() {
+    let wrk = Workdir::new("groupby_rollup");
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["country", "city", "n"],
+            svec!["fr", "paris", "1"],
+            svec!["fr", "lyon", "2"],
+            svec!["fr", "paris", "3"],
+            svec!["de", "berlin", "4"],
+        ],
+    );
+
+    let mut cmd = wrk.command("groupby");
+    cmd.arg("--rollup")
+        .arg("country,city")
+        .arg("sum(n) as sum, count() as count")
+        .arg("data.csv");
+
+    let mut got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    got[1..].sort();
+    let expected = vec![
+        svec!["country", "city", "sum", "count"],
+        svec!["", "", "10", "4"],
+        svec!["de", "", "4", "1"],
+        svec!["de", "berlin", "4", "1"],
+        svec!["fr", "", "6", "3"],
+        svec!["fr", "lyon", "2", "1"],
+        svec!["fr", "paris", "4", "2"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn groupby_rollup_order() {
+    let wrk = Workdir::new("groupby_rollup_order");
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["country", "city", "n"],
+            svec!["fr", "paris", "1"],
+            svec!["fr", "lyon", "2"],
+            svec!["fr", "paris", "3"],
+            svec!["de", "berlin", "4"],
+            svec!["fr", "nice", "5"],
+        ],
+    );
+
+    // NOTE: rolled-up groups are merged in the order they were first seen
+    let mut cmd = wrk.command("groupby");
+    cmd.arg("--rollup")
+        .arg("country,city")
+        .arg("values(n) as values, first(n) as first, last(n) as last")
+        .arg("data.csv");
+
+    let mut got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    got[1..].sort();
+    let expected = vec![
+        svec!["country", "city", "values", "first", "last"],
+        svec!["", "", "1|3|2|4|5", "1", "5"],
+        svec!["de", "", "4", "4", "4"],
+        svec!["de", "berlin", "4", "4", "4"],
+        svec!["fr", "", "1|3|2|5", "1", "5"],
+        svec!["fr", "lyon", "2", "2", "2"],
+        svec!["fr", "nice", "5", "5", "5"],
+        svec!["fr", "paris", "1|3", "1", "3"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn groupby_grouping_sets() {
+    let wrk = Workdir::new("groupby_grouping_sets");
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["country", "city", "n"],
+            svec!["fr", "paris", "1"],
+            svec!["fr", "lyon", "2"],
+            svec!["fr", "paris", "3"],
+            svec!["de", "berlin", "4"],
+        ],
+    );
+
+    let mut cmd = wrk.command("groupby");
+    cmd.args(["--grouping-sets", "city;"])
+        .args(["--marker", "<all>"])
+        .arg("country,city")
+        .arg("sum(n) as sum")
+        .arg("data.csv");
+
+    let mut got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    got[1..].sort();
+    let expected = vec![
+        svec!["country", "city", "sum"],
+        svec!["<all>", "<all>", "10"],
+        svec!["<all>", "berlin", "4"],
+        svec!["<all>", "lyon", "2"],
+        svec!["<all>", "paris", "4"],
+    ];
+    assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("groupby");
+    cmd.args(["--grouping-sets", "country,city;country"])
+        .arg("country,city")
+        .arg("median(n) as median, cardinality(n) as cardinality")
+        .arg("data.csv");
+
+    let mut got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    got[1..].sort();
+    let expected = vec![
+        svec!["country", "city", "median", "cardinality"],
+        svec!["de", "", "4", "1"],
+        svec!["de", "berlin", "4", "1"],
+        svec!["fr", "", "2", "3"],
+        svec!["fr", "lyon", "2", "1"],
+        svec!["fr", "paris", "2", "2"],
+    ];
+    assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("groupby");
+    cmd.args(["--grouping-sets", "n"])
+        .arg("country,city")
+        .arg("sum(n) as sum")
+        .arg("data.csv");
+
+    wrk.assert_err(&mut cmd);
+}
+
+#[test]
+fn groupby_external() {
+    let wrk = Workdir::new("groupby_external");
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["id", "value"],
+            svec!["x", "1"],
+            svec!["y", "2"],
+            svec!["z", "3"],
+            svec!["y", "1"],
+            svec!["w", "2"],
+            svec!["z", "3"],
+            svec!["x", "4"],
+        ],
+    );
+
+    let mut cmd = wrk.command("groupby");
+    cmd.arg("--external")
+        .args(["-m", "0"])
+        .arg("id")
+        .arg("sum(value) as sum, first(value) as first, argmin(value) as argmin")
+        .arg("data.csv");
+
+    let mut got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    got[1..].sort();
+    let expected = vec![
+        svec!["id", "sum", "first", "argmin"],
+        svec!["w", "2", "2", "4"],
+        svec!["x", "5", "1", "0"],
+        svec!["y", "3", "2", "3"],
+        svec!["z", "6", "3", "2"],
+    ];
+    assert_eq!(got, expected);
+}
+
+fn check_groupby_external_against_memory(name: &str, rows: usize, groups: usize, limit: &str) {
+    let wrk = Workdir::new(name);
+
+    let mut data = vec![svec!["id", "value", "name"]];
+
+    for i in 0..rows {
+        data.push(vec![
+            format!("g{}", (i * 7919) % groups),
+            ((i * 31) % 97).to_string(),
+            format!("v{}", i % 13),
+        ]);
+    }
+
+    wrk.create("data.csv", data);
+
+    let expression = "count() as count, sum(value) as sum, first(value) as first, last(value) as last, values(name) as values, median(value) as median, mode(name) as mode, argmax(value) as argmax";
+
+    let mut cmd = wrk.command("groupby");
+    cmd.arg("id").arg(expression).arg("data.csv");
+
+    let mut expected: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    expected[1..].sort();
+
+    let mut cmd = wrk.command("groupby");
+    cmd.arg("--external")
+        .args(["-m", limit])
+        .arg("id")
+        .arg(expression)
+        .arg("data.csv");
+
+    let mut got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    got[1..].sort();
+
+    assert_eq!(got.len(), groups + 1);
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn groupby_external_recursive_spilling() {
+    // NOTE: with a limit of 0, states are spilled as soon as more than one
+    // group is in memory, so partitions are themselves spilled several times
+    check_groupby_external_against_memory("groupby_external_recursive_spilling", 600, 90, "0");
+}
+
+#[test]
+fn groupby_external_partitions() {
+    check_groupby_external_against_memory("groupby_external_partitions", 60_000, 300, "1");
+}
+
+#[test]
+fn groupby_star_expansion() {
+    let wrk = Workdir::new("groupby_star_expansion");
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["name", "count_a", "count_b"],
+            svec!["john", "1", "2"],
+            svec!["mary", "5", "6"],
+            svec!["john", "3", "1"],
+        ],
+    );
+
+    let mut cmd = wrk.command("groupby");
+    cmd.arg("name")
+        .arg("sum(cols(\"count_*\")), max(*[1:])")
+        .arg("data.csv");
+
+    let mut got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    got[1..].sort();
+    let expected = vec![
+        svec![
+            "name",
+            "sum(count_a)",
+            "sum(count_b)",
+            "max(count_a)",
+            "max(count_b)"
+        ],
+        svec!["john", "4", "3", "3", "2"],
+        svec!["mary", "5", "6", "5", "6"],
+    ];
+    assert_eq!(got, expected);
+}
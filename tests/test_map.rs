@@ -152,3 +152,66 @@ fn map_errors_log() {
     ];
     assert_eq!(got, expected);
 }
+
+#[test]
+fn map_cols_pattern() {
+    let wrk = Workdir::new("map_cols_pattern");
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["count_a", "count_b", "name"],
+            svec!["1", "2", "john"],
+            svec!["3", "4", "mary"],
+        ],
+    );
+    let mut cmd = wrk.command("map");
+    cmd.arg("sum(cols('count_*'))").arg("total").arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["count_a", "count_b", "name", "total"],
+        svec!["1", "2", "john", "3"],
+        svec!["3", "4", "mary", "7"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn map_cols_pattern_exact_name() {
+    let wrk = Workdir::new("map_cols_pattern_exact_name");
+    wrk.create(
+        "data.csv",
+        vec![svec!["ab", "a*"], svec!["1", "2"], svec!["3", "4"]],
+    );
+    let mut cmd = wrk.command("map");
+    cmd.arg("join(cols('a*'), '|')").arg("c").arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["ab", "a*", "c"],
+        svec!["1", "2", "2"],
+        svec!["3", "4", "4"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn map_concat_list() {
+    let wrk = Workdir::new("map_concat_list");
+    wrk.create(
+        "data.csv",
+        vec![svec!["a", "b"], svec!["1,2", "3"], svec!["4", "5"]],
+    );
+    let mut cmd = wrk.command("map");
+    cmd.arg("join(concat(split(a, ','), b), '|')")
+        .arg("r")
+        .arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["a", "b", "r"],
+        svec!["1,2", "3", "1|2|3"],
+        svec!["4", "5", "4|5"],
+    ];
+    assert_eq!(got, expected);
+}
@@ -0,0 +1,99 @@
+use crate::workdir::Workdir;
+
+fn data() -> Vec<Vec<String>> {
+    vec![
+        svec!["country", "year_2023", "year_2024"],
+        svec!["fr", "10", "15"],
+        svec!["de", "7", ""],
+    ]
+}
+
+#[test]
+fn melt() {
+    let wrk = Workdir::new("melt");
+    wrk.create("data.csv", data());
+
+    let mut cmd = wrk.command("melt");
+    cmd.arg("year_*").arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["country", "variable", "value"],
+        svec!["fr", "year_2023", "10"],
+        svec!["fr", "year_2024", "15"],
+        svec!["de", "year_2023", "7"],
+        svec!["de", "year_2024", ""],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn melt_names_and_drop_empty() {
+    let wrk = Workdir::new("melt_names_and_drop_empty");
+    wrk.create("data.csv", data());
+
+    let mut cmd = wrk.command("melt");
+    cmd.arg("year_*")
+        .args(["--variable-name", "year"])
+        .args(["--value-name", "count"])
+        .arg("--drop-empty")
+        .arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["country", "year", "count"],
+        svec!["fr", "year_2023", "10"],
+        svec!["fr", "year_2024", "15"],
+        svec!["de", "year_2023", "7"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn melt_extract() {
+    let wrk = Workdir::new("melt_extract");
+    wrk.create("data.csv", data());
+
+    let mut cmd = wrk.command("melt");
+    cmd.arg("year_*")
+        .args(["--extract", r"\d+"])
+        .arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["country", "variable", "value"],
+        svec!["fr", "2023", "10"],
+        svec!["fr", "2024", "15"],
+        svec!["de", "2023", "7"],
+        svec!["de", "2024", ""],
+    ];
+    assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("melt");
+    cmd.arg("year_*")
+        .args(["--extract", r"^year_(\d{2})"])
+        .arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["country", "variable", "value"],
+        svec!["fr", "20", "10"],
+        svec!["fr", "20", "15"],
+        svec!["de", "20", "7"],
+        svec!["de", "20", ""],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn melt_no_headers() {
+    let wrk = Workdir::new("melt_no_headers");
+    wrk.create("data.csv", vec![svec!["fr", "10", "15"]]);
+
+    let mut cmd = wrk.command("melt");
+    cmd.arg("1:").arg("--no-headers").arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![svec!["fr", "1", "10"], svec!["fr", "2", "15"]];
+    assert_eq!(got, expected);
+}
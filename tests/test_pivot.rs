@@ -0,0 +1,102 @@
+use crate::workdir::Workdir;
+
+fn data() -> Vec<Vec<String>> {
+    vec![
+        svec!["country", "year", "count"],
+        svec!["fr", "2023", "10"],
+        svec!["fr", "2024", "12"],
+        svec!["de", "2023", "7"],
+        svec!["fr", "2024", "3"],
+        svec!["it", "2022", "1"],
+    ]
+}
+
+#[test]
+fn pivot() {
+    let wrk = Workdir::new("pivot");
+    wrk.create("data.csv", data());
+
+    let mut cmd = wrk.command("pivot");
+    cmd.arg("year")
+        .arg("sum(count)")
+        .args(["-g", "country"])
+        .arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["country", "2023", "2024", "2022"],
+        svec!["fr", "10", "15", ""],
+        svec!["de", "7", "", ""],
+        svec!["it", "", "", "1"],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn pivot_no_groupby() {
+    let wrk = Workdir::new("pivot_no_groupby");
+    wrk.create("data.csv", data());
+
+    let mut cmd = wrk.command("pivot");
+    cmd.arg("year").arg("count()").arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![svec!["2023", "2024", "2022"], svec!["2", "2", "1"]];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn pivot_multiple_aggregations() {
+    let wrk = Workdir::new("pivot_multiple_aggregations");
+    wrk.create("data.csv", data());
+
+    let mut cmd = wrk.command("pivot");
+    cmd.arg("year")
+        .arg("sum(count) as sum, count() as n")
+        .args(["-g", "country"])
+        .args(["-l", "2"])
+        .arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["country", "2023_sum", "2023_n", "2024_sum", "2024_n"],
+        svec!["fr", "10", "1", "15", "2"],
+        svec!["de", "7", "1", "", ""],
+        svec!["it", "", "", "", ""],
+    ];
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn pivot_column_order() {
+    let wrk = Workdir::new("pivot_column_order");
+    wrk.create("data.csv", data());
+
+    let mut cmd = wrk.command("pivot");
+    cmd.arg("year")
+        .arg("first(count)")
+        .args(["-g", "country"])
+        .args(["--column-order", "asc"])
+        .args(["--fill", "0"])
+        .arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["country", "2022", "2023", "2024"],
+        svec!["fr", "0", "10", "12"],
+        svec!["de", "0", "7", "0"],
+        svec!["it", "1", "0", "0"],
+    ];
+    assert_eq!(got, expected);
+
+    let mut cmd = wrk.command("pivot");
+    cmd.arg("year")
+        .arg("first(count)")
+        .args(["--column-order", "count"])
+        .args(["--limit", "1"])
+        .arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![svec!["2023"], svec!["10"]];
+    assert_eq!(got, expected);
+}
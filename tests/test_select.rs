@@ -206,6 +206,29 @@ fn select_evaluate() {
     assert_eq!(got, expected);
 }
 
+#[test]
+fn select_evaluate_star() {
+    let wrk = Workdir::new("select_evaluate_star");
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["name", "count1", "count2"],
+            svec!["john", "2", "3"],
+            svec!["mary", "5", "7"],
+        ],
+    );
+    let mut cmd = wrk.command("select");
+    cmd.arg("-e").arg("*[count1:], name as n").arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["count1", "count2", "n"],
+        svec!["2", "3", "john"],
+        svec!["5", "7", "mary"],
+    ];
+    assert_eq!(got, expected);
+}
+
 #[test]
 fn select_append() {
     let wrk = Workdir::new("select_append");
@@ -118,6 +118,17 @@ fn get_field_value(wrk: &Workdir, cmd: &mut process::Command, field: &str) -> St
     if field == "mode" {
         cmd.arg("--cardinality");
     }
+    if [
+        "cv",
+        "skewness",
+        "kurtosis",
+        "geometric_mean",
+        "harmonic_mean",
+    ]
+    .contains(&field)
+    {
+        cmd.arg("--moments");
+    }
 
     let mut rows: Vec<Vec<String>> = wrk.read_stdout(cmd);
     let headers = rows.remove(0);
@@ -237,6 +248,36 @@ stats_tests!(
     "2.5"
 );
 stats_tests!(stats_median_mix, "median", &["1", "2.5", "3"], "2.5");
+stats_tests!(
+    stats_skewness,
+    "skewness",
+    &["1", "2", "3", "10"],
+    "1.0182337649086284"
+);
+stats_tests!(
+    stats_kurtosis,
+    "kurtosis",
+    &["1", "2", "3", "10"],
+    "-0.7696000000000001"
+);
+stats_tests!(
+    stats_geometric_mean,
+    "geometric_mean",
+    &["1", "4", "16"],
+    "4"
+);
+stats_tests!(
+    stats_geometric_mean_null,
+    "geometric_mean",
+    &["", "1", "4", "16"],
+    "4"
+);
+stats_tests!(
+    stats_geometric_mean_zero,
+    "geometric_mean",
+    &["0", "4", "16"],
+    ""
+);
 
 mod stats_infer_nothing {
     // Only test CSV data with headers.
@@ -270,3 +311,152 @@ mod stats_header_fields {
     stats_test_headers!(stats_header_field_name, "field", &["a"], "header");
     stats_test_no_headers!(stats_header_no_field_name, "field", &["a"], "0");
 }
+
+#[test]
+fn stats_weight() {
+    let wrk = Workdir::new("stats_weight");
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["n", "w"],
+            svec!["1", "2"],
+            svec!["2", "1"],
+            svec!["4", "1"],
+            svec!["5", ""],
+        ],
+    );
+
+    let mut cmd = wrk.command("stats");
+    cmd.args(["-w", "w"])
+        .arg("-q")
+        .args(["-s", "n"])
+        .arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec!["field", "count", "sum", "mean", "q1", "median", "q3", "max"],
+        svec!["n", "3", "8", "2", "1", "1.5", "3", "4"],
+    ];
+
+    let select = |row: &Vec<String>| {
+        expected[0]
+            .iter()
+            .map(|h| row[got[0].iter().position(|c| c == h).unwrap()].clone())
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(got.iter().map(select).collect::<Vec<_>>(), expected);
+}
+
+#[test]
+fn stats_temporal() {
+    let wrk = Workdir::new("stats_temporal");
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["date", "month", "name"],
+            svec!["2023-01-05", "2023-01", "john"],
+            svec!["2024-03-02", "2023-05", "mary"],
+            svec!["unknown", "2024-01", "lucy"],
+            svec!["2023-11-30T10:30:00", "", "bob"],
+        ],
+    );
+
+    let mut cmd = wrk.command("stats");
+    cmd.arg("-T").arg("data.csv");
+
+    let got: Vec<Vec<String>> = wrk.read_stdout(&mut cmd);
+    let expected = vec![
+        svec![
+            "field",
+            "earliest",
+            "latest",
+            "granularity",
+            "span_days",
+            "per_year",
+            "invalid_dates"
+        ],
+        svec![
+            "date",
+            "2023-01-05T00:00:00Z",
+            "2024-03-02T00:00:00Z",
+            "minute",
+            "422",
+            "2023:2|2024:1",
+            "0.25"
+        ],
+        svec![
+            "month",
+            "2023-01",
+            "2024-01",
+            "month",
+            "365",
+            "2023:2|2024:1",
+            "0"
+        ],
+        svec!["name", "", "", "", "", "", ""],
+    ];
+
+    let select = |row: &Vec<String>| {
+        expected[0]
+            .iter()
+            .map(|h| row[got[0].iter().position(|c| c == h).unwrap()].clone())
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(got.iter().map(select).collect::<Vec<_>>(), expected);
+}
+
+#[test]
+fn stats_temporal_types_and_offsets() {
+    let wrk = Workdir::new("stats_temporal_types_and_offsets");
+    wrk.create(
+        "data.csv",
+        vec![
+            svec!["month", "datetime"],
+            svec!["2023-04", "2023-04-12T14:05:12Z"],
+            svec!["2023-05", "2023-04-12T14:05:12+02:00"],
+        ],
+    );
+
+    let select = |got: Vec<Vec<String>>, headers: &[&str]| {
+        got.iter()
+            .map(|row| {
+                headers
+                    .iter()
+                    .map(|h| row[got[0].iter().position(|c| c == h).unwrap()].clone())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let mut cmd = wrk.command("stats");
+    cmd.arg("data.csv");
+
+    let without_temporal = select(wrk.read_stdout(&mut cmd), &["field", "type"]);
+
+    let mut cmd = wrk.command("stats");
+    cmd.arg("-T").arg("data.csv");
+
+    let got = select(
+        wrk.read_stdout(&mut cmd),
+        &["field", "type", "earliest", "latest", "granularity"],
+    );
+    let expected = vec![
+        svec!["field", "type", "earliest", "latest", "granularity"],
+        svec!["month", "string", "2023-04", "2023-05", "month"],
+        svec![
+            "datetime",
+            "string",
+            "2023-04-12T12:05:12Z",
+            "2023-04-12T14:05:12Z",
+            "second"
+        ],
+    ];
+
+    assert_eq!(got, expected);
+    assert_eq!(
+        without_temporal,
+        got.iter().map(|row| row[..2].to_vec()).collect::<Vec<_>>()
+    );
+}
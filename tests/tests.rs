@@ -39,9 +39,11 @@ mod test_implode;
 mod test_index;
 mod test_join;
 mod test_map;
+mod test_melt;
 mod test_merge;
 mod test_parallel;
 mod test_partition;
+mod test_pivot;
 mod test_range;
 mod test_regex_join;
 mod test_rename;